@@ -0,0 +1,211 @@
+//! The registry of diagnostic codes.
+//!
+//! Every kind of diagnostic has a stable code (e.g. `E0001`), whose long-form
+//! explanation is returned by [`explain`]. Codes are never reused.
+
+use helios_formatting::FormattedString;
+
+/// A program is missing an expected token (e.g. a closing parenthesis).
+pub const MISSING_KIND: &str = "E0001";
+
+/// A token was found where a different token was expected.
+pub const UNEXPECTED_KIND: &str = "E0002";
+
+/// A character that can't start any token was found.
+pub const UNKNOWN_CHARACTER: &str = "E0003";
+
+/// A line is indented to a level that doesn't match any enclosing block.
+pub const INVALID_INDENTATION: &str = "E0004";
+
+/// A character that looks like a symbol (e.g. a Greek question mark instead
+/// of a semicolon) was found.
+pub const CONFUSABLE_CHARACTER: &str = "E0005";
+
+/// A name was used that isn't bound by any `let` in scope.
+pub const UNRESOLVED_NAME: &str = "E0006";
+
+/// An expression doesn't have the type its context requires (e.g. a
+/// comparison is added to a number).
+pub const MISMATCHED_TYPES: &str = "E0007";
+
+/// The long-form explanation of a diagnostic code.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Explanation {
+    pub code: &'static str,
+    pub title: &'static str,
+    pub explanation: FormattedString,
+}
+
+/// A registered code, its title and a function building its explanation.
+type Entry = (&'static str, &'static str, fn() -> FormattedString);
+
+/// Every registered code.
+const REGISTRY: &[Entry] = &[
+    (MISSING_KIND, "Missing token", missing_kind),
+    (UNEXPECTED_KIND, "Unexpected token", unexpected_kind),
+    (UNKNOWN_CHARACTER, "Unknown character", unknown_character),
+    (
+        INVALID_INDENTATION,
+        "Invalid indentation",
+        invalid_indentation,
+    ),
+    (
+        CONFUSABLE_CHARACTER,
+        "Confusable character",
+        confusable_character,
+    ),
+    (UNRESOLVED_NAME, "Unresolved name", unresolved_name),
+    (MISMATCHED_TYPES, "Mismatched types", mismatched_types),
+];
+
+/// Returns the explanation of the given code (ignoring its case), or `None`
+/// if there is no such code.
+pub fn explain(code: &str) -> Option<Explanation> {
+    REGISTRY
+        .iter()
+        .find(|(it, ..)| it.eq_ignore_ascii_case(code))
+        .map(|(code, title, explanation)| Explanation {
+            code,
+            title,
+            explanation: explanation(),
+        })
+}
+
+/// Returns the title of the given code, or `None` if there is no such code.
+pub fn title(code: &str) -> Option<&'static str> {
+    REGISTRY
+        .iter()
+        .find(|(it, ..)| it.eq_ignore_ascii_case(code))
+        .map(|(_, title, _)| *title)
+}
+
+/// Returns every registered code in ascending order.
+pub fn all() -> impl Iterator<Item = &'static str> {
+    REGISTRY.iter().map(|(code, ..)| *code)
+}
+
+fn missing_kind() -> FormattedString {
+    FormattedString::new()
+        .text(
+            "A token that must appear at a particular place in a program \
+             could not be found. For example, every opening parenthesis must \
+             be followed by a matching closing parenthesis:",
+        )
+        .code_block("let a = (1 + 2")
+        .text("To fix this error, add the missing token:")
+        .code_block("let a = (1 + 2)")
+}
+
+fn unexpected_kind() -> FormattedString {
+    FormattedString::new()
+        .text(
+            "A token was found where it can't be used. This usually happens \
+             when an expression is incomplete, or when a keyword is used \
+             where a name is expected:",
+        )
+        .code_block("let let = 1")
+        .text("The diagnostic lists every token that would have been valid. ")
+        .text("In this case, the binding needs a name that isn't reserved:")
+        .code_block("let value = 1")
+}
+
+fn unknown_character() -> FormattedString {
+    FormattedString::new()
+        .text(
+            "A character that can't start any token was found outside of a \
+             string literal, character literal or comment:",
+        )
+        .code_block("let price = 10 $")
+        .text("Remove the character, or move it into a string literal if ")
+        .text("it was meant to be text:")
+        .code_block("let price = \"10 $\"")
+}
+
+fn invalid_indentation() -> FormattedString {
+    FormattedString::new()
+        .text(
+            "Helios uses indentation to group lines into blocks. When a line \
+             is dedented, it must line up with one of the blocks that \
+             encloses it:",
+        )
+        .code_block("let a =\n    1\n  + 2")
+        .text("Indent the line to the same level as the block it belongs to.")
+}
+
+fn confusable_character() -> FormattedString {
+    FormattedString::new()
+        .text(
+            "A character that looks like one of the symbols Helios is \
+             written with was found. This usually happens when code is \
+             pasted from a word processor (which turns quotes into smart \
+             quotes) or typed with an input method that produces full-width \
+             characters:",
+        )
+        .code_block("let a = 1 \u{FF0B} 2")
+        .text("The character above is a full-width plus sign rather than ")
+        .code("+")
+        .text(". Replace it with the symbol it looks like:")
+        .code_block("let a = 1 + 2")
+        .text("Running ")
+        .code("helios fix")
+        .text(" replaces every such character automatically.")
+}
+
+fn unresolved_name() -> FormattedString {
+    FormattedString::new()
+        .text(
+            "A name was used, but no binding with that name is in scope. \
+             This is usually a typo:",
+        )
+        .code_block("let count = 1\nlet total = cuont + 1")
+        .text("The name ")
+        .code("cuont")
+        .text(" isn't bound to anything. Use the name of the binding instead:")
+        .code_block("let count = 1\nlet total = count + 1")
+        .text("Otherwise, declare a binding with that name using ")
+        .code("let")
+        .text(".")
+}
+
+fn mismatched_types() -> FormattedString {
+    FormattedString::new()
+        .text(
+            "An expression was used where a value of another type is \
+             expected. Both sides of an operator must have the same type, so \
+             a number can't be compared with the result of a comparison:",
+        )
+        .code_block("let a = (1 < 2) = 3")
+        .text("Types are inferred, so no annotation is needed: the type of ")
+        .code("(1 < 2)")
+        .text(" is ")
+        .code("Bool")
+        .text(" and the type of ")
+        .code("3")
+        .text(" is ")
+        .code("Int")
+        .text(". To fix this error, make both sides the same type:")
+        .code_block("let a = (1 < 2) = (3 < 4)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_codes_are_unique_and_sorted() {
+        let codes = all().collect::<Vec<_>>();
+        let mut sorted = codes.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(codes, sorted);
+    }
+
+    #[test]
+    fn test_explain() {
+        let explanation = explain("e0001").unwrap();
+        assert_eq!(explanation.code, MISSING_KIND);
+        assert_eq!(explanation.title, "Missing token");
+        assert!(!explanation.explanation.render_plain().is_empty());
+        assert_eq!(explain("E9999"), None);
+    }
+}
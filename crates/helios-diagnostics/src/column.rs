@@ -0,0 +1,176 @@
+//! Converting between byte offsets and columns.
+//!
+//! What a column is depends on who is asking (see [`ColumnKind`]): users
+//! count characters, LSP clients count UTF-16 code units and terminals count
+//! display cells.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// How the columns of a line are counted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColumnKind {
+    /// Every Unicode scalar value is a column.
+    Chars,
+    /// Every UTF-16 code unit is a column (as in the Language Server
+    /// Protocol).
+    Utf16,
+    /// Every cell of a terminal is a column, where tabs advance to the next
+    /// multiple of `tab_width`.
+    Display { tab_width: usize },
+}
+
+impl ColumnKind {
+    /// The number of columns taken by a unit (i.e. a character, or a grapheme
+    /// cluster when counting display cells) starting at the given column.
+    fn width(self, unit: &str, column: usize) -> usize {
+        match self {
+            Self::Chars => unit.chars().count(),
+            Self::Utf16 => unit.encode_utf16().count(),
+            Self::Display { tab_width } if unit == "\t" => {
+                let tab_width = tab_width.max(1);
+                tab_width - column % tab_width
+            }
+            // Wide emoji sequences (e.g. joined with a zero-width joiner) are
+            // drawn as a single wide character by most terminals
+            Self::Display { .. } => unit.width().min(2),
+        }
+    }
+
+    /// The units of the given text and the byte offsets they start at.
+    fn units(self, text: &str) -> Box<dyn Iterator<Item = (usize, &str)> + '_> {
+        match self {
+            Self::Display { .. } => Box::new(text.grapheme_indices(true)),
+            Self::Chars | Self::Utf16 => Box::new(
+                text.char_indices()
+                    .map(move |(i, c)| (i, &text[i..i + c.len_utf8()])),
+            ),
+        }
+    }
+}
+
+/// The column the given byte offset of a line is at (zero-indexed).
+///
+/// The line should not include its line feed, although an offset pointing at
+/// it (or past it) is given the column right after the last character.
+pub fn column_at(line: &str, byte_offset: usize, kind: ColumnKind) -> usize {
+    let mut column = 0;
+
+    for (start, unit) in kind.units(line) {
+        if start + unit.len() > byte_offset {
+            break;
+        }
+        column += kind.width(unit, column);
+    }
+
+    column
+}
+
+/// The byte offset of the given column of a line (zero-indexed).
+///
+/// A column inside a unit (e.g. the second column of a wide character or
+/// between the code units of a surrogate pair) is the offset of the unit.
+pub fn offset_at_column(line: &str, column: usize, kind: ColumnKind) -> usize {
+    let mut current = 0;
+
+    for (start, unit) in kind.units(line) {
+        let width = kind.width(unit, current);
+        if current + width > column {
+            return start;
+        }
+        current += width;
+    }
+
+    line.len()
+}
+
+/// Expands the tabs of the given text (which starts at the given display
+/// column) to spaces, returning the expanded text and the display column it
+/// ends at.
+pub fn expand_tabs(
+    text: &str,
+    start_column: usize,
+    tab_width: usize,
+) -> (String, usize) {
+    let kind = ColumnKind::Display { tab_width };
+    let mut expanded = String::with_capacity(text.len());
+    let mut column = start_column;
+
+    for (_, unit) in kind.units(text) {
+        let width = kind.width(unit, column);
+        if unit == "\t" {
+            expanded.push_str(&" ".repeat(width));
+        } else {
+            expanded.push_str(unit);
+        }
+        column += width;
+    }
+
+    (expanded, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DISPLAY: ColumnKind = ColumnKind::Display { tab_width: 4 };
+
+    #[test]
+    fn test_column_at() {
+        let line = "a世\tb😀e\u{301}!";
+        let offsets = [0, 1, 4, 5, 6, 10, 13, 14];
+
+        let columns = |kind| {
+            offsets
+                .iter()
+                .map(|&offset| column_at(line, offset, kind))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(columns(ColumnKind::Chars), [0, 1, 2, 3, 4, 5, 7, 8]);
+        assert_eq!(columns(ColumnKind::Utf16), [0, 1, 2, 3, 4, 6, 8, 9]);
+        assert_eq!(columns(DISPLAY), [0, 1, 3, 4, 5, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_column_at_inside_a_character() {
+        // The second byte of `世` is still at its column
+        assert_eq!(column_at("a世b", 2, ColumnKind::Chars), 1);
+        // So is the combining accent of `é` when counting display cells
+        assert_eq!(column_at("e\u{301}x", 1, DISPLAY), 0);
+        assert_eq!(column_at("e\u{301}x", 3, DISPLAY), 1);
+    }
+
+    #[test]
+    fn test_offset_at_column() {
+        let line = "a世\tb😀!";
+        let offsets = |kind, columns: &[usize]| {
+            columns
+                .iter()
+                .map(|&column| offset_at_column(line, column, kind))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            offsets(ColumnKind::Chars, &[0, 1, 2, 3, 4, 5, 99]),
+            [0, 1, 4, 5, 6, 10, 11]
+        );
+        // Column 5 is between the two code units of `😀`
+        assert_eq!(
+            offsets(ColumnKind::Utf16, &[3, 4, 5, 6, 7]),
+            [5, 6, 6, 10, 11]
+        );
+        // Column 2 is the second cell of `世`, and column 3 is the tab
+        assert_eq!(
+            offsets(DISPLAY, &[1, 2, 3, 4, 5, 6, 7, 8]),
+            [1, 1, 4, 5, 6, 6, 10, 11]
+        );
+    }
+
+    #[test]
+    fn test_expand_tabs() {
+        assert_eq!(expand_tabs("\ta\tb", 0, 4), ("    a   b".to_string(), 9));
+        assert_eq!(expand_tabs("世\t", 1, 4), ("世 ".to_string(), 4));
+        assert_eq!(expand_tabs("\t", 0, 0), (" ".to_string(), 1));
+    }
+}
@@ -0,0 +1,183 @@
+//! Configuring how diagnostics are rendered (see [`EmitConfig`]).
+
+use colored::Color;
+use helios_locale::Locale;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// When to use colors.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ColorChoice {
+    /// Always use colors.
+    Always,
+    /// Never use colors.
+    Never,
+    /// Use colors if stdout is a terminal, unless `NO_COLOR` is set (or
+    /// `CLICOLOR_FORCE` is set, in which case colors are always used).
+    #[default]
+    Auto,
+}
+
+impl ColorChoice {
+    /// Determines if colors should be used with this choice.
+    pub fn should_colorize(self) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Auto => colored::control::SHOULD_COLORIZE.should_colorize(),
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            "auto" => Ok(Self::Auto),
+            _ => Err(format!(
+                "unknown color choice `{s}` (expected `always`, `never` or \
+                 `auto`)"
+            )),
+        }
+    }
+}
+
+/// How diagnostics are rendered by [`emit`](crate::emit).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EmitConfig {
+    pub color: ColorChoice,
+    /// The width text is wrapped at, or `None` to use the width of the
+    /// terminal.
+    pub width: Option<usize>,
+    /// The number of columns between tab stops when tabs are expanded.
+    pub tab_width: usize,
+    /// Whether snippets are drawn with ASCII characters only (the default),
+    /// rather than with box-drawing characters.
+    pub ascii_only: bool,
+    /// The number of lines shown before and after the lines of each
+    /// annotated span.
+    pub context_lines: usize,
+    /// The locale of the text around the diagnostic's own messages (e.g. the
+    /// name of its severity).
+    pub locale: Locale,
+}
+
+impl Default for EmitConfig {
+    fn default() -> Self {
+        Self {
+            color: ColorChoice::Auto,
+            width: None,
+            tab_width: 4,
+            ascii_only: true,
+            context_lines: 0,
+            locale: Locale::current(),
+        }
+    }
+}
+
+impl EmitConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn width(mut self, width: impl Into<Option<usize>>) -> Self {
+        self.width = width.into();
+        self
+    }
+
+    pub fn tab_width(mut self, tab_width: usize) -> Self {
+        self.tab_width = tab_width;
+        self
+    }
+
+    pub fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    pub fn context_lines(mut self, context_lines: usize) -> Self {
+        self.context_lines = context_lines;
+        self
+    }
+
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// The width text is wrapped at.
+    pub(crate) fn resolved_width(&self) -> usize {
+        self.width.unwrap_or_else(textwrap::termwidth)
+    }
+
+    /// Resolves this configuration into the [`Painter`] used while rendering
+    /// a diagnostic (so that the terminal is only inspected once).
+    pub(crate) fn painter(&self) -> Painter {
+        Painter {
+            colorize: self.color.should_colorize(),
+        }
+    }
+}
+
+/// Applies colors and effects to text, unless colors are disabled.
+///
+/// The escape codes are written directly (rather than with `colored`'s
+/// `Colorize` trait), so that the output doesn't depend on `colored`'s global
+/// settings.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Painter {
+    pub colorize: bool,
+}
+
+impl Painter {
+    fn paint(self, text: impl Display, code: &str) -> String {
+        if self.colorize {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    }
+
+    pub fn color(self, text: impl Display, color: Color) -> String {
+        self.paint(text, &color.to_fg_str())
+    }
+
+    pub fn dimmed(self, text: impl Display) -> String {
+        self.paint(text, "2")
+    }
+
+    pub fn underline(self, text: impl Display) -> String {
+        self.paint(text, "4")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_choice_from_str() {
+        assert_eq!("always".parse(), Ok(ColorChoice::Always));
+        assert_eq!("never".parse(), Ok(ColorChoice::Never));
+        assert_eq!("auto".parse(), Ok(ColorChoice::Auto));
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+    }
+
+    #[test]
+    fn test_painter() {
+        let painter = EmitConfig::new().color(ColorChoice::Always).painter();
+        assert_eq!(painter.color("a", Color::Red), "\x1b[31ma\x1b[0m");
+        assert_eq!(painter.dimmed("a"), "\x1b[2ma\x1b[0m");
+
+        let painter = EmitConfig::new().color(ColorChoice::Never).painter();
+        assert_eq!(painter.color("a", Color::Red), "a");
+    }
+}
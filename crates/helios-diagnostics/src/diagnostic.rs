@@ -1,6 +1,9 @@
 use helios_formatting::FormattedString;
+use serde::Serialize;
 use std::ops::Range;
 
+use crate::suggestion::Suggestion;
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Location<FileId> {
     pub file_id: FileId,
@@ -16,31 +19,54 @@ impl<FileId> Location<FileId> {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+/// A secondary location of a diagnostic with a message describing its role
+/// (e.g. "first defined here").
+///
+/// A label may point to a different file than the one the diagnostic is
+/// reported in.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Label<FileId> {
+    pub location: Location<FileId>,
+    pub message: String,
+}
+
+impl<FileId> Label<FileId> {
+    pub fn new(location: Location<FileId>, message: impl Into<String>) -> Self {
+        Self {
+            location,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
 #[repr(u8)]
 pub enum Severity {
     Bug = 3,
     Error = 2,
     Warning = 1,
+    #[default]
     Note = 0,
 }
 
-impl Default for Severity {
-    fn default() -> Self {
-        Self::Note
-    }
-}
-
 /// A diagnostic that provides information about a found issue in a Helios
 /// source file like errors or warnings.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Diagnostic<FileId> {
     pub location: Location<FileId>,
+    /// The stable code identifying the kind of this diagnostic (see the
+    /// [`codes`](crate::codes) module).
+    pub code: Option<&'static str>,
     pub severity: Severity,
     pub title: String,
     pub description: Option<FormattedString>,
     pub message: FormattedString,
     pub hint: Option<FormattedString>,
+    pub labels: Vec<Label<FileId>>,
+    pub suggestions: Vec<Suggestion<FileId>>,
 }
 
 impl<FileId> Diagnostic<FileId>
@@ -57,11 +83,14 @@ where
     ) -> Self {
         Self {
             location,
+            code: None,
             severity,
             title: title.into(),
             description: description.into(),
             message: message.into(),
             hint: hint.into(),
+            labels: Vec::new(),
+            suggestions: Vec::new(),
         }
     }
 
@@ -102,6 +131,11 @@ where
         self
     }
 
+    pub fn code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
     pub fn title(mut self, title: impl Into<String>) -> Self {
         self.title = title.into();
         self
@@ -129,6 +163,22 @@ where
         self.hint = Some(hint.into());
         self
     }
+
+    /// Adds a secondary labeled location to the diagnostic.
+    pub fn label(
+        mut self,
+        location: Location<FileId>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.labels.push(Label::new(location, message));
+        self
+    }
+
+    /// Adds a suggested fix to the diagnostic.
+    pub fn suggestion(mut self, suggestion: Suggestion<FileId>) -> Self {
+        self.suggestions.push(suggestion);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -1,15 +1,81 @@
-use std::cmp::min;
 use std::fmt::Display;
 use std::ops::Range;
 
+use crate::column::{self, ColumnKind};
 use crate::{Error, Result};
 
-/// Generates all the byte indexes where a line feed character (`'\n'`) appears.
+/// The byte indexes where the lines of a source text start.
 ///
-/// Carriage returns (`'\r'`) are treated as ordinary characters to keep things
-/// simple and platform-agnostic.
-fn line_indexes(source: &str) -> impl '_ + Iterator<Item = usize> {
-    std::iter::once(0).chain(source.match_indices('\n').map(|(i, _)| i + 1))
+/// A line ends right after a line feed character (`'\n'`), which means the
+/// last line of a text ending with a line feed is empty. Carriage returns
+/// (`'\r'`) are treated as ordinary characters to keep things simple and
+/// platform-agnostic.
+///
+/// This is the line index shared by [`OneFile`] (and thus [`ManyFiles`]) and
+/// the query database, so that every front-end agrees on where lines are.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LineIndex {
+    starts: Vec<usize>,
+    len: usize,
+}
+
+impl LineIndex {
+    /// Creates the line index of the given source text.
+    pub fn new(source: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        Self {
+            starts,
+            len: source.len(),
+        }
+    }
+
+    /// The byte indexes where each line starts (the first one is always `0`).
+    pub fn starts(&self) -> &[usize] {
+        &self.starts
+    }
+
+    /// The number of lines, which is never zero.
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Returns the byte index from where the given line index starts.
+    ///
+    /// This function will return [`Error::OutOfBounds`] if the given
+    /// `line_index` is not smaller than the number of lines.
+    pub fn line_start(&self, line_index: usize) -> Result<usize> {
+        self.starts
+            .get(line_index)
+            .copied()
+            .ok_or(Error::OutOfBounds {
+                given: line_index,
+                max: self.starts.len() - 1,
+            })
+    }
+
+    /// Returns the index of the line the given byte index is in, or the last
+    /// line's index if the byte index is out of bounds.
+    pub fn line_index(&self, byte_index: usize) -> usize {
+        // Because `starts` is already sorted, we'll do a binary search to get
+        // the expected position of the line index. It's most likely the given
+        // `byte_index` will NOT be in the vector (meaning `byte_index` is
+        // somewhere inside a line), so we'll decrement the expected position
+        // by one to get the line's actual index.
+        self.starts
+            .binary_search(&byte_index)
+            .unwrap_or_else(|expected| expected.saturating_sub(1))
+    }
+
+    /// Returns the byte range of the given line, including its line feed.
+    pub fn line_range(&self, line_index: usize) -> Result<Range<usize>> {
+        let line_start = self.line_start(line_index)?;
+        let line_end = self.line_start(line_index + 1).unwrap_or(self.len);
+
+        Ok(line_start..line_end)
+    }
 }
 
 /// A trait to inspect the texts, lines and columns of files.
@@ -69,41 +135,41 @@ pub trait FileInspector<'a> {
         line_index: usize,
     ) -> Result<Range<usize>>;
 
-    /// Returns the column index of a file at the given byte index.
+    /// Returns the column index of a file at the given byte index, where
+    /// every character is a column.
     ///
     /// Note that the returned value will be zero-indexed (i.e this method will
     /// return `0` for the first column).
     ///
     /// Regardless if you call this method with [`OneFile`] or [`ManyFiles`],
     /// this method will not throw if the byte index is out of bounds – it will
-    /// simply return `0`.
+    /// simply return the column after the last character of the last line.
     fn column_index(
         &'a self,
         id: Self::FileId,
         byte_index: usize,
+    ) -> Result<usize> {
+        self.column_index_of_kind(id, byte_index, ColumnKind::Chars)
+    }
+
+    /// Just like [`FileInspector::column_index`], except the columns are
+    /// counted as described by the given [`ColumnKind`] (e.g. in UTF-16 code
+    /// units for LSP clients).
+    fn column_index_of_kind(
+        &'a self,
+        id: Self::FileId,
+        byte_index: usize,
+        kind: ColumnKind,
     ) -> Result<usize> {
         let source = self.source(id)?;
-        let source_len = source.as_ref().len();
+        let source = source.as_ref();
 
         let line_index = self.line_index(id, byte_index)?;
         let line_range = self.line_range(id, line_index)?;
+        let line = source[line_range.clone()].trim_end_matches(['\n', '\r']);
 
-        // If the current line's range is empty, we must be looking at an empty
-        // line (i.e. a LF immediately followed by EOF). If this is the case,
-        // we'll get the last line's range and get its last column position
-        // (i.e. the index of the LF character).
-        if line_range.is_empty() {
-            let last_line_index = line_index.checked_sub(1).unwrap_or_default();
-            let last_line_range = self.line_range(id, last_line_index)?;
-            return Ok(last_line_range.end);
-        }
-
-        let end_index = min(byte_index, min(line_range.end, source_len));
-        let column_index = (line_range.start..end_index)
-            .filter(|index| source.as_ref().is_char_boundary(index + 1))
-            .count();
-
-        Ok(column_index)
+        let offset = byte_index.saturating_sub(line_range.start);
+        Ok(column::column_at(line, offset, kind))
     }
 
     /// Just like [`FileInspector::column_index`], except it returns a
@@ -128,7 +194,7 @@ pub trait FileInspector<'a> {
 pub struct OneFile<Name, Source> {
     name: Name,
     source: Source,
-    line_indexes: Vec<usize>,
+    lines: LineIndex,
 }
 
 impl<Name, Source> OneFile<Name, Source>
@@ -138,12 +204,12 @@ where
 {
     /// Creates a new [`OneFile`] with the given file name and source text.
     pub fn new(name: Name, source: Source) -> Self {
-        let line_indexes = line_indexes(source.as_ref()).collect();
+        let lines = LineIndex::new(source.as_ref());
 
         Self {
             name,
             source,
-            line_indexes,
+            lines,
         }
     }
 
@@ -157,18 +223,9 @@ where
         &self.source
     }
 
-    /// Returns the byte index from where the given line index starts.
-    ///
-    /// This function will return [`Error::OutOfBounds`] if the given
-    /// `line_index` is larger than the actual number of lines of the file.
-    fn line_start(&self, line_index: usize) -> Result<usize> {
-        self.line_indexes
-            .get(line_index)
-            .cloned()
-            .ok_or(Error::OutOfBounds {
-                given: line_index,
-                max: self.line_indexes.len() - 1,
-            })
+    /// Gets the line index of the file.
+    pub fn lines(&self) -> &LineIndex {
+        &self.lines
     }
 }
 
@@ -190,7 +247,7 @@ where
     }
 
     fn line_count(&'a self, _: Self::FileId) -> Result<usize> {
-        Ok(self.line_indexes.len())
+        Ok(self.lines.line_count())
     }
 
     fn line_index(
@@ -198,15 +255,7 @@ where
         _: Self::FileId,
         byte_index: usize,
     ) -> Result<usize> {
-        // Because `line_indexes` is already sorted, we'll do a binary search
-        // to get the expected position of the line index. It's most likely the
-        // given `byte_index` will NOT be in the vector (meaning `byte_index`
-        // is somewhere inside a line), so we'll decrement the expected position
-        // by one to get the line's actual index.
-        Ok(self
-            .line_indexes
-            .binary_search(&byte_index)
-            .unwrap_or_else(|expected| expected.saturating_sub(1)))
+        Ok(self.lines.line_index(byte_index))
     }
 
     fn line_range(
@@ -214,17 +263,7 @@ where
         _: Self::FileId,
         line_index: usize,
     ) -> Result<Range<usize>> {
-        let line_start = self.line_start(line_index)?;
-        let next_line_start = min(line_index + 1, self.line_count(())? - 1);
-        let line_end = if next_line_start == line_index {
-            // We're already at the last line, so we'll set `line_end` to the
-            // remaining length of the source text.
-            self.source.as_ref().len()
-        } else {
-            self.line_start(next_line_start)?
-        };
-
-        Ok(line_start..line_end)
+        self.lines.line_range(line_index)
     }
 }
 
@@ -242,10 +281,10 @@ pub struct ManyFiles<Name, Source> {
     files: Vec<OneFile<Name, Source>>,
 }
 
-impl<'a, Name, Source> ManyFiles<Name, Source>
+impl<Name, Source> ManyFiles<Name, Source>
 where
-    Name: 'a + std::fmt::Display + Clone,
-    Source: 'a + AsRef<str>,
+    Name: std::fmt::Display + Clone,
+    Source: AsRef<str>,
 {
     pub fn new() -> Self {
         Self { files: Vec::new() }
@@ -262,10 +301,10 @@ where
     }
 }
 
-impl<'a, Name, Source> Default for ManyFiles<Name, Source>
+impl<Name, Source> Default for ManyFiles<Name, Source>
 where
-    Name: 'a + std::fmt::Display + Clone,
-    Source: 'a + AsRef<str>,
+    Name: std::fmt::Display + Clone,
+    Source: AsRef<str>,
 {
     fn default() -> Self {
         Self::new()
@@ -320,80 +359,55 @@ mod tests {
     const FILE_A_SOURCE: &str = "let a = 0\nlet b = 1\r\nlet x = 2\r\n\nfoo\n";
     const FILE_B_SOURCE: &str = "let x = 0\r\nlet y = 1\n\r\nlet z =\n\r\rbar";
 
-    const FILE_A_LINE_INDEXES: &[usize] = &[
-        0,  // "let a = 0\n"
-        10, // "let b = 1\r\n"
-        21, // "let c = 2\r\n"
-        32, // "\n"
-        33, // "foo"
-        37, // "\n"
+    const FILE_A_LINE_RANGES: &[Range<usize>] = &[
+        0..10,  // "let a = 0\n"
+        10..21, // "let b = 1\r\n"
+        21..32, // "let x = 2\r\n"
+        32..33, // "\n"
+        33..37, // "foo\n"
+        37..37, // ""
     ];
 
-    const FILE_B_LINE_INDEXES: &[usize] = &[
-        0,  // "let x = 0\r\n"
-        11, // "let y = 1\n"
-        21, // "\r\n"
-        23, // "let z =\n"
-        31, // "\r\rbar"
+    const FILE_B_LINE_RANGES: &[Range<usize>] = &[
+        0..11,  // "let x = 0\r\n"
+        11..21, // "let y = 1\n"
+        21..23, // "\r\n"
+        23..31, // "let z =\n"
+        31..36, // "\r\rbar"
     ];
 
-    fn check_line_indexes_and_ranges<
-        Name: Clone + Display,
-        Source: AsRef<str>,
-    >(
-        file: &OneFile<Name, Source>,
-        indexes: &[usize],
+    fn check_line_indexes_and_ranges(
+        lines: &LineIndex,
+        ranges: &[Range<usize>],
     ) {
-        for (line_idx, byte_idx) in indexes.iter().enumerate() {
-            let line_start = *byte_idx;
-            assert_eq!(file.line_start(line_idx), Ok(line_start));
-            assert_eq!(file.line_index((), line_start), Ok(line_idx));
-            assert_eq!(file.line_number((), line_start), Ok(line_idx + 1));
-
-            // FIXME: This implementation is very similar to
-            // `OneFile::line_range`, which means we can't verify if the
-            // implementation itself is correct.
-            let line_end = {
-                let next_line_start =
-                    indexes.get(line_idx + 1).copied().unwrap_or_else(|| {
-                        indexes.last().copied().unwrap_or_default()
-                    });
-
-                if next_line_start == line_start {
-                    // We're already at the last line, so we'll return the
-                    // remaining length of the source text.
-                    file.source().as_ref().len()
-                } else {
-                    next_line_start
-                }
-            };
-
-            let expected_line_range = line_start..line_end;
-            assert_eq!(file.line_range((), line_idx), Ok(expected_line_range));
-
-            // Since we already checked the line index at `byte_idx`, we start
-            // checking 5 positions after. It won't matter if the new index
-            // turns out to be larger than than `next_byte_idx` since the range
-            // will be empty (and thus this for loop won't run).
-            for inner_idx in ((line_start + 5)..line_end).step_by(5) {
-                assert_eq!(file.line_index((), inner_idx), Ok(line_idx));
+        let starts = ranges.iter().map(|range| range.start).collect::<Vec<_>>();
+        assert_eq!(lines.starts(), starts);
+        assert_eq!(lines.line_count(), ranges.len());
+
+        for (line_idx, range) in ranges.iter().enumerate() {
+            assert_eq!(lines.line_start(line_idx), Ok(range.start));
+            assert_eq!(lines.line_range(line_idx), Ok(range.clone()));
+            assert_eq!(lines.line_index(range.start), line_idx);
+
+            // Every byte of the line (including its line feed) is in the line
+            for byte_idx in range.clone() {
+                assert_eq!(lines.line_index(byte_idx), line_idx);
             }
         }
 
+        // Byte indexes past the end are in the last line
+        let last_idx = ranges.len() - 1;
+        assert_eq!(lines.line_index(ranges[last_idx].end + 10), last_idx);
+
         // This should return `Error::OutOfBounds`.
-        assert!(file.line_start(indexes.len()).is_err());
+        assert!(lines.line_start(ranges.len()).is_err());
+        assert!(lines.line_range(ranges.len()).is_err());
     }
 
-    fn check_last_line_is_empty<Name: Clone + Display, Source: AsRef<str>>(
-        file: &OneFile<Name, Source>,
-        indexes: &[usize],
-        expected: bool,
-    ) {
-        assert_eq!(
-            file.line_range((), indexes.len() - 1)
-                .map(|range| range.is_empty()),
-            Ok(expected)
-        );
+    #[test]
+    fn test_line_index_of_empty_source() {
+        let lines = LineIndex::new("");
+        check_line_indexes_and_ranges(&lines, std::slice::from_ref(&(0..0)));
     }
 
     #[test]
@@ -401,10 +415,9 @@ mod tests {
         let file_a = OneFile::new(FILE_A_NAME, FILE_A_SOURCE);
         assert_eq!(file_a.name(), &FILE_A_NAME);
         assert_eq!(file_a.source(), &FILE_A_SOURCE);
-        assert_eq!(file_a.line_indexes, FILE_A_LINE_INDEXES);
-        assert_eq!(file_a.line_count(()), Ok(FILE_A_LINE_INDEXES.len()));
-        check_line_indexes_and_ranges(&file_a, FILE_A_LINE_INDEXES);
-        check_last_line_is_empty(&file_a, FILE_A_LINE_INDEXES, true);
+        assert_eq!(file_a.line_count(()), Ok(FILE_A_LINE_RANGES.len()));
+        assert_eq!(file_a.line_number((), 12), Ok(2));
+        check_line_indexes_and_ranges(file_a.lines(), FILE_A_LINE_RANGES);
     }
 
     #[test]
@@ -412,10 +425,9 @@ mod tests {
         let file_b = OneFile::new(FILE_B_NAME, FILE_B_SOURCE);
         assert_eq!(file_b.name(), &FILE_B_NAME);
         assert_eq!(file_b.source(), &FILE_B_SOURCE);
-        assert_eq!(file_b.line_indexes, FILE_B_LINE_INDEXES);
-        assert_eq!(file_b.line_count(()), Ok(FILE_B_LINE_INDEXES.len()));
-        check_line_indexes_and_ranges(&file_b, FILE_B_LINE_INDEXES);
-        check_last_line_is_empty(&file_b, FILE_B_LINE_INDEXES, false);
+        assert_eq!(file_b.line_count(()), Ok(FILE_B_LINE_RANGES.len()));
+        assert_eq!(file_b.line_range((), 4), Ok(31..36));
+        check_line_indexes_and_ranges(file_b.lines(), FILE_B_LINE_RANGES);
     }
 
     #[test]
@@ -427,11 +439,11 @@ mod tests {
         assert!(files.get(file_a).is_ok());
         assert_eq!(files.name(file_a), Ok(FILE_A_NAME));
         assert_eq!(files.source(file_a), Ok(FILE_A_SOURCE));
-        assert_eq!(files.line_count(file_a), Ok(FILE_A_LINE_INDEXES.len()));
+        assert_eq!(files.line_count(file_a), Ok(FILE_A_LINE_RANGES.len()));
 
         assert!(files.get(file_b).is_ok());
         assert_eq!(files.name(file_b), Ok(FILE_B_NAME));
         assert_eq!(files.source(file_b), Ok(FILE_B_SOURCE));
-        assert_eq!(files.line_count(file_b), Ok(FILE_B_LINE_INDEXES.len()));
+        assert_eq!(files.line_count(file_b), Ok(FILE_B_LINE_RANGES.len()));
     }
 }
@@ -0,0 +1,226 @@
+//! Emitting diagnostics as JSON, one diagnostic per line (see
+//! [`emit_json`]).
+
+use helios_formatting::FormattedString;
+use serde::Serialize;
+use std::io::Write;
+use std::ops::Range;
+
+use crate::files::FileInspector;
+use crate::{Applicability, Diagnostic, Location, Result, Severity};
+
+/// A one-indexed line and column position.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A [`Location`] resolved with a [`FileInspector`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ResolvedLocation {
+    pub file: String,
+    pub range: Range<usize>,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl ResolvedLocation {
+    /// Resolves the file name and positions of the given location.
+    pub fn new<'a, F: FileInspector<'a>>(
+        inspector: &'a F,
+        location: &Location<F::FileId>,
+    ) -> Result<Self> {
+        let file_id = location.file_id;
+        let range = location.range.clone();
+
+        let position = |byte_index: usize| -> Result<Position> {
+            Ok(Position {
+                line: inspector.line_number(file_id, byte_index)?,
+                column: inspector.column_number(file_id, byte_index)?,
+            })
+        };
+
+        Ok(Self {
+            file: inspector.name(file_id)?.to_string(),
+            start: position(range.start)?,
+            end: position(range.end)?,
+            range,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct JsonLabel<'d> {
+    message: &'d str,
+    #[serde(flatten)]
+    location: ResolvedLocation,
+}
+
+#[derive(Serialize)]
+struct JsonEdit<'d> {
+    replacement: &'d str,
+    #[serde(flatten)]
+    location: ResolvedLocation,
+}
+
+#[derive(Serialize)]
+struct JsonSuggestion<'d> {
+    message: &'d str,
+    applicability: Applicability,
+    edits: Vec<JsonEdit<'d>>,
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic<'d> {
+    severity: Severity,
+    code: Option<&'static str>,
+    title: &'d str,
+    description: Option<&'d FormattedString>,
+    message: &'d FormattedString,
+    hint: Option<&'d FormattedString>,
+    #[serde(flatten)]
+    location: ResolvedLocation,
+    labels: Vec<JsonLabel<'d>>,
+    suggestions: Vec<JsonSuggestion<'d>>,
+}
+
+/// Converts the given diagnostic to a JSON value.
+pub fn to_json<'a, F: FileInspector<'a>>(
+    inspector: &'a F,
+    diagnostic: &Diagnostic<F::FileId>,
+) -> Result<serde_json::Value> {
+    let labels = diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            Ok(JsonLabel {
+                message: &label.message,
+                location: ResolvedLocation::new(inspector, &label.location)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let suggestions = diagnostic
+        .suggestions
+        .iter()
+        .map(|suggestion| {
+            let edits = suggestion
+                .edits
+                .iter()
+                .map(|edit| {
+                    Ok(JsonEdit {
+                        replacement: &edit.replacement,
+                        location: ResolvedLocation::new(
+                            inspector,
+                            &edit.location,
+                        )?,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(JsonSuggestion {
+                message: &suggestion.message,
+                applicability: suggestion.applicability,
+                edits,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let diagnostic = JsonDiagnostic {
+        severity: diagnostic.severity,
+        code: diagnostic.code,
+        title: &diagnostic.title,
+        description: diagnostic.description.as_ref(),
+        message: &diagnostic.message,
+        hint: diagnostic.hint.as_ref(),
+        location: ResolvedLocation::new(inspector, &diagnostic.location)?,
+        labels,
+        suggestions,
+    };
+
+    Ok(serde_json::to_value(diagnostic)?)
+}
+
+/// Writes the given diagnostic as a single line of JSON.
+///
+/// The object has the fields of a [`Diagnostic`] (its formatted strings as
+/// lists of segments), and each location is given by `file`, `range`, `start`
+/// and `end` (one-indexed lines and columns).
+pub fn emit_json<'a, F: FileInspector<'a>>(
+    f: &mut dyn Write,
+    inspector: &'a F,
+    diagnostic: &Diagnostic<F::FileId>,
+) -> Result<()> {
+    let value = to_json(inspector, diagnostic)?;
+    writeln!(f, "{value}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::ManyFiles;
+    use crate::{Edit, Suggestion};
+    use serde_json::json;
+
+    #[test]
+    fn test_emit_json() {
+        let mut files = ManyFiles::new();
+        let file_id = files.add("main.hl", "let a = 1\nlet b = $\n");
+
+        let diagnostic = Diagnostic::error("Unknown character")
+            .code(crate::codes::UNKNOWN_CHARACTER)
+            .location(Location::new(file_id, 18..19))
+            .message(FormattedString::new().text("I found ").code("$"))
+            .hint("Remove it.")
+            .label(Location::new(file_id, 10..13), "in this binding")
+            .suggestion(
+                Suggestion::new("removing it", Applicability::MaybeIncorrect)
+                    .edit(Edit::delete(Location::new(file_id, 17..19))),
+            );
+
+        let mut output = Vec::new();
+        emit_json(&mut output, &files, &diagnostic).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 1);
+
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "severity": "error",
+                "code": "E0003",
+                "title": "Unknown character",
+                "description": null,
+                "message": [
+                    { "kind": "text", "value": "I found " },
+                    { "kind": "code", "value": "$" },
+                ],
+                "hint": [{ "kind": "text", "value": "Remove it." }],
+                "file": "main.hl",
+                "range": { "start": 18, "end": 19 },
+                "start": { "line": 2, "column": 9 },
+                "end": { "line": 2, "column": 10 },
+                "labels": [{
+                    "message": "in this binding",
+                    "file": "main.hl",
+                    "range": { "start": 10, "end": 13 },
+                    "start": { "line": 2, "column": 1 },
+                    "end": { "line": 2, "column": 4 },
+                }],
+                "suggestions": [{
+                    "message": "removing it",
+                    "applicability": "maybe_incorrect",
+                    "edits": [{
+                        "replacement": "",
+                        "file": "main.hl",
+                        "range": { "start": 17, "end": 19 },
+                        "start": { "line": 2, "column": 8 },
+                        "end": { "line": 2, "column": 10 },
+                    }],
+                }],
+            })
+        );
+    }
+}
@@ -1,13 +1,28 @@
+pub mod codes;
+pub mod column;
+pub mod config;
 pub mod diagnostic;
 pub mod files;
+pub mod json;
+pub mod sarif;
+pub mod sink;
+mod snippet;
+pub mod suggestion;
 
-use colored::*;
+use crate::snippet::{Renderer, Span, SpanStyle};
+
+use colored::Color;
+use helios_formatting::FormattedString;
 use std::{fmt::Display, io::Write};
 
+pub use crate::column::ColumnKind;
+pub use crate::config::{ColorChoice, EmitConfig};
 pub use crate::diagnostic::*;
 pub use crate::files::*;
-
-use crate::files::FileInspector;
+pub use crate::json::emit_json;
+pub use crate::sarif::SarifReport;
+pub use crate::sink::{DiagnosticSink, Level, Outcome};
+pub use crate::suggestion::{Applicability, Edit, Suggestion};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -17,6 +32,7 @@ pub enum Error {
     OutOfBounds { given: usize, max: usize },
     IoError(String),
     FmtError(std::fmt::Error),
+    JsonError(String),
 }
 
 impl Display for Error {
@@ -33,6 +49,9 @@ impl Display for Error {
             Self::FmtError(error) =>{
                 write!(f, "a formatting error occurred: {error}")
             }
+            Self::JsonError(error) => {
+                write!(f, "a JSON serialization error occurred: {error}")
+            }
         }
     }
 }
@@ -45,105 +64,400 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::JsonError(error.to_string())
+    }
+}
+
 impl From<std::fmt::Error> for Error {
     fn from(error: std::fmt::Error) -> Self {
         Self::FmtError(error)
     }
 }
 
+/// Writes the given diagnostic in a human-readable format, as configured by
+/// the given [`EmitConfig`].
 pub fn emit<'a, F: FileInspector<'a>>(
     f: &mut dyn Write,
     inspector: &'a F,
     diagnostic: &Diagnostic<F::FileId>,
+    config: &EmitConfig,
 ) -> Result<()> {
+    let (painter, locale) = (config.painter(), config.locale);
+    let renderer = Renderer::new(config, painter);
+    let width = config.resolved_width();
+
     let file_id = diagnostic.location.file_id;
     let file_name = inspector.name(file_id)?;
-    let source = inspector.source(file_id)?;
 
     let severity = diagnostic.severity;
     let error_range = diagnostic.location.range.clone();
-    let error_start = error_range.start;
-    let error_end = error_range.end;
-
-    let line_index = inspector.line_index(file_id, error_range.start)?;
-    let line_range = inspector.line_range(file_id, line_index)?;
-    let line_number = line_index + 1;
 
-    let column_start = inspector.column_number(file_id, error_start)?;
-    let column_end = inspector.column_number(file_id, error_end)?;
+    let line_number = inspector.line_number(file_id, error_range.start)?;
+    let column_start = inspector.column_number(file_id, error_range.start)?;
 
     let (color, header, underline) = {
-        let make_header = |msg: String| {
-            let remaining_len = textwrap::termwidth()
-                .checked_sub(msg.len())
-                .unwrap_or_default();
-            format!("{msg}{}", "-".repeat(remaining_len))
+        let code = diagnostic
+            .code
+            .map(|code| format!("[{code}]"))
+            .unwrap_or_default();
+
+        let (name_id, color, underline) = match severity {
+            Severity::Bug => ("emit-bug", Color::Magenta, '^'),
+            Severity::Error => ("emit-error", Color::Red, '^'),
+            Severity::Warning => ("emit-warning", Color::Yellow, '~'),
+            Severity::Note => ("emit-note", Color::Blue, '-'),
         };
+        let name = locale.text(name_id, &[]);
+
+        let msg = format!("-- {name}{code}: {} ", diagnostic.title);
+        let remaining_len = width.saturating_sub(msg.chars().count());
+        let header = format!("{msg}{}", "-".repeat(remaining_len));
+        (color, header, underline)
+    };
+
+    let wrap = |text: &str| textwrap::fill(text, width);
+    let text = |text: &FormattedString| {
+        if painter.colorize {
+            text.render_ansi()
+        } else {
+            text.render_plain()
+        }
+    };
+
+    let location_str = format!("-> {file_name}:{line_number}:{column_start}");
+    writeln!(f, "{}", painter.color(header, color))?;
+    writeln!(f, "{}\n", painter.color(location_str, color))?;
+
+    if let Some(description) = &diagnostic.description {
+        writeln!(f, "{}\n", wrap(&text(description)))?;
+    }
+
+    let primary_style = SpanStyle { color, underline };
+    let secondary_style = SpanStyle {
+        color: Color::Blue,
+        underline: '-',
+    };
+
+    let secondary_span = |label: &Label<F::FileId>| Span {
+        range: label.location.range.clone(),
+        label: Some(label.message.clone()),
+        style: secondary_style,
+    };
+
+    // The primary location is rendered along with the labels in its file...
+    let mut spans = vec![Span {
+        range: error_range.clone(),
+        label: None,
+        style: primary_style,
+    }];
+    spans.extend(
+        diagnostic
+            .labels
+            .iter()
+            .filter(|label| label.location.file_id == file_id)
+            .map(secondary_span),
+    );
+    renderer.render_snippet(f, inspector, file_id, &spans)?;
+
+    // ...followed by the labels in other files, grouped by file
+    let mut other_files: Vec<F::FileId> = Vec::new();
+    for label in &diagnostic.labels {
+        let id = label.location.file_id;
+        if id != file_id && !other_files.contains(&id) {
+            other_files.push(id);
+        }
+    }
+
+    writeln!(f, "{}\n", wrap(&text(&diagnostic.message)).trim_end())?;
+
+    for other_file_id in other_files {
+        let labels = diagnostic
+            .labels
+            .iter()
+            .filter(|label| label.location.file_id == other_file_id)
+            .collect::<Vec<_>>();
+
+        let start = labels[0].location.range.start;
+        let name = inspector.name(other_file_id)?;
+        let line_number = inspector.line_number(other_file_id, start)?;
+        let column_number = inspector.column_number(other_file_id, start)?;
+        let location_str = format!("-> {name}:{line_number}:{column_number}");
+        writeln!(
+            f,
+            "{}\n",
+            painter.color(location_str, secondary_style.color)
+        )?;
+
+        let spans = labels.into_iter().map(secondary_span).collect::<Vec<_>>();
+        renderer.render_snippet(f, inspector, other_file_id, &spans)?;
+        writeln!(f)?;
+    }
+
+    if let Some(hint) = &diagnostic.hint {
+        let label = painter.underline(locale.text("emit-hint", &[]));
+        let hint = format!("{label}: {}", text(hint));
+        writeln!(f, "{}\n", wrap(&hint))?;
+    }
+
+    for suggestion in &diagnostic.suggestions {
+        let help = format!(
+            "{}: {}",
+            painter.underline(locale.text("emit-help", &[])),
+            locale.text(
+                "emit-help-message",
+                &[("suggestion", suggestion.message.as_str().into())]
+            )
+        );
+        writeln!(f, "{}\n", wrap(&help))?;
 
-        match severity {
-            Severity::Bug => {
-                let msg = format!("-- Bug: {} ", diagnostic.title);
-                let header = make_header(msg);
-                (Color::Magenta, header, "^")
+        // The edits are shown file by file, starting with the diagnostic's
+        let mut edit_files = vec![file_id];
+        for edit in &suggestion.edits {
+            if !edit_files.contains(&edit.location.file_id) {
+                edit_files.push(edit.location.file_id);
             }
-            Severity::Error => {
-                let msg = format!("-- Error: {} ", diagnostic.title);
-                let header = make_header(msg);
-                (Color::Red, header, "^")
+        }
+
+        for edit_file_id in edit_files {
+            let edits = suggestion
+                .edits
+                .iter()
+                .filter(|edit| edit.location.file_id == edit_file_id)
+                .map(|edit| (edit.location.range.clone(), &*edit.replacement))
+                .collect::<Vec<_>>();
+
+            if edits.is_empty() {
+                continue;
             }
-            Severity::Warning => {
-                let msg = format!("-- Warning: {} ", diagnostic.title);
-                let header = make_header(msg);
-                (Color::Yellow, header, "~")
+
+            if edit_file_id != file_id {
+                let start = edits[0].0.start;
+                let name = inspector.name(edit_file_id)?;
+                let line_number = inspector.line_number(edit_file_id, start)?;
+                let column_number =
+                    inspector.column_number(edit_file_id, start)?;
+                let location_str =
+                    format!("-> {name}:{line_number}:{column_number}");
+                writeln!(f, "{}\n", painter.color(location_str, Color::Green))?;
             }
-            Severity::Note => {
-                let msg = format!("-- Note: {} ", diagnostic.title);
-                let header = make_header(msg);
-                (Color::Blue, header, "-")
+
+            if renderer.render_edits(
+                f,
+                inspector,
+                edit_file_id,
+                &edits,
+                Color::Green,
+            )? {
+                writeln!(f)?;
             }
         }
-    };
+    }
 
-    macro_rules! wrap {
-        ($formatting:literal, $( $args:expr ),* $(,)?) => {
-            textwrap::fill(
-                &format!($formatting, $( $args ),*),
-                textwrap::Options::with_termwidth(),
-            )
-        };
-        ($item:expr) => {
-            textwrap::fill(
-                &format!("{}", $item),
-                textwrap::Options::with_termwidth(),
+    if let Some(code) = diagnostic
+        .code
+        .filter(|code| codes::explain(code).is_some())
+    {
+        let footer = wrap(
+            &locale.text("emit-more-information", &[("code", code.into())]),
+        );
+        writeln!(f, "{}\n", painter.dimmed(footer))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::{expect, Expect};
+    use helios_locale::Locale;
+
+    fn config() -> EmitConfig {
+        EmitConfig::new()
+            .color(ColorChoice::Never)
+            .width(60)
+            .locale(Locale::EnUs)
+    }
+
+    fn check<'a, F: FileInspector<'a>>(
+        inspector: &'a F,
+        diagnostic: &Diagnostic<F::FileId>,
+        config: &EmitConfig,
+        expected: Expect,
+    ) {
+        let mut output = Vec::new();
+        emit(&mut output, inspector, diagnostic, config).unwrap();
+        expected.assert_eq(&String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_emit_error() {
+        let file = OneFile::new("main.hl", "let let = (1\n");
+        let diagnostic = Diagnostic::error("Unexpected keyword")
+            .code(codes::UNEXPECTED_KIND)
+            .location(Location::new((), 4..7))
+            .description("I was partway through a binding:")
+            .message(
+                FormattedString::new()
+                    .text("I expected a name here, but found the keyword ")
+                    .code("let")
+                    .text(", which can't be used as a name."),
             )
-        };
+            .hint("Try using a different name instead.")
+            .suggestion(
+                Suggestion::new("renaming it", Applicability::MaybeIncorrect)
+                    .edit(Edit::new(Location::new((), 4..7), "let_")),
+            );
+
+        check(
+            &file,
+            &diagnostic,
+            &config(),
+            expect![[r#"
+                -- Error[E0002]: Unexpected keyword ------------------------
+                -> main.hl:1:5
+
+                I was partway through a binding:
+
+                   1 | let let = (1
+                     |     ^^^
+                I expected a name here, but found the keyword `let`, which
+                can't be used as a name.
+
+                Hint: Try using a different name instead.
+
+                Help: try renaming it:
+
+                   1 | let let_ = (1
+                     |     ++++
+
+                For more information about this diagnostic, try `helios
+                explain E0002`.
+
+            "#]],
+        );
     }
 
-    let location_str = format!("-> {file_name}:{line_number}:{column_start}");
-    writeln!(f, "{}", header.color(color))?;
-    writeln!(f, "{}\n", location_str.color(color))?;
+    #[test]
+    fn test_emit_in_french() {
+        let file = OneFile::new("main.hl", "let a = 1 ¬ 2\n");
+        let diagnostic = Diagnostic::error("Caractère inconnu")
+            .code(codes::UNKNOWN_CHARACTER)
+            .location(Location::new((), 10..12))
+            .message("Ce caractère n'est pas un lexème valide.")
+            .hint("Vouliez-vous vraiment l'écrire ?")
+            .suggestion(
+                Suggestion::new(
+                    "essayez de supprimer le caractère",
+                    Applicability::MaybeIncorrect,
+                )
+                .edit(Edit::delete(Location::new((), 10..12))),
+            );
 
-    if let Some(description) = &diagnostic.description {
-        writeln!(f, "{}\n", wrap!(description))?;
+        check(
+            &file,
+            &diagnostic,
+            &config().locale(Locale::Fr),
+            expect![[r#"
+                -- Erreur[E0003]: Caractère inconnu ------------------------
+                -> main.hl:1:11
+
+                   1 | let a = 1 ¬ 2
+                     |           ^
+                Ce caractère n'est pas un lexème valide.
+
+                Indice: Vouliez-vous vraiment l'écrire ?
+
+                Aide: essayez de supprimer le caractère :
+
+                   1 | let a = 1 ¬ 2
+                     |           -
+
+                Pour plus d'informations sur ce diagnostic, essayez `helios
+                explain E0003`.
+
+            "#]],
+        );
     }
 
-    let gutter = format!("{line_number:>4} | ");
-    let line = &source.as_ref()[line_range].trim_end(); // remove trailing LF
-    writeln!(f, "{}{line}", gutter.dimmed())?;
+    #[test]
+    fn test_emit_warning_with_unicode_glyphs() {
+        let file = OneFile::new("main.hl", "let a =\n\t(1 +\n\t 2)\n");
+        let diagnostic = Diagnostic::warning("Redundant parentheses")
+            .location(Location::new((), 9..18))
+            .message("These parentheses can be removed.");
 
-    // `column_start` is indexed by 1
-    let offset = " ".repeat(gutter.len() + column_start - 1);
-    // The difference of the column positions, or 1, whichever is larger
-    let underline_count = std::cmp::max(1, column_end - column_start);
-    // Underline string repeated `underline_count` times
-    let underline = underline.repeat(underline_count).color(color);
-    writeln!(f, "{offset}{underline}")?;
+        check(
+            &file,
+            &diagnostic,
+            &config().ascii_only(false).tab_width(2).context_lines(1),
+            expect![[r#"
+                -- Warning: Redundant parentheses --------------------------
+                -> main.hl:2:2
 
-    writeln!(f, "{}\n", wrap!(diagnostic.message).trim_end())?;
+                   1 │   let a =
+                   2 │     (1 +
+                     │ ╭───┅
+                   3 │ │    2)
+                     │ ╰─────┅
+                These parentheses can be removed.
 
-    if let Some(hint) = &diagnostic.hint {
-        writeln!(f, "{}\n", wrap!("{}: {hint}", "Hint".underline()))?;
+            "#]],
+        );
     }
 
-    Ok(())
+    #[test]
+    fn test_emit_with_colors() {
+        let file = OneFile::new("main.hl", "$\n");
+        let diagnostic = Diagnostic::error("Unknown character")
+            .location(Location::new((), 0..1))
+            .message(FormattedString::new().code("$"));
+
+        let mut output = Vec::new();
+        let config = config().color(ColorChoice::Always);
+        emit(&mut output, &file, &diagnostic, &config).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.starts_with("\x1b[31m-- Error: Unknown character -"));
+        assert!(output.contains("\x1b[31m^\x1b[0m"));
+        assert!(output.contains("\x1b[33m$\x1b[0m"));
+    }
+
+    #[test]
+    fn test_emit_labels_across_files() {
+        let mut files = ManyFiles::new();
+        let a = files.add("a.hl", "let foo = 1\n");
+        let b = files.add("b.hl", "let bar = 2\nlet foo = (bar +\n  bar)\n");
+
+        let diagnostic = Diagnostic::error("Duplicate binding")
+            .location(Location::new(b, 16..19))
+            .message("`foo` is defined more than once.")
+            .label(Location::new(b, 22..35), "while binding this expression")
+            .label(Location::new(a, 4..7), "first defined here");
+
+        let mut output = Vec::new();
+        emit(&mut output, &files, &diagnostic, &config()).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let expected_snippet = "\
+-> b.hl:2:5
+
+   2 |   let foo = (bar +
+     |       ^^^
+     |  ___________-
+   3 | |   bar)
+     | |______- while binding this expression
+`foo` is defined more than once.
+
+-> a.hl:1:5
+
+   1 | let foo = 1
+     |     --- first defined here
+";
+
+        assert!(output.starts_with("-- Error: Duplicate binding "));
+        assert!(output.contains(expected_snippet), "{output}");
+    }
 }
@@ -0,0 +1,343 @@
+//! Writing diagnostics as a SARIF report.
+//!
+//! A [`SarifReport`] collects diagnostics and writes them as a single run of a
+//! [SARIF 2.1.0] log.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use serde_json::{json, Value};
+use std::collections::BTreeSet;
+use std::io::Write;
+
+use crate::files::FileInspector;
+use crate::json::ResolvedLocation;
+use crate::{codes, Diagnostic, Result, Severity, Suggestion};
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+/// A SARIF report of the diagnostics found by a tool.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SarifReport {
+    tool_name: String,
+    tool_version: String,
+    rules: BTreeSet<&'static str>,
+    results: Vec<Value>,
+}
+
+impl SarifReport {
+    /// Creates an empty report for the tool with the given name and version.
+    pub fn new(
+        tool_name: impl Into<String>,
+        tool_version: impl Into<String>,
+    ) -> Self {
+        Self {
+            tool_name: tool_name.into(),
+            tool_version: tool_version.into(),
+            rules: BTreeSet::new(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Adds the given diagnostic to the report as a result.
+    pub fn add<'a, F: FileInspector<'a>>(
+        &mut self,
+        inspector: &'a F,
+        diagnostic: &Diagnostic<F::FileId>,
+    ) -> Result<()> {
+        let location = ResolvedLocation::new(inspector, &diagnostic.location)?;
+
+        let related_locations = diagnostic
+            .labels
+            .iter()
+            .enumerate()
+            .map(|(id, label)| {
+                let location =
+                    ResolvedLocation::new(inspector, &label.location)?;
+                let mut value = physical_location(&location);
+                value["id"] = json!(id);
+                value["message"] = json!({ "text": label.message });
+                Ok(value)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut result = json!({
+            "level": level(diagnostic.severity),
+            "message": {
+                "text": message_text(diagnostic),
+                "markdown": message_markdown(diagnostic),
+            },
+            "locations": [physical_location(&location)],
+        });
+
+        if let Some(code) = diagnostic.code {
+            result["ruleId"] = json!(code);
+            self.rules.insert(code);
+        }
+
+        if !related_locations.is_empty() {
+            result["relatedLocations"] = Value::Array(related_locations);
+        }
+
+        let fixes = diagnostic
+            .suggestions
+            .iter()
+            .map(|suggestion| fix(inspector, suggestion))
+            .collect::<Result<Vec<_>>>()?;
+
+        if !fixes.is_empty() {
+            result["fixes"] = Value::Array(fixes);
+        }
+
+        self.results.push(result);
+        Ok(())
+    }
+
+    /// The number of results in the report.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Determines if the report has no results.
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Converts the report to a SARIF log.
+    ///
+    /// Every diagnostic code used by the results is described as a rule of
+    /// the tool, along with its explanation from the [`codes`] registry.
+    pub fn to_json(&self) -> Value {
+        let rules = self
+            .rules
+            .iter()
+            .map(|code| match codes::explain(code) {
+                Some(explanation) => json!({
+                    "id": code,
+                    "shortDescription": { "text": explanation.title },
+                    "fullDescription": {
+                        "text": explanation.explanation.render_plain(),
+                        "markdown": explanation.explanation.render_markdown(),
+                    },
+                }),
+                None => json!({ "id": code }),
+            })
+            .collect::<Vec<_>>();
+
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": SARIF_VERSION,
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": self.tool_name,
+                        "version": self.tool_version,
+                        "rules": rules,
+                    },
+                },
+                "results": self.results,
+                // Columns are counted in characters rather than in UTF-16
+                // code units (which is the default)
+                "columnKind": "unicodeCodePoints",
+            }],
+        })
+    }
+
+    /// Writes the report as a (pretty-printed) SARIF log.
+    pub fn write(&self, f: &mut dyn Write) -> Result<()> {
+        serde_json::to_writer_pretty(&mut *f, &self.to_json())?;
+        writeln!(f)?;
+        Ok(())
+    }
+}
+
+/// The SARIF level of the given severity.
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Bug | Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note => "note",
+    }
+}
+
+fn physical_location(location: &ResolvedLocation) -> Value {
+    json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": location.file },
+            "region": {
+                "startLine": location.start.line,
+                "startColumn": location.start.column,
+                "endLine": location.end.line,
+                "endColumn": location.end.column,
+                "charOffset": location.range.start,
+                "charLength": location.range.len(),
+            },
+        },
+    })
+}
+
+/// Converts a suggestion to a SARIF fix, grouping its edits by file.
+fn fix<'a, F: FileInspector<'a>>(
+    inspector: &'a F,
+    suggestion: &Suggestion<F::FileId>,
+) -> Result<Value> {
+    let mut changes: Vec<(String, Vec<Value>)> = Vec::new();
+
+    for edit in &suggestion.edits {
+        let location = ResolvedLocation::new(inspector, &edit.location)?;
+        let replacement = json!({
+            "deletedRegion": {
+                "charOffset": location.range.start,
+                "charLength": location.range.len(),
+            },
+            "insertedContent": { "text": edit.replacement },
+        });
+
+        match changes.iter_mut().find(|(file, _)| *file == location.file) {
+            Some((_, replacements)) => replacements.push(replacement),
+            None => changes.push((location.file, vec![replacement])),
+        }
+    }
+
+    let changes = changes
+        .into_iter()
+        .map(|(file, replacements)| {
+            json!({
+                "artifactLocation": { "uri": file },
+                "replacements": replacements,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "description": { "text": suggestion.message },
+        "artifactChanges": changes,
+    }))
+}
+
+fn message_text<FileId>(diagnostic: &Diagnostic<FileId>) -> String {
+    let mut text = format!(
+        "{}: {}",
+        diagnostic.title,
+        diagnostic.message.render_plain()
+    );
+
+    if let Some(hint) = &diagnostic.hint {
+        text.push_str(&format!("\n\nHint: {}", hint.render_plain()));
+    }
+
+    text
+}
+
+fn message_markdown<FileId>(diagnostic: &Diagnostic<FileId>) -> String {
+    let mut markdown = format!("**{}**", diagnostic.title);
+
+    if let Some(description) = &diagnostic.description {
+        markdown.push_str(&format!("\n\n{}", description.render_markdown()));
+    }
+
+    markdown.push_str(&format!("\n\n{}", diagnostic.message.render_markdown()));
+
+    if let Some(hint) = &diagnostic.hint {
+        markdown.push_str(&format!("\n\n*Hint:* {}", hint.render_markdown()));
+    }
+
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::ManyFiles;
+    use crate::{Applicability, Edit, Location};
+    use helios_formatting::FormattedString;
+
+    #[test]
+    fn test_sarif_report() {
+        let mut files = ManyFiles::new();
+        let a = files.add("a.hl", "let a = 1\n");
+        let b = files.add("b.hl", "let a = 2\n");
+
+        let diagnostics = [
+            Diagnostic::error("Duplicate binding")
+                .code(crate::codes::MISSING_KIND)
+                .location(Location::new(b, 4..5))
+                .message(
+                    FormattedString::new().code("a").text(" is bound twice"),
+                )
+                .hint("Rename one of them.")
+                .label(Location::new(a, 4..5), "first bound here")
+                .suggestion(
+                    Suggestion::new(
+                        "renaming it",
+                        Applicability::MaybeIncorrect,
+                    )
+                    .edit(Edit::new(Location::new(b, 4..5), "b")),
+                ),
+            Diagnostic::warning("Unused binding")
+                .location(Location::new(a, 4..5))
+                .message("This binding is never used."),
+        ];
+
+        let mut report = SarifReport::new("helios", "0.2.0");
+        for diagnostic in &diagnostics {
+            report.add(&files, diagnostic).unwrap();
+        }
+        assert_eq!(report.len(), 2);
+
+        let mut output = Vec::new();
+        report.write(&mut output).unwrap();
+        let log: Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "helios");
+        assert_eq!(run["columnKind"], "unicodeCodePoints");
+
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0]["id"], "E0001");
+        assert_eq!(rules[0]["shortDescription"]["text"], "Missing token");
+
+        let error = &run["results"][0];
+        assert_eq!(error["level"], "error");
+        assert_eq!(error["ruleId"], "E0001");
+        assert_eq!(
+            error["message"]["text"],
+            "Duplicate binding: `a` is bound twice\n\nHint: Rename one of them."
+        );
+        assert_eq!(
+            error["message"]["markdown"],
+            "**Duplicate binding**\n\n`a` is bound twice\n\n\
+             *Hint:* Rename one of them."
+        );
+
+        let region = &error["locations"][0]["physicalLocation"]["region"];
+        assert_eq!(region["startLine"], 1);
+        assert_eq!(region["startColumn"], 5);
+        assert_eq!(region["charOffset"], 4);
+        assert_eq!(region["charLength"], 1);
+
+        let related = &error["relatedLocations"][0];
+        assert_eq!(related["message"]["text"], "first bound here");
+        assert_eq!(
+            related["physicalLocation"]["artifactLocation"]["uri"],
+            "a.hl"
+        );
+
+        let fix = &error["fixes"][0];
+        assert_eq!(fix["description"]["text"], "renaming it");
+        let change = &fix["artifactChanges"][0];
+        assert_eq!(change["artifactLocation"]["uri"], "b.hl");
+        let replacement = &change["replacements"][0];
+        assert_eq!(replacement["deletedRegion"]["charOffset"], 4);
+        assert_eq!(replacement["deletedRegion"]["charLength"], 1);
+        assert_eq!(replacement["insertedContent"]["text"], "b");
+
+        let warning = &run["results"][1];
+        assert_eq!(warning["level"], "warning");
+        assert!(warning.get("relatedLocations").is_none());
+        assert!(warning.get("ruleId").is_none());
+        assert!(warning.get("fixes").is_none());
+    }
+}
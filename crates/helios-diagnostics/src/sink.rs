@@ -0,0 +1,366 @@
+//! Collecting the diagnostics reported while compiling a program.
+//!
+//! A [`DiagnosticSink`] applies the severity overrides requested by the user,
+//! then returns the diagnostics to emit along with an [`Outcome`].
+
+use crate::diagnostic::{Diagnostic, Severity};
+
+/// The name that refers to every warning in a severity override, regardless
+/// of its code (as in `-D warnings`).
+pub const WARNINGS: &str = "warnings";
+
+/// The level a diagnostic is reported at when its severity is overridden.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Level {
+    /// The diagnostic is not reported at all.
+    Allow,
+    /// The diagnostic is reported as a warning.
+    Warn,
+    /// The diagnostic is reported as an error.
+    Deny,
+}
+
+/// A summary of the diagnostics collected by a [`DiagnosticSink`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Outcome {
+    pub bugs: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub notes: usize,
+    /// The number of diagnostics that were allowed (and thus not reported).
+    pub allowed: usize,
+    /// The number of diagnostics that were dropped because they duplicate a
+    /// diagnostic that is at least as severe.
+    pub duplicates: usize,
+    /// The number of errors (and bugs) that weren't returned because the
+    /// error limit was reached. They are still counted in `errors` and
+    /// `bugs`.
+    pub omitted: usize,
+}
+
+impl Outcome {
+    /// Determines if the compilation succeeded (i.e. no errors or bugs were
+    /// reported, no matter how many warnings or notes there are).
+    pub fn is_success(&self) -> bool {
+        self.bugs == 0 && self.errors == 0
+    }
+}
+
+/// A collector of diagnostics.
+#[derive(Clone, Debug)]
+pub struct DiagnosticSink<FileId> {
+    diagnostics: Vec<Diagnostic<FileId>>,
+    levels: Vec<(String, Level)>,
+    error_limit: Option<usize>,
+    allowed: usize,
+}
+
+impl<FileId> Default for DiagnosticSink<FileId> {
+    fn default() -> Self {
+        Self {
+            diagnostics: Vec::new(),
+            levels: Vec::new(),
+            error_limit: None,
+            allowed: 0,
+        }
+    }
+}
+
+impl<FileId> DiagnosticSink<FileId>
+where
+    FileId: PartialEq,
+{
+    /// Creates an empty sink without an error limit or severity overrides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of errors (and bugs) returned by
+    /// [`DiagnosticSink::finish`], where `0` means there is no limit.
+    pub fn error_limit(mut self, limit: usize) -> Self {
+        self.error_limit = (limit > 0).then_some(limit);
+        self
+    }
+
+    /// Overrides the level of the diagnostics with the given code (ignoring
+    /// its case), or of every warning if the code is [`WARNINGS`].
+    ///
+    /// Overrides are applied in the order they are given, so a later override
+    /// of the same code wins. Overriding a code takes precedence over
+    /// overriding [`WARNINGS`], and bugs are never overridden.
+    pub fn level(mut self, code: impl Into<String>, level: Level) -> Self {
+        self.levels.push((code.into(), level));
+        self
+    }
+
+    /// The level the given diagnostic is overridden to, if any.
+    fn level_of(&self, diagnostic: &Diagnostic<FileId>) -> Option<Level> {
+        let last_level = |matches: &dyn Fn(&str) -> bool| {
+            self.levels
+                .iter()
+                .rev()
+                .find(|(code, _)| matches(code))
+                .map(|(_, level)| *level)
+        };
+
+        let code_level = diagnostic.code.and_then(|diagnostic_code| {
+            last_level(&|code| code.eq_ignore_ascii_case(diagnostic_code))
+        });
+
+        code_level.or_else(|| {
+            if diagnostic.severity == Severity::Warning {
+                last_level(&|code| code == WARNINGS)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Adds a diagnostic to the sink, applying the severity overrides.
+    pub fn push(&mut self, mut diagnostic: Diagnostic<FileId>) {
+        if diagnostic.severity != Severity::Bug {
+            match self.level_of(&diagnostic) {
+                Some(Level::Allow) => {
+                    self.allowed += 1;
+                    return;
+                }
+                Some(Level::Warn) => diagnostic.severity = Severity::Warning,
+                Some(Level::Deny) => diagnostic.severity = Severity::Error,
+                None => {}
+            }
+        }
+
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// Returns the collected diagnostics and a summary of them.
+    ///
+    /// The diagnostics are sorted by location, where files are ordered by
+    /// when a diagnostic was first reported in them. A diagnostic is dropped
+    /// if a diagnostic that is at least as severe is reported at the exact
+    /// same location, or has the same code and an overlapping range, as it's
+    /// usually a consequence of the same problem. Diagnostics with different
+    /// codes are kept even if they overlap (e.g. a name error inside a broad
+    /// parse error). If an error limit was set, the errors past the limit are
+    /// dropped as well.
+    pub fn finish(self) -> (Vec<Diagnostic<FileId>>, Outcome) {
+        let mut files: Vec<&FileId> = Vec::new();
+        for diagnostic in &self.diagnostics {
+            if !files.contains(&&diagnostic.location.file_id) {
+                files.push(&diagnostic.location.file_id);
+            }
+        }
+
+        let file_rank = |file_id: &FileId| {
+            files
+                .iter()
+                .position(|it| *it == file_id)
+                .unwrap_or_default()
+        };
+
+        let mut order = (0..self.diagnostics.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&index| {
+            let location = &self.diagnostics[index].location;
+            (
+                file_rank(&location.file_id),
+                location.range.start,
+                location.range.end,
+            )
+        });
+
+        // The most severe diagnostics are kept first, so that a less severe
+        // one never hides a more severe one
+        let mut by_severity = order.clone();
+        by_severity.sort_by_key(|&index| {
+            std::cmp::Reverse(self.diagnostics[index].severity)
+        });
+
+        let mut kept: Vec<usize> = Vec::new();
+        let mut outcome = Outcome {
+            allowed: self.allowed,
+            ..Outcome::default()
+        };
+
+        for index in by_severity {
+            let diagnostic = &self.diagnostics[index];
+            let is_duplicate = kept
+                .iter()
+                .any(|&other| duplicates(&self.diagnostics[other], diagnostic));
+
+            if is_duplicate {
+                outcome.duplicates += 1;
+            } else {
+                kept.push(index);
+            }
+        }
+
+        let mut keep = vec![false; self.diagnostics.len()];
+        for index in kept {
+            keep[index] = true;
+        }
+
+        let mut diagnostics =
+            self.diagnostics.into_iter().map(Some).collect::<Vec<_>>();
+        let mut finished = Vec::new();
+        let mut error_count = 0;
+
+        for index in order {
+            if !keep[index] {
+                continue;
+            }
+
+            let diagnostic = diagnostics[index].take().unwrap();
+            match diagnostic.severity {
+                Severity::Bug => outcome.bugs += 1,
+                Severity::Error => outcome.errors += 1,
+                Severity::Warning => outcome.warnings += 1,
+                Severity::Note => outcome.notes += 1,
+            }
+
+            if diagnostic.severity >= Severity::Error {
+                error_count += 1;
+                if self.error_limit.is_some_and(|limit| error_count > limit) {
+                    outcome.omitted += 1;
+                    continue;
+                }
+            }
+
+            finished.push(diagnostic);
+        }
+
+        (finished, outcome)
+    }
+}
+
+impl<FileId> Extend<Diagnostic<FileId>> for DiagnosticSink<FileId>
+where
+    FileId: PartialEq,
+{
+    fn extend<I: IntoIterator<Item = Diagnostic<FileId>>>(&mut self, iter: I) {
+        for diagnostic in iter {
+            self.push(diagnostic);
+        }
+    }
+}
+
+/// Determines if two diagnostics report the same problem, i.e. if they're
+/// reported at the same location, or have the same code and overlapping
+/// locations.
+fn duplicates<FileId: PartialEq>(
+    a: &Diagnostic<FileId>,
+    b: &Diagnostic<FileId>,
+) -> bool {
+    let same_code = a.code == b.code;
+    let (a, b) = (&a.location, &b.location);
+    a.file_id == b.file_id
+        && (a.range == b.range
+            || (same_code
+                && a.range.start < b.range.end
+                && b.range.start < a.range.end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::Location;
+
+    fn diagnostic(
+        severity: Severity,
+        range: std::ops::Range<usize>,
+    ) -> Diagnostic<u8> {
+        Diagnostic::error("")
+            .severity(severity)
+            .location(Location::new(0, range))
+    }
+
+    fn ranges(diagnostics: &[Diagnostic<u8>]) -> Vec<std::ops::Range<usize>> {
+        diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.location.range.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_and_dedup() {
+        let mut sink = DiagnosticSink::new();
+        sink.push(diagnostic(Severity::Warning, 10..12));
+        sink.push(diagnostic(Severity::Error, 4..7));
+        sink.push(diagnostic(Severity::Note, 5..6));
+        sink.push(diagnostic(Severity::Error, 11..11));
+        sink.push(diagnostic(Severity::Error, 11..11));
+        sink.push(diagnostic(Severity::Error, 0..1));
+
+        let (diagnostics, outcome) = sink.finish();
+        assert_eq!(ranges(&diagnostics), vec![0..1, 4..7, 11..11]);
+        assert_eq!(outcome.errors, 3);
+        assert_eq!(outcome.duplicates, 3);
+        assert!(!outcome.is_success());
+    }
+
+    #[test]
+    fn test_dedup_only_same_code() {
+        let mut sink = DiagnosticSink::new();
+        sink.push(diagnostic(Severity::Error, 0..20).code("E0002"));
+        sink.push(diagnostic(Severity::Error, 5..8).code("E0006"));
+        sink.push(diagnostic(Severity::Error, 6..9).code("E0006"));
+        sink.push(diagnostic(Severity::Warning, 12..14).code("E0007"));
+        sink.push(diagnostic(Severity::Warning, 0..20).code("E0007"));
+
+        // The name error inside the parse error is unrelated to it, but the
+        // second one overlapping it has the same code, and the last one is
+        // at the same location as the parse error
+        let (diagnostics, outcome) = sink.finish();
+        assert_eq!(ranges(&diagnostics), vec![0..20, 5..8, 12..14]);
+        assert_eq!(outcome.duplicates, 2);
+    }
+
+    #[test]
+    fn test_notes_and_errors_fail() {
+        let mut sink = DiagnosticSink::new();
+        sink.push(diagnostic(Severity::Note, 0..1));
+        sink.push(diagnostic(Severity::Error, 2..3));
+        assert!(!sink.finish().1.is_success());
+
+        let mut sink = DiagnosticSink::new();
+        sink.push(diagnostic(Severity::Note, 0..1));
+        sink.push(diagnostic(Severity::Warning, 2..3));
+        assert!(sink.finish().1.is_success());
+    }
+
+    #[test]
+    fn test_error_limit() {
+        let mut sink = DiagnosticSink::new().error_limit(2);
+        sink.extend((0..5).map(|i| diagnostic(Severity::Error, i..i + 1)));
+        sink.push(diagnostic(Severity::Warning, 9..10));
+
+        let (diagnostics, outcome) = sink.finish();
+        assert_eq!(ranges(&diagnostics), vec![0..1, 1..2, 9..10]);
+        assert_eq!(outcome.errors, 5);
+        assert_eq!(outcome.omitted, 3);
+    }
+
+    #[test]
+    fn test_level_overrides() {
+        let mut sink = DiagnosticSink::new()
+            .level("e0002", Level::Warn)
+            .level("E0003", Level::Allow)
+            .level(WARNINGS, Level::Deny);
+
+        sink.push(diagnostic(Severity::Error, 0..1).code("E0002"));
+        sink.push(diagnostic(Severity::Error, 2..3).code("E0003"));
+        sink.push(diagnostic(Severity::Warning, 4..5));
+        sink.push(diagnostic(Severity::Bug, 6..7).code("E0003"));
+
+        let (diagnostics, outcome) = sink.finish();
+        let severities = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.severity)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            severities,
+            vec![Severity::Warning, Severity::Error, Severity::Bug]
+        );
+        assert_eq!(outcome.allowed, 1);
+        assert_eq!(outcome.warnings, 1);
+    }
+}
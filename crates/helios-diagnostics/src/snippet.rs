@@ -0,0 +1,768 @@
+//! Rendering snippets of source text with annotated spans.
+//!
+//! The [`Renderer`] structure prints the lines a list of [`Span`]s touch, or
+//! the lines the edits of a suggested fix would change.
+
+use colored::Color;
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::ops::Range;
+
+use crate::column::{self, expand_tabs, ColumnKind};
+use crate::config::{EmitConfig, Painter};
+use crate::files::FileInspector;
+use crate::Result;
+
+/// The maximum number of lines of a multi-line span shown at its start and at
+/// its end. The lines in between are elided.
+const CONTEXT_LINES: usize = 2;
+
+/// The width of the line number in the gutter.
+const GUTTER_WIDTH: usize = 4;
+
+/// The characters snippets are drawn with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct Glyphs {
+    /// Separates the gutter from the source text.
+    pub gutter: char,
+    /// Marks the lines covered by a multi-line span in the left margin.
+    pub margin: char,
+    /// Connects the margin to where a multi-line span starts or ends.
+    pub connector: char,
+    /// The margin marker on the line a multi-line span starts on.
+    pub start: char,
+    /// The margin marker on the line a multi-line span ends on.
+    pub end: char,
+}
+
+impl Glyphs {
+    pub const ASCII: Self = Self {
+        gutter: '|',
+        margin: '|',
+        connector: '_',
+        start: ' ',
+        end: '|',
+    };
+
+    pub const UNICODE: Self = Self {
+        gutter: '│',
+        margin: '│',
+        connector: '─',
+        start: '╭',
+        end: '╰',
+    };
+
+    /// The underline drawn by the box-drawing counterpart of the given ASCII
+    /// underline.
+    pub fn underline(&self, ascii: char) -> char {
+        if *self == Self::ASCII {
+            return ascii;
+        }
+
+        match ascii {
+            '^' => '━',
+            '~' => '┅',
+            '-' => '─',
+            other => other,
+        }
+    }
+}
+
+/// How a [`Span`] is drawn.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct SpanStyle {
+    pub color: Color,
+    pub underline: char,
+}
+
+/// A range of a file to annotate in a snippet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Span {
+    pub range: Range<usize>,
+    pub label: Option<String>,
+    pub style: SpanStyle,
+}
+
+/// A [`Span`] resolved to line and column positions.
+struct ResolvedSpan<'s> {
+    span: &'s Span,
+    start_line: usize,
+    end_line: usize,
+    /// The column the span starts at on its first line.
+    start_column: usize,
+    /// The column of the last character of the span on its last line.
+    end_column: usize,
+    /// The index of the column this span occupies in the left margin (only
+    /// for spans covering multiple lines).
+    margin: Option<usize>,
+}
+
+impl ResolvedSpan<'_> {
+    fn is_multiline(&self) -> bool {
+        self.margin.is_some()
+    }
+
+    /// Determines if the margin marker of this span is drawn next to the given
+    /// line (i.e. the line is after the span's first line, up to and including
+    /// its last line).
+    fn covers(&self, line: usize) -> bool {
+        self.is_multiline() && self.start_line < line && line <= self.end_line
+    }
+}
+
+/// Draws snippets of source text as configured by an [`EmitConfig`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Renderer {
+    pub painter: Painter,
+    pub glyphs: Glyphs,
+    pub tab_width: usize,
+    pub context_lines: usize,
+}
+
+impl Renderer {
+    pub fn new(config: &EmitConfig, painter: Painter) -> Self {
+        Self {
+            painter,
+            glyphs: if config.ascii_only {
+                Glyphs::ASCII
+            } else {
+                Glyphs::UNICODE
+            },
+            tab_width: config.tab_width,
+            context_lines: config.context_lines,
+        }
+    }
+
+    /// The gutter next to the given line, or next to the lines annotating it
+    /// if there is no line number.
+    fn gutter(&self, line_number: Option<&str>) -> String {
+        let line_number = line_number.unwrap_or_default();
+        let gutter =
+            format!("{line_number:>GUTTER_WIDTH$} {} ", self.glyphs.gutter);
+        self.painter.dimmed(gutter)
+    }
+
+    /// Writes the lines of the file touched by the given spans, annotating
+    /// each span (and its label if it has one).
+    pub fn render_snippet<'a, F: FileInspector<'a>>(
+        &self,
+        f: &mut dyn Write,
+        inspector: &'a F,
+        file_id: F::FileId,
+        spans: &[Span],
+    ) -> Result<()> {
+        let painter = self.painter;
+        let glyphs = self.glyphs;
+        let source = inspector.source(file_id)?;
+        let source = source.as_ref();
+
+        // The range of the line at the given index, excluding its line feed
+        let content_range = |line_index: usize| -> Result<Range<usize>> {
+            let range = inspector.line_range(file_id, line_index)?;
+            let line = &source[range.clone()];
+            let len = line.trim_end_matches(['\n', '\r']).len();
+            Ok(range.start..range.start + len)
+        };
+
+        // The display column of the given byte index on the line starting at
+        // the given byte index
+        let column = |line_start: usize, byte_index: usize| {
+            let line = &source[line_start..];
+            let line = &line[..line.find('\n').unwrap_or(line.len())];
+            let kind = ColumnKind::Display {
+                tab_width: self.tab_width,
+            };
+            column::column_at(line, byte_index - line_start, kind)
+        };
+
+        let mut margin_count = 0;
+        let mut resolved = Vec::with_capacity(spans.len());
+
+        for span in spans {
+            let start = span.range.start.min(source.len());
+            let last = span.range.end.max(start + 1) - 1;
+            let last = last.min(source.len());
+
+            let start_line = inspector.line_index(file_id, start)?;
+            let end_line = inspector.line_index(file_id, last)?;
+            let start_content = content_range(start_line)?;
+            let end_content = content_range(end_line)?;
+
+            let margin = if end_line > start_line {
+                margin_count += 1;
+                Some(margin_count - 1)
+            } else {
+                None
+            };
+
+            let start_column = column(start_content.start, start);
+            let end_column = if margin.is_some() {
+                // Don't point at the line feed if the span ends with one
+                let last = if last >= end_content.end && !end_content.is_empty()
+                {
+                    end_content.end - 1
+                } else {
+                    last
+                };
+                column(end_content.start, last)
+            } else {
+                // Single-line spans are underlined until their end (but at
+                // least once, even if they're empty or only cover the line
+                // feed)
+                let end = span.range.end.min(start_content.end).max(start);
+                (column(start_content.start, end)).max(start_column + 1) - 1
+            };
+
+            resolved.push(ResolvedSpan {
+                span,
+                start_line,
+                end_line,
+                start_column,
+                end_column,
+                margin,
+            });
+        }
+
+        // The lines to show, where multi-line spans only show a few lines of
+        // context at their start and at their end
+        let mut lines = BTreeSet::new();
+        // The empty line after a trailing line feed is never shown as context
+        let last_line =
+            inspector.line_index(file_id, source.len().saturating_sub(1))?;
+        for span in &resolved {
+            let context_end =
+                span.end_line.min(span.start_line + CONTEXT_LINES - 1);
+            let context_start = span
+                .end_line
+                .saturating_sub(CONTEXT_LINES - 1)
+                .max(span.start_line);
+            let before = span.start_line.saturating_sub(self.context_lines);
+            let after = (span.end_line + self.context_lines)
+                .min(last_line)
+                .max(span.end_line);
+            lines.extend(before..=context_end);
+            lines.extend(context_start..=after);
+        }
+
+        let margin_width = if margin_count > 0 {
+            margin_count + 1
+        } else {
+            0
+        };
+
+        // The left margin next to the given line, where `marker` overrides
+        // the column of a span (and fills every column after it)
+        let margin_at = |line: usize, marker: Option<(usize, char, char)>| {
+            let mut margin = String::new();
+            let mut fill = ' ';
+
+            for span in &resolved {
+                let Some(index) = span.margin else { continue };
+                let color = span.span.style.color;
+                let c = match marker {
+                    Some((marker_index, c, rest)) if marker_index == index => {
+                        fill = rest;
+                        painter.color(c, color)
+                    }
+                    _ if fill != ' ' => painter.color(fill, color),
+                    _ if span.covers(line) => {
+                        painter.color(glyphs.margin, color)
+                    }
+                    _ => " ".to_string(),
+                };
+                margin.push_str(&c);
+            }
+
+            if margin_width > 0 {
+                margin.push(fill);
+            }
+
+            margin
+        };
+
+        let mut previous_line: Option<usize> = None;
+
+        for &line in &lines {
+            if previous_line.is_some_and(|previous| line > previous + 1) {
+                let margin = margin_at(line, None);
+                writeln!(
+                    f,
+                    "{}{}",
+                    self.gutter(Some("...")),
+                    margin.trim_end()
+                )?;
+            }
+            previous_line = Some(line);
+
+            let margin = margin_at(line, None);
+            let text = &source[content_range(line)?];
+            let text = expand_tabs(text, 0, self.tab_width).0;
+            let row = format!("{margin}{text}");
+            writeln!(
+                f,
+                "{}{}",
+                self.gutter(Some(&(line + 1).to_string())),
+                row.trim_end()
+            )?;
+
+            // Underline the single-line spans on this line
+            let mut single_line_spans = resolved
+                .iter()
+                .filter(|span| !span.is_multiline() && span.start_line == line)
+                .collect::<Vec<_>>();
+            single_line_spans.sort_by_key(|span| span.start_column);
+
+            for span in single_line_spans {
+                let margin = margin_at(line, None);
+                let style = span.span.style;
+                let offset = " ".repeat(span.start_column);
+                let count = span.end_column - span.start_column + 1;
+                let underline =
+                    glyphs.underline(style.underline).to_string().repeat(count);
+                write!(
+                    f,
+                    "{}{margin}{offset}{}",
+                    self.gutter(None),
+                    painter.color(underline, style.color)
+                )?;
+                self.write_label(f, span)?;
+            }
+
+            // Connect the starts and ends of multi-line spans to the margin
+            for span in &resolved {
+                let Some(index) = span.margin else { continue };
+                let style = span.span.style;
+                let underline = glyphs.underline(style.underline);
+                let connector = glyphs.connector;
+
+                if span.start_line == line {
+                    let marker = Some((index, glyphs.start, connector));
+                    let margin = margin_at(line, marker);
+                    let connectors =
+                        connector.to_string().repeat(span.start_column);
+                    writeln!(
+                        f,
+                        "{}{margin}{}{}",
+                        self.gutter(None),
+                        painter.color(connectors, style.color),
+                        painter.color(underline, style.color)
+                    )?;
+                } else if span.end_line == line {
+                    let marker = Some((index, glyphs.end, connector));
+                    let margin = margin_at(line, marker);
+                    let connectors =
+                        connector.to_string().repeat(span.end_column);
+                    write!(
+                        f,
+                        "{}{margin}{}{}",
+                        self.gutter(None),
+                        painter.color(connectors, style.color),
+                        painter.color(underline, style.color)
+                    )?;
+                    self.write_label(f, span)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the lines of the file touched by the given edits as they would
+    /// read once the edits are applied, marking inserted text with `+`. Lines
+    /// where text is only removed are written as they currently read, with the
+    /// removed text marked with `-`.
+    ///
+    /// Returns `false` without writing anything if an edit can't be shown on a
+    /// single line (i.e. it spans several lines or inserts a line feed).
+    pub fn render_edits<'a, F: FileInspector<'a>>(
+        &self,
+        f: &mut dyn Write,
+        inspector: &'a F,
+        file_id: F::FileId,
+        edits: &[(Range<usize>, &str)],
+        color: Color,
+    ) -> Result<bool> {
+        let source = inspector.source(file_id)?;
+        let source = source.as_ref();
+
+        let mut lines = Vec::<(usize, Vec<(Range<usize>, &str)>)>::new();
+        for (range, replacement) in edits {
+            let line = inspector.line_index(file_id, range.start)?;
+            let line_range = inspector.line_range(file_id, line)?;
+            let content_end = line_range.start
+                + source[line_range.clone()]
+                    .trim_end_matches(['\n', '\r'])
+                    .len();
+
+            if range.end > content_end || replacement.contains('\n') {
+                return Ok(false);
+            }
+
+            match lines.iter_mut().find(|(index, _)| *index == line) {
+                Some((_, edits)) => edits.push((range.clone(), replacement)),
+                None => lines.push((line, vec![(range.clone(), replacement)])),
+            }
+        }
+        lines.sort_by_key(|(line, _)| *line);
+
+        for (line, mut edits) in lines {
+            edits.sort_by_key(|(range, _)| (range.start, range.end));
+
+            let line_range = inspector.line_range(file_id, line)?;
+            let content =
+                source[line_range.clone()].trim_end_matches(['\n', '\r']);
+            let only_deletions = edits.iter().all(|(_, text)| text.is_empty());
+
+            let mut text = String::new();
+            let mut markers = String::new();
+            let mut column = 0;
+            let mut cursor = line_range.start;
+
+            // Appends a piece of the line, marking it with the given character
+            let mut push = |piece: &str, marker: char| {
+                let (expanded, end) =
+                    expand_tabs(piece, column, self.tab_width);
+                text.push_str(&expanded);
+                markers.push_str(&marker.to_string().repeat(end - column));
+                column = end;
+            };
+
+            for (range, replacement) in edits {
+                if range.start < cursor {
+                    continue;
+                }
+
+                push(&source[cursor..range.start], ' ');
+
+                if only_deletions {
+                    push(&source[range.clone()], '-');
+                } else {
+                    push(replacement, '+');
+                }
+
+                cursor = range.end;
+            }
+            push(&source[cursor..line_range.start + content.len()], ' ');
+
+            let line_number = (line + 1).to_string();
+            writeln!(
+                f,
+                "{}{}",
+                self.gutter(Some(&line_number)),
+                text.trim_end()
+            )?;
+            writeln!(
+                f,
+                "{}{}",
+                self.gutter(None),
+                self.painter.color(markers.trim_end(), color)
+            )?;
+        }
+
+        Ok(true)
+    }
+
+    /// Writes the label of the given span (if any) and ends the line.
+    fn write_label(
+        &self,
+        f: &mut dyn Write,
+        span: &ResolvedSpan,
+    ) -> Result<()> {
+        match &span.span.label {
+            Some(label) => writeln!(
+                f,
+                " {}",
+                self.painter.color(label, span.span.style.color)
+            )?,
+            None => writeln!(f)?,
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::files::OneFile;
+    use expect_test::{expect, Expect};
+
+    const PRIMARY: SpanStyle = SpanStyle {
+        color: Color::Red,
+        underline: '^',
+    };
+
+    const SECONDARY: SpanStyle = SpanStyle {
+        color: Color::Blue,
+        underline: '-',
+    };
+
+    fn span(
+        range: Range<usize>,
+        label: Option<&str>,
+        style: SpanStyle,
+    ) -> Span {
+        Span {
+            range,
+            label: label.map(str::to_string),
+            style,
+        }
+    }
+
+    fn renderer(config: EmitConfig) -> Renderer {
+        let config = config.color(crate::config::ColorChoice::Never);
+        Renderer::new(&config, config.painter())
+    }
+
+    fn check(source: &str, spans: Vec<Span>, expected: Expect) {
+        check_with(EmitConfig::new(), source, spans, expected);
+    }
+
+    fn check_with(
+        config: EmitConfig,
+        source: &str,
+        spans: Vec<Span>,
+        expected: Expect,
+    ) {
+        let file = OneFile::new("test.hl", source);
+        // The header keeps `expect!` from trimming the gutter's indentation
+        let mut output = b"-> test.hl\n".to_vec();
+        renderer(config)
+            .render_snippet(&mut output, &file, (), &spans)
+            .unwrap();
+        expected.assert_eq(&String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_render_single_line() {
+        check(
+            "let a = $\n",
+            vec![span(8..9, None, PRIMARY)],
+            expect![[r#"
+                -> test.hl
+                   1 | let a = $
+                     |         ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_empty_span_at_end_of_file() {
+        check(
+            "let a =",
+            vec![span(7..7, None, PRIMARY)],
+            expect![[r#"
+                -> test.hl
+                   1 | let a =
+                     |        ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_labels_on_same_line() {
+        check(
+            "let a = a + b",
+            vec![
+                span(12..13, None, PRIMARY),
+                span(4..5, Some("first defined here"), SECONDARY),
+            ],
+            expect![[r#"
+                -> test.hl
+                   1 | let a = a + b
+                     |     - first defined here
+                     |             ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_multiple_lines() {
+        check(
+            "let a = (1 +\n  2 +\n  3)\nlet b = a\n",
+            vec![span(8..24, Some("this expression"), PRIMARY)],
+            expect![[r#"
+                -> test.hl
+                   1 |   let a = (1 +
+                     |  _________^
+                   2 | |   2 +
+                   3 | |   3)
+                     | |____^ this expression
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_multiple_lines_with_elided_lines() {
+        check(
+            "(1 +\n2 +\n3 +\n4 +\n5 +\n6)",
+            vec![span(0..27, None, PRIMARY)],
+            expect![[r#"
+                -> test.hl
+                   1 |   (1 +
+                     |  _^
+                   2 | | 2 +
+                 ... | |
+                   5 | | 5 +
+                   6 | | 6)
+                     | |__^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_multiple_and_single_line_spans() {
+        check(
+            "let a = 1\nlet b = (a +\n  a)\n",
+            vec![
+                span(18..27, None, PRIMARY),
+                span(4..5, Some("defined here"), SECONDARY),
+            ],
+            expect![[r#"
+                -> test.hl
+                   1 |   let a = 1
+                     |       - defined here
+                   2 |   let b = (a +
+                     |  _________^
+                   3 | |   a)
+                     | |____^
+            "#]],
+        );
+    }
+
+    fn check_edits(
+        source: &str,
+        edits: &[(Range<usize>, &str)],
+        expected: Expect,
+    ) {
+        let file = OneFile::new("test.hl", source);
+        let mut output = b"-> test.hl\n".to_vec();
+        let rendered = renderer(EmitConfig::new())
+            .render_edits(&mut output, &file, (), edits, Color::Green)
+            .unwrap();
+        assert!(rendered);
+        expected.assert_eq(&String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn test_render_edits() {
+        check_edits(
+            "let let = (1 +\n  2\n",
+            &[(4..7, "let_"), (18..18, ")")],
+            expect![[r#"
+                -> test.hl
+                   1 | let let_ = (1 +
+                     |     ++++
+                   2 |   2)
+                     |    +
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_deletions() {
+        check_edits(
+            "let a = 1 $\n",
+            &[(9..11, "")],
+            expect![[r#"
+                -> test.hl
+                   1 | let a = 1 $
+                     |          --
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_multi_line_edits_is_skipped() {
+        let file = OneFile::new("test.hl", "let a =\n  1\n");
+        let mut output = Vec::new();
+        let edits = [(4..11, "b")];
+        let rendered = renderer(EmitConfig::new())
+            .render_edits(&mut output, &file, (), &edits, Color::Green)
+            .unwrap();
+        assert!(!rendered);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_render_span_ending_with_line_feed() {
+        check(
+            "abc\ndef\n",
+            vec![span(0..4, None, PRIMARY)],
+            expect![[r#"
+                -> test.hl
+                   1 | abc
+                     | ^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_with_tabs() {
+        check_with(
+            EmitConfig::new().tab_width(4),
+            "let a =\t$\n",
+            vec![span(8..9, None, PRIMARY), span(7..8, None, SECONDARY)],
+            expect![[r#"
+                -> test.hl
+                   1 | let a = $
+                     |        -
+                     |         ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_wide_characters() {
+        check(
+            "let 名前 = \"😀\" $\n",
+            vec![
+                span(4..10, Some("a name"), SECONDARY),
+                span(20..21, None, PRIMARY),
+            ],
+            expect![[r#"
+                -> test.hl
+                   1 | let 名前 = "😀" $
+                     |     ---- a name
+                     |                 ^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_with_context_lines() {
+        check_with(
+            EmitConfig::new().context_lines(1),
+            "let a = 1\nlet b = $\nlet c = 3\nlet d = 4\n",
+            vec![span(18..19, None, PRIMARY)],
+            expect![[r#"
+                -> test.hl
+                   1 | let a = 1
+                   2 | let b = $
+                     |         ^
+                   3 | let c = 3
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_render_unicode_glyphs() {
+        check_with(
+            EmitConfig::new().ascii_only(false),
+            "let a = (1 +\n  2)\nlet b = a\n",
+            vec![
+                span(8..17, Some("this expression"), PRIMARY),
+                span(27..28, None, SECONDARY),
+            ],
+            expect![[r#"
+                -> test.hl
+                   1 │   let a = (1 +
+                     │ ╭─────────━
+                   2 │ │   2)
+                     │ ╰────━ this expression
+                   3 │   let b = a
+                     │            ─
+            "#]],
+        );
+    }
+}
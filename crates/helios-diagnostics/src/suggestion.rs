@@ -0,0 +1,167 @@
+//! Suggested fixes attached to diagnostics.
+//!
+//! A [`Suggestion`] describes a fix along with the [`Edit`]s that carry it out,
+//! which can be applied with [`apply_edits`].
+
+use serde::Serialize;
+use std::ops::Range;
+
+use crate::diagnostic::Location;
+
+/// How confident we are that a [`Suggestion`] fixes the issue it's attached
+/// to.
+#[derive(
+    Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended and may be
+    /// applied automatically.
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it's uncertain. It
+    /// should only be applied after a human has reviewed it.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders (e.g. `<name>`) that must be
+    /// filled in before it can be applied.
+    HasPlaceholders,
+    /// The applicability of the suggestion is unknown.
+    #[default]
+    Unspecified,
+}
+
+/// A replacement of a range of a file with new text.
+///
+/// An edit with an empty range is an insertion, and an edit with an empty
+/// replacement is a deletion.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Edit<FileId> {
+    pub location: Location<FileId>,
+    pub replacement: String,
+}
+
+impl<FileId> Edit<FileId> {
+    pub fn new(
+        location: Location<FileId>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        Self {
+            location,
+            replacement: replacement.into(),
+        }
+    }
+
+    pub fn insert(
+        file_id: FileId,
+        offset: usize,
+        text: impl Into<String>,
+    ) -> Self {
+        Self::new(Location::new(file_id, offset..offset), text)
+    }
+
+    pub fn delete(location: Location<FileId>) -> Self {
+        Self::new(location, "")
+    }
+}
+
+/// A fix for the issue described by a diagnostic.
+///
+/// The message is shown after "try", so it should read as the rest of that
+/// sentence (e.g. "renaming the binding").
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Suggestion<FileId> {
+    pub message: String,
+    pub edits: Vec<Edit<FileId>>,
+    pub applicability: Applicability,
+}
+
+impl<FileId> Suggestion<FileId> {
+    pub fn new(
+        message: impl Into<String>,
+        applicability: Applicability,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            edits: Vec::new(),
+            applicability,
+        }
+    }
+
+    pub fn edit(mut self, edit: Edit<FileId>) -> Self {
+        self.edits.push(edit);
+        self
+    }
+
+    pub fn is_machine_applicable(&self) -> bool {
+        self.applicability == Applicability::MachineApplicable
+    }
+}
+
+/// Applies the given edits to a source text, returning the edited text and
+/// the number of edits that were applied.
+///
+/// Edits are applied in order of their position in the source text. An edit
+/// that overlaps an edit that was already applied (or that points outside of
+/// the source text or inside of a character) is skipped. Several insertions
+/// at the same offset are applied in the order they were given.
+pub fn apply_edits(
+    source: &str,
+    edits: &[(Range<usize>, &str)],
+) -> (String, usize) {
+    let mut sorted = edits.iter().enumerate().collect::<Vec<_>>();
+    sorted.sort_by_key(|(index, (range, _))| (range.start, range.end, *index));
+
+    let mut output = String::with_capacity(source.len());
+    let mut cursor = 0;
+    let mut applied = 0;
+
+    for (_, (range, replacement)) in sorted {
+        let is_valid = range.start <= range.end
+            && range.end <= source.len()
+            && source.is_char_boundary(range.start)
+            && source.is_char_boundary(range.end);
+
+        // An edit may start right where the previous one ended
+        let overlaps = range.start < cursor;
+
+        if !is_valid || overlaps {
+            continue;
+        }
+
+        output.push_str(&source[cursor..range.start]);
+        output.push_str(replacement);
+        cursor = range.end;
+        applied += 1;
+    }
+
+    output.push_str(&source[cursor..]);
+    (output, applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_edits() {
+        let (output, applied) =
+            apply_edits("let let = (1 + 2", &[(16..16, ")"), (4..7, "let_")]);
+        assert_eq!(output, "let let_ = (1 + 2)");
+        assert_eq!(applied, 2);
+    }
+
+    #[test]
+    fn test_apply_edits_skips_overlapping_edits() {
+        let (output, applied) =
+            apply_edits("abcdef", &[(1..4, "X"), (2..3, "Y"), (4..4, "Z")]);
+        assert_eq!(output, "aXZef");
+        assert_eq!(applied, 2);
+    }
+
+    #[test]
+    fn test_apply_edits_skips_invalid_ranges() {
+        let (output, applied) =
+            apply_edits("é", &[(1..1, "X"), (0..10, "Y"), (2..2, "!")]);
+        assert_eq!(output, "é!");
+        assert_eq!(applied, 1);
+    }
+}
@@ -0,0 +1,481 @@
+//! Formatting Helios source files.
+//!
+//! The [`format()`] function prints a syntax tree back out with normalized
+//! spacing and indentation, keeping every comment where it was written.
+
+use helios_syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
+use rowan::NodeOrToken;
+
+/// Options to configure the output of the formatter.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FormatOptions {
+    /// The maximum width of each line before binary expressions are wrapped.
+    pub max_width: usize,
+    /// The number of spaces each block is indented by.
+    pub indent_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            max_width: 80,
+            indent_width: 4,
+        }
+    }
+}
+
+/// Parses and formats the given source text.
+pub fn format_source(source: &str, options: &FormatOptions) -> String {
+    let parse = helios_parser::parse((), source);
+    format(&parse.syntax(), options)
+}
+
+/// Formats the given syntax tree, returning the formatted source text.
+pub fn format(root: &SyntaxNode, options: &FormatOptions) -> String {
+    let mut printer = Printer::new(options);
+    printer.reindent = !has_inconsistent_dedent(root);
+    printer.node(root);
+    printer.finish()
+}
+
+/// What a significant token is to the [`Printer`], which determines whether a
+/// space is needed between it and the token before it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Role {
+    /// The operator of a binary expression, or the `=` of a binding.
+    Operator(SyntaxKind),
+    PrefixOperator,
+    Token(SyntaxKind),
+}
+
+struct Printer<'a> {
+    options: &'a FormatOptions,
+    output: String,
+    /// The indentation of the current line, written before its first token.
+    indent: String,
+    /// The number of blocks the current line is in.
+    depth: usize,
+    /// Whether lines are indented according to their depth, rather than kept
+    /// as they were written.
+    reindent: bool,
+    /// The number of consecutive line feeds written so far.
+    line_feeds: usize,
+    /// The number of characters on the current line.
+    column: usize,
+    /// The role of the last token written on the current line, if any.
+    previous: Option<Role>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(options: &'a FormatOptions) -> Self {
+        Self {
+            options,
+            output: String::new(),
+            indent: String::new(),
+            depth: 0,
+            reindent: false,
+            line_feeds: 0,
+            column: 0,
+            previous: None,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        let len = self.output.trim_end().len();
+        self.output.truncate(len);
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+        self.output
+    }
+
+    fn is_at_line_start(&self) -> bool {
+        self.column == 0
+    }
+
+    fn node(&mut self, node: &SyntaxNode) {
+        let mut after_operator = false;
+
+        for child in node.children_with_tokens() {
+            match child {
+                NodeOrToken::Node(child) => {
+                    if after_operator {
+                        self.wrap_if_needed(&child);
+                        after_operator = false;
+                    }
+                    self.node(&child);
+                }
+                NodeOrToken::Token(token) => {
+                    if node.kind() == SyntaxKind::Exp_Binary
+                        && token.kind().is_symbol()
+                    {
+                        after_operator = true;
+                    }
+                    self.token(&token);
+                }
+            }
+        }
+    }
+
+    fn token(&mut self, token: &SyntaxToken) {
+        match token.kind() {
+            SyntaxKind::Whitespace => {}
+            SyntaxKind::Newline | SyntaxKind::Indent | SyntaxKind::Dedent => {
+                self.layout(token)
+            }
+            SyntaxKind::Comment | SyntaxKind::DocComment => {
+                self.comment(token.text())
+            }
+            // A line with an inconsistent dedent is kept as it was written
+            SyntaxKind::Error if token.text().starts_with('\n') => {
+                let line = token.text().trim_start_matches('\n');
+                let text = line.trim_start_matches(' ');
+                self.line_feed(line[..line.len() - text.len()].to_string());
+                self.write(text.trim_end());
+                self.previous = Some(Role::Token(SyntaxKind::Error));
+            }
+            _ => {
+                let role = role(token);
+                if !self.is_at_line_start() && self.needs_space_before(role) {
+                    self.write(" ");
+                }
+
+                self.write(token.text());
+                self.previous = Some(role);
+            }
+        }
+    }
+
+    fn needs_space_before(&self, next: Role) -> bool {
+        use SyntaxKind::*;
+
+        let kind = |role| match role {
+            Role::Operator(kind) | Role::Token(kind) => Some(kind),
+            Role::PrefixOperator => None,
+        };
+
+        // A prefix operator followed by another symbol would be merged with
+        // it (e.g. `-` and `>`)
+        let previous = match self.previous {
+            Some(Role::PrefixOperator) => {
+                return kind(next).is_some_and(|kind| {
+                    kind.is_symbol() && kind != Sym_LParen
+                });
+            }
+            Some(previous) => previous,
+            None => return false,
+        };
+        let is_number =
+            |role| matches!(kind(role), Some(Lit_Integer | Lit_Float));
+
+        match (kind(previous), kind(next)) {
+            (Some(Sym_LParen | Sym_LBracket), _)
+            | (
+                _,
+                Some(Sym_RParen | Sym_RBracket | Sym_Comma | Sym_Semicolon),
+            ) => false,
+            // A dot next to a number would make it a float
+            (Some(Sym_Dot), _) => is_number(next),
+            (_, Some(Sym_Dot)) => is_number(previous),
+            _ if matches!(previous, Role::Operator(_))
+                || matches!(next, Role::Operator(_)) =>
+            {
+                true
+            }
+            (
+                Some(Identifier | Sym_RParen | Sym_RBracket),
+                Some(Sym_LParen),
+            ) => false,
+            _ => true,
+        }
+    }
+
+    /// Wraps the current line if the given operand of a binary expression
+    /// doesn't fit on it.
+    fn wrap_if_needed(&mut self, operand: &SyntaxNode) {
+        if self.is_at_line_start() {
+            return;
+        }
+
+        let width = flat_width(operand);
+        if self.column + 1 + width > self.options.max_width {
+            self.line_feed(self.indent.clone());
+        }
+    }
+
+    /// Writes the line feed of a `Newline`, `Indent` or `Dedent` token, and
+    /// enters or leaves a block if needed.
+    fn layout(&mut self, token: &SyntaxToken) {
+        match token.kind() {
+            SyntaxKind::Indent => self.depth += 1,
+            SyntaxKind::Dedent => self.depth = self.depth.saturating_sub(1),
+            _ => {}
+        }
+
+        // Zero-width `Dedent` tokens only appear at the end of the file.
+        if token.text().is_empty() {
+            return;
+        }
+
+        let indent = if self.reindent {
+            " ".repeat(self.depth * self.options.indent_width)
+        } else {
+            token.text().trim_start_matches(['\n', '\r']).to_string()
+        };
+
+        // Leaving several blocks at once gives a `Dedent` token for each one,
+        // but there's only one line feed.
+        let is_same_line_feed = token.kind() == SyntaxKind::Dedent
+            && token
+                .prev_token()
+                .is_some_and(|prev| prev.kind() == SyntaxKind::Dedent);
+        if is_same_line_feed {
+            self.indent = indent;
+        } else {
+            self.line_feed(indent);
+        }
+    }
+
+    /// Writes a line feed, followed by the given indentation once the next
+    /// token is written.
+    fn line_feed(&mut self, indent: String) {
+        // Remove any leading line feeds.
+        if self.output.is_empty() {
+            return;
+        }
+
+        self.line_feeds += 1;
+        if self.line_feeds <= 2 {
+            let len = self.output.trim_end_matches(' ').len();
+            self.output.truncate(len);
+            self.output.push('\n');
+        }
+
+        self.indent = indent;
+        self.column = 0;
+        self.previous = None;
+    }
+
+    fn comment(&mut self, text: &str) {
+        if !self.is_at_line_start() {
+            self.write(" ");
+        }
+
+        self.write(text.trim_end());
+        self.previous = None;
+    }
+
+    fn write(&mut self, text: &str) {
+        if self.is_at_line_start() {
+            self.output.push_str(&self.indent);
+            self.column = self.indent.chars().count();
+        }
+
+        self.output.push_str(text);
+        self.column += text.chars().count();
+        self.line_feeds = 0;
+    }
+}
+
+/// The role of the given significant token.
+fn role(token: &SyntaxToken) -> Role {
+    let kind = token.kind();
+    let parent = match token.parent() {
+        Some(parent) => parent,
+        None => return Role::Token(kind),
+    };
+
+    match parent.kind() {
+        SyntaxKind::Exp_Binary if kind.is_symbol() => Role::Operator(kind),
+        SyntaxKind::Dec_GlobalBinding if kind == SyntaxKind::Sym_Eq => {
+            Role::Operator(kind)
+        }
+        SyntaxKind::Exp_UnaryPrefix
+            if parent.first_token().as_ref() == Some(token) =>
+        {
+            Role::PrefixOperator
+        }
+        _ => Role::Token(kind),
+    }
+}
+
+/// Determines if a line of the given tree dedents to a column that no block
+/// started at, which the parser turns into an `Error` token starting with the
+/// line feed.
+fn has_inconsistent_dedent(root: &SyntaxNode) -> bool {
+    root.descendants_with_tokens()
+        .filter_map(NodeOrToken::into_token)
+        .any(|token| {
+            token.kind() == SyntaxKind::Error && token.text().starts_with('\n')
+        })
+}
+
+/// The width of the first line of the given node when formatted on its own
+/// without any wrapping.
+fn flat_width(node: &SyntaxNode) -> usize {
+    let options = FormatOptions {
+        max_width: usize::MAX,
+        ..FormatOptions::default()
+    };
+
+    let mut printer = Printer::new(&options);
+    printer.node(node);
+    printer
+        .output
+        .lines()
+        .next()
+        .map_or(0, |line| line.chars().count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The kinds of the tokens significant to the parser in the given source.
+    fn significant_kinds(source: &str) -> Vec<SyntaxKind> {
+        helios_parser::parse((), source)
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(NodeOrToken::into_token)
+            .map(|token| token.kind())
+            .filter(|kind| !kind.is_trivia())
+            .collect()
+    }
+
+    /// The titles of the diagnostics of the parser for the given source.
+    fn errors(source: &str) -> Vec<String> {
+        helios_parser::parse((), source)
+            .messages()
+            .iter()
+            .map(|message| message.generate_diagnostic().title)
+            .collect()
+    }
+
+    fn check_with(options: FormatOptions, input: &str, expected: &str) {
+        let formatted = format_source(input, &options);
+        assert_eq!(formatted, expected);
+
+        // Formatting must never change the meaning of the program, nor make
+        // it invalid...
+        assert_eq!(significant_kinds(&formatted), significant_kinds(input));
+        assert_eq!(errors(&formatted), errors(input));
+
+        // ...and formatting twice must give the same output.
+        assert_eq!(format_source(&formatted, &options), formatted);
+    }
+
+    fn check(input: &str, expected: &str) {
+        check_with(FormatOptions::default(), input, expected);
+    }
+
+    #[test]
+    fn test_format_empty() {
+        check("", "");
+        check("\n\n", "");
+    }
+
+    #[test]
+    fn test_format_global_binding() {
+        check("let   foo=bar", "let foo = bar\n");
+        check("  let foo =    1+2   \n", "let foo = 1 + 2\n");
+    }
+
+    #[test]
+    fn test_format_expressions() {
+        check("-(2-((10+10)))*20+-5", "-(2 - ((10 + 10))) * 20 + -5\n");
+        check("( a )", "(a)\n");
+        check("a;b ; c", "a; b; c\n");
+        check("! a", "!a\n");
+        check("1 <  - x", "1 < -x\n");
+        assert!(errors("-(2 - ((10 + 10))) * 20 + -5\n").is_empty());
+    }
+
+    #[test]
+    fn test_format_punctuation() {
+        check("f (x)", "f(x)\n");
+        check("a , b", "a, b\n");
+        check("a . b", "a.b\n");
+        check("1 . 5", "1 . 5\n");
+        check("(f (a)) (b)", "(f(a))(b)\n");
+        check("- > = $ 1", "- > = $ 1\n");
+    }
+
+    #[test]
+    fn test_format_reindents_blocks() {
+        check("let a =\n (1)\nc", "let a =\n    (1)\nc\n");
+        check(
+            "a\n  b\n      c\n      # c\n  d\ne",
+            "a\n    b\n        c\n        # c\n    d\ne\n",
+        );
+        check("a\n b\n  c\nd", "a\n    b\n        c\nd\n");
+        check_with(
+            FormatOptions {
+                indent_width: 2,
+                ..FormatOptions::default()
+            },
+            "a\n    b",
+            "a\n  b\n",
+        );
+
+        // A dedent to a column no block started at keeps every indentation
+        check("a\n    b\n  c", "a\n    b\n  c\n");
+    }
+
+    #[test]
+    fn test_format_preserves_comments() {
+        check(
+            "## Docs\nlet a=1 # one\n\n\n\n# Comment\n",
+            "## Docs\nlet a = 1 # one\n\n# Comment\n",
+        );
+        check("1 +   # add\n2", "1 + # add\n2\n");
+    }
+
+    #[test]
+    fn test_format_wraps_long_binary_expressions() {
+        check_with(
+            FormatOptions {
+                max_width: 20,
+                ..FormatOptions::default()
+            },
+            "let total = alpha + beta + gamma + delta",
+            "let total = alpha +\nbeta + gamma + delta\n",
+        );
+        check_with(
+            FormatOptions {
+                max_width: 16,
+                ..FormatOptions::default()
+            },
+            "let x = first * second + third",
+            "let x = first *\nsecond + third\n",
+        );
+    }
+
+    #[test]
+    fn test_format_preserves_errors() {
+        check("let = $ 1", "let = $ 1\n");
+        check("let a =\n    1 +  2", "let a =\n    1 + 2\n");
+    }
+
+    #[test]
+    fn test_format_is_idempotent() {
+        let sources = [
+            "let x=1\nlet y=x*2  # double\n\n\n## docs\nlet z =(x+y)/ -2",
+            "a +\nb +\nc",
+            "let a =\n  (1 +\n  2)\nc",
+            "???  + ¬",
+            "a\n  b\n\n\n    c # c\nd",
+            "a\n    b\n  c  +  d",
+            "f (x) , y . z",
+        ];
+
+        for source in sources {
+            let options = FormatOptions {
+                max_width: 12,
+                ..FormatOptions::default()
+            };
+            let once = format_source(source, &options);
+            let twice = format_source(&once, &options);
+            assert_eq!(once, twice);
+        }
+    }
+}
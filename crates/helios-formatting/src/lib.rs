@@ -1,12 +1,28 @@
-use colored::*;
+pub mod render;
+
+pub use crate::render::{Ansi, Html, Markdown, Plain, Renderer};
+
+use serde::Serialize;
 use std::fmt::{self, Display};
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// A segment of a [`FormattedString`].
+///
+/// When serialized, each segment is an object with a `kind` field (in
+/// snake_case) and, unless it is a line break, a `value` field.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "value", rename_all = "snake_case")]
 pub enum FormattedStringSegment {
     LineBreak,
     Text(String),
     Code(String),
     CodeBlock(String),
+    Emphasis(String),
+    Strong(String),
+    Link {
+        text: String,
+        url: String,
+    },
+    /// A bulleted list, whose items may themselves contain lists.
     List(Vec<FormattedString>),
 }
 
@@ -23,38 +39,30 @@ impl FormattedStringSegment {
         Self::CodeBlock(code_block.into())
     }
 
+    pub fn emphasis(text: impl Into<String>) -> Self {
+        Self::Emphasis(text.into())
+    }
+
+    pub fn strong(text: impl Into<String>) -> Self {
+        Self::Strong(text.into())
+    }
+
+    pub fn link(text: impl Into<String>, url: impl Into<String>) -> Self {
+        Self::Link {
+            text: text.into(),
+            url: url.into(),
+        }
+    }
+
     pub fn list(list: impl Into<Vec<FormattedString>>) -> Self {
         Self::List(list.into())
     }
 }
 
+/// Renders the segment as plain text (see [`Plain`]).
 impl Display for FormattedStringSegment {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let colorize = colored::control::SHOULD_COLORIZE.should_colorize();
-        match self {
-            Self::LineBreak => write!(f, "\n\n"),
-            Self::Text(text) => write!(f, "{text}"),
-            Self::Code(code) => {
-                if colorize {
-                    write!(f, "{}", code.yellow())
-                } else {
-                    write!(f, "`{code}`")
-                }
-            }
-            Self::CodeBlock(block) => {
-                if colorize {
-                    write!(f, "    {}", block.yellow())
-                } else {
-                    write!(f, "    {block}")
-                }
-            }
-            Self::List(lines) => {
-                for line in lines {
-                    writeln!(f, "    {line}")?;
-                }
-                Ok(())
-            }
-        }
+        Plain.write_segment(f, self, 0)
     }
 }
 
@@ -70,7 +78,12 @@ impl From<&str> for FormattedStringSegment {
     }
 }
 
-#[derive(Clone, Default, Debug, Eq, PartialEq)]
+/// A string made of [`FormattedStringSegment`]s, which may be rendered for a
+/// terminal, as plain text, as Markdown or as HTML (see [`Renderer`]).
+///
+/// When serialized, a formatted string is a list of its segments.
+#[derive(Clone, Default, Debug, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
 pub struct FormattedString {
     segments: Vec<FormattedStringSegment>,
 }
@@ -107,6 +120,21 @@ impl FormattedString {
             .with(FormattedStringSegment::LineBreak)
     }
 
+    pub fn emphasis(self, text: impl Into<String>) -> Self {
+        self.with(FormattedStringSegment::emphasis(text))
+    }
+
+    pub fn strong(self, text: impl Into<String>) -> Self {
+        self.with(FormattedStringSegment::strong(text))
+    }
+
+    pub fn link(self, text: impl Into<String>, url: impl Into<String>) -> Self {
+        self.with(FormattedStringSegment::link(text, url))
+    }
+
+    /// Adds a list surrounded by line breaks. Use
+    /// [`FormattedStringSegment::list`] instead to nest a list in an item of
+    /// another list.
     pub fn list(self, list: impl Into<Vec<FormattedString>>) -> Self {
         self.with(FormattedStringSegment::LineBreak)
             .with(FormattedStringSegment::list(list))
@@ -114,17 +142,49 @@ impl FormattedString {
     }
 
     pub fn finish(self) -> String {
-        self.to_string().trim_end().to_string()
+        self.render_plain()
+    }
+
+    /// The segments making up this string.
+    pub fn segments(&self) -> &[FormattedStringSegment] {
+        &self.segments
+    }
+
+    /// Renders this string with the given renderer, without trailing
+    /// whitespace.
+    pub fn render(&self, renderer: &dyn Renderer) -> String {
+        let mut output = String::new();
+        let _ = renderer.write_string(&mut output, self, 0);
+        output.truncate(output.trim_end().len());
+        output
+    }
+
+    /// Renders this string for a terminal, with escape codes (see [`Ansi`]).
+    pub fn render_ansi(&self) -> String {
+        self.render(&Ansi)
+    }
+
+    /// Renders this string without any escape codes (see [`Plain`]).
+    pub fn render_plain(&self) -> String {
+        self.render(&Plain)
+    }
+
+    /// Renders this string as Markdown (see [`Markdown`]).
+    pub fn render_markdown(&self) -> String {
+        self.render(&Markdown)
+    }
+
+    /// Renders this string as HTML (see [`Html`]).
+    pub fn render_html(&self) -> String {
+        self.render(&Html)
     }
 }
 
+/// Renders the string as plain text (see [`Plain`]), so that the output never
+/// depends on the terminal it's written to.
 impl Display for FormattedString {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for segment in &self.segments {
-            write!(f, "{}", segment)?;
-        }
-
-        Ok(())
+        Plain.write_string(f, self, 0)
     }
 }
 
@@ -140,29 +200,134 @@ impl From<&str> for FormattedString {
     }
 }
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//
-//     #[test]
-//     fn test_formatter() {
-//         println!();
-//         let text = FormattedString::new()
-//             .text("The 2nd argument for the function ")
-//             .code("foo")
-//             .text(" expected a value of type:")
-//             .code_block("Vector Char")
-//             .text("But I received a value of type:")
-//             .code_block("String")
-//             .text("Here's a list of random types:")
-//             .list(
-//                 ["Foo.T", "Foo.Bar.T", "Foo.Bar.Baz.T", "Quux.T"]
-//                     .iter()
-//                     .map(|s| FormattedString::new().code(*s).text(" (info)"))
-//                     .collect::<Vec<_>>(),
-//             )
-//             .finish();
-//
-//         println!("{text}\n");
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> FormattedString {
+        FormattedString::new()
+            .text("I expected a value of type ")
+            .code("Int")
+            .text(" but found:")
+            .code_block("let a = `b`")
+            .text("Try one of these:")
+            .list(vec![
+                FormattedString::new().code("a * 2"),
+                FormattedString::new().text("a [literal]"),
+            ])
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(
+            sample().render_plain(),
+            "I expected a value of type `Int` but found:\n\n    let a = `b`\n\n\
+             Try one of these:\n\n    `a * 2`\n    a [literal]"
+        );
+    }
+
+    #[test]
+    fn test_text_with_colors() {
+        let string = FormattedString::new().text("Use ").code("a");
+        assert_eq!(string.render_ansi(), "Use \x1b[33ma\x1b[0m");
+        assert_eq!(string.render_plain(), "Use `a`");
+        assert_eq!(string.to_string(), "Use `a`");
+    }
+
+    #[test]
+    fn test_markdown() {
+        assert_eq!(
+            sample().render_markdown(),
+            "I expected a value of type `Int` but found:\n\n```\nlet a = `b`\n```\
+             \n\nTry one of these:\n\n- `a * 2`\n- a \\[literal\\]"
+        );
+    }
+
+    #[test]
+    fn test_markdown_code_with_backticks() {
+        let string = FormattedString::new().code("`a`").code("b``c");
+        assert_eq!(string.render_markdown(), "`` `a` `` ```b``c```");
+    }
+
+    #[test]
+    fn test_html_link_escapes_url() {
+        let string = FormattedString::new()
+            .link("docs", "https://example.com/?a=1&b=\"2\"");
+        assert_eq!(
+            string.render_html(),
+            "<p><a href=\"https://example.com/?a=1&amp;b=&quot;2&quot;\">\
+             docs</a></p>"
+        );
+    }
+
+    fn styled() -> FormattedString {
+        FormattedString::new()
+            .emphasis("Note")
+            .text(" that ")
+            .strong("a < b")
+            .text(", see ")
+            .link("the docs", "https://example.com/a b")
+    }
+
+    fn nested() -> FormattedString {
+        FormattedString::new().text("Either:").list(vec![
+            FormattedString::new().text("a").with(
+                FormattedStringSegment::list(vec![
+                    FormattedString::new().code("b"),
+                    FormattedString::new().text("c"),
+                ]),
+            ),
+            FormattedString::new().text("d"),
+        ])
+    }
+
+    #[test]
+    fn test_styles_and_links() {
+        assert_eq!(
+            styled().render_plain(),
+            "Note that a < b, see the docs (https://example.com/a b)"
+        );
+        assert_eq!(
+            styled().render_ansi(),
+            "\x1b[3mNote\x1b[0m that \x1b[1ma < b\x1b[0m, see \
+             \x1b]8;;https://example.com/a b\x1b\\the docs\x1b]8;;\x1b\\"
+        );
+        assert_eq!(
+            styled().render_markdown(),
+            "*Note* that **a \\< b**, see [the docs](https://example.com/a%20b)"
+        );
+        assert_eq!(
+            styled().render_html(),
+            "<p><em>Note</em> that <strong>a &lt; b</strong>, see \
+             <a href=\"https://example.com/a b\">the docs</a></p>"
+        );
+    }
+
+    #[test]
+    fn test_nested_lists() {
+        assert_eq!(
+            nested().render_plain(),
+            "Either:\n\n    a\n        `b`\n        c\n    d"
+        );
+        assert_eq!(
+            nested().render_markdown(),
+            "Either:\n\n- a\n  - `b`\n  - c\n- d"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        assert_eq!(
+            sample().render_html(),
+            "<p>I expected a value of type <code>Int</code> but found:</p>\n\
+             <pre><code>let a = `b`</code></pre>\n\
+             <p>Try one of these:</p>\n\
+             <ul><li><code>a * 2</code></li><li>a [literal]</li></ul>"
+        );
+        assert_eq!(
+            nested().render_html(),
+            "<p>Either:</p>\n<ul><li>a<ul><li><code>b</code></li>\
+             <li>c</li></ul></li><li>d</li></ul>"
+        );
+    }
+}
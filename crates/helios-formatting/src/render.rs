@@ -0,0 +1,330 @@
+//! Rendering formatted strings.
+//!
+//! A [`Renderer`] writes the segments of a [`FormattedString`] in a particular
+//! format (e.g. [`Ansi`] for terminals or [`Markdown`] for editors).
+
+use crate::{FormattedString, FormattedStringSegment};
+use std::fmt::{self, Write};
+
+/// A way of writing the segments of a [`FormattedString`].
+pub trait Renderer {
+    /// Writes a single segment at the given list depth.
+    fn write_segment(
+        &self,
+        f: &mut dyn Write,
+        segment: &FormattedStringSegment,
+        depth: usize,
+    ) -> fmt::Result;
+
+    /// Writes a whole string at the given list depth, which writes each of
+    /// its segments one after the other by default.
+    fn write_string(
+        &self,
+        f: &mut dyn Write,
+        string: &FormattedString,
+        depth: usize,
+    ) -> fmt::Result {
+        for segment in string.segments() {
+            self.write_segment(f, segment, depth)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders text for a terminal, where code is colored, emphasis is italic,
+/// strong text is bold and links are OSC 8 hyperlinks.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ansi;
+
+/// Renders text without any escape codes, where code is wrapped in backticks
+/// and links are followed by their URL.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Plain;
+
+/// Renders CommonMark, escaping the text that would otherwise be interpreted
+/// as formatting.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Markdown;
+
+/// Renders HTML, where the text between line breaks is wrapped in paragraphs.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Html;
+
+impl Renderer for Ansi {
+    fn write_segment(
+        &self,
+        f: &mut dyn Write,
+        segment: &FormattedStringSegment,
+        depth: usize,
+    ) -> fmt::Result {
+        write_terminal(self, f, segment, depth, true)
+    }
+}
+
+impl Renderer for Plain {
+    fn write_segment(
+        &self,
+        f: &mut dyn Write,
+        segment: &FormattedStringSegment,
+        depth: usize,
+    ) -> fmt::Result {
+        write_terminal(self, f, segment, depth, false)
+    }
+}
+
+/// Writes a segment for a terminal, with escape codes only if `colorize` is
+/// true.
+fn write_terminal(
+    renderer: &dyn Renderer,
+    f: &mut dyn Write,
+    segment: &FormattedStringSegment,
+    depth: usize,
+    colorize: bool,
+) -> fmt::Result {
+    use FormattedStringSegment::*;
+
+    let paint = |text: &str, code: &str| {
+        if colorize {
+            format!("\x1b[{code}m{text}\x1b[0m")
+        } else {
+            text.to_string()
+        }
+    };
+
+    match segment {
+        LineBreak => write!(f, "\n\n"),
+        Text(text) => write!(f, "{text}"),
+        Code(code) if colorize => write!(f, "{}", paint(code, "33")),
+        Code(code) => write!(f, "`{code}`"),
+        CodeBlock(block) => write!(f, "    {}", paint(block, "33")),
+        Emphasis(text) => write!(f, "{}", paint(text, "3")),
+        Strong(text) => write!(f, "{}", paint(text, "1")),
+        Link { text, url } if colorize => {
+            write!(f, "\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+        }
+        Link { text, url } if text == url => write!(f, "{url}"),
+        Link { text, url } => write!(f, "{text} ({url})"),
+        List(items) => {
+            let indent = "    ".repeat(depth + 1);
+            write_list_items(f, items, depth, |f, item| {
+                write!(f, "{indent}")?;
+                renderer.write_string(f, item, depth + 1)
+            })
+        }
+    }
+}
+
+impl Renderer for Markdown {
+    fn write_segment(
+        &self,
+        f: &mut dyn Write,
+        segment: &FormattedStringSegment,
+        depth: usize,
+    ) -> fmt::Result {
+        use FormattedStringSegment::*;
+
+        match segment {
+            LineBreak => write!(f, "\n\n"),
+            Text(text) => write!(f, "{}", escape_markdown(text)),
+            Code(code) => {
+                // The code span must be delimited by more backticks than the
+                // longest run of backticks inside it
+                let fence = "`".repeat(longest_backtick_run(code) + 1);
+                let padding = if code.starts_with('`') || code.ends_with('`') {
+                    " "
+                } else {
+                    ""
+                };
+                write!(f, "{fence}{padding}{code}{padding}{fence}")
+            }
+            CodeBlock(block) => {
+                let fence = "`".repeat(longest_backtick_run(block).max(2) + 1);
+                write!(f, "{fence}\n{block}\n{fence}")
+            }
+            Emphasis(text) => write!(f, "*{}*", escape_markdown(text)),
+            Strong(text) => write!(f, "**{}**", escape_markdown(text)),
+            Link { text, url } => write!(
+                f,
+                "[{}]({})",
+                escape_markdown(text),
+                url.replace(' ', "%20")
+                    .replace('(', "%28")
+                    .replace(')', "%29")
+            ),
+            List(items) => {
+                let indent = "  ".repeat(depth);
+                write_list_items(f, items, depth, |f, item| {
+                    write!(f, "{indent}- ")?;
+                    self.write_string(f, item, depth + 1)
+                })
+            }
+        }
+    }
+
+    fn write_string(
+        &self,
+        f: &mut dyn Write,
+        string: &FormattedString,
+        depth: usize,
+    ) -> fmt::Result {
+        use FormattedStringSegment::Code;
+
+        let mut previous: Option<&FormattedStringSegment> = None;
+        for segment in string.segments() {
+            // The fences of adjacent code spans would merge into a single run
+            // of backticks
+            if let (Some(Code(_)), Code(_)) = (previous, segment) {
+                write!(f, " ")?;
+            }
+            self.write_segment(f, segment, depth)?;
+            previous = Some(segment);
+        }
+        Ok(())
+    }
+}
+
+impl Renderer for Html {
+    fn write_segment(
+        &self,
+        f: &mut dyn Write,
+        segment: &FormattedStringSegment,
+        depth: usize,
+    ) -> fmt::Result {
+        use FormattedStringSegment::*;
+
+        match segment {
+            LineBreak => Ok(()),
+            Text(text) => write!(f, "{}", escape_html(text)),
+            Code(code) => write!(f, "<code>{}</code>", escape_html(code)),
+            CodeBlock(block) => {
+                write!(f, "<pre><code>{}</code></pre>", escape_html(block))
+            }
+            Emphasis(text) => write!(f, "<em>{}</em>", escape_html(text)),
+            Strong(text) => {
+                write!(f, "<strong>{}</strong>", escape_html(text))
+            }
+            Link { text, url } => write!(
+                f,
+                "<a href=\"{}\">{}</a>",
+                escape_html(url),
+                escape_html(text)
+            ),
+            List(items) => {
+                write!(f, "<ul>")?;
+                for item in items {
+                    write!(f, "<li>")?;
+                    self.write_string(f, item, depth + 1)?;
+                    write!(f, "</li>")?;
+                }
+                write!(f, "</ul>")
+            }
+        }
+    }
+
+    fn write_string(
+        &self,
+        f: &mut dyn Write,
+        string: &FormattedString,
+        depth: usize,
+    ) -> fmt::Result {
+        // The items of lists are never split into paragraphs
+        if depth > 0 {
+            for segment in string.segments() {
+                self.write_segment(f, segment, depth)?;
+            }
+            return Ok(());
+        }
+
+        // Otherwise, the inline segments between line breaks are paragraphs,
+        // while code blocks and lists stand on their own
+        let mut paragraph = String::new();
+        let mut blocks = Vec::new();
+        for segment in string.segments() {
+            match segment {
+                FormattedStringSegment::LineBreak => {
+                    blocks.push(std::mem::take(&mut paragraph));
+                }
+                FormattedStringSegment::CodeBlock(_)
+                | FormattedStringSegment::List(_) => {
+                    blocks.push(std::mem::take(&mut paragraph));
+                    let mut block = String::new();
+                    self.write_segment(&mut block, segment, depth)?;
+                    blocks.push(block);
+                }
+                _ => self.write_segment(&mut paragraph, segment, depth)?,
+            }
+        }
+        blocks.push(paragraph);
+
+        let blocks = blocks
+            .into_iter()
+            .filter(|block| !block.trim().is_empty())
+            .map(|block| {
+                if block.starts_with("<pre>") || block.starts_with("<ul>") {
+                    block
+                } else {
+                    format!("<p>{}</p>", block.trim())
+                }
+            })
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", blocks.join("\n"))
+    }
+}
+
+/// Writes the items of a list on their own lines. A list nested in an item of
+/// another list (i.e. at a depth larger than `0`) starts on a new line and
+/// doesn't end with one, as the enclosing list already ends the item.
+fn write_list_items(
+    f: &mut dyn Write,
+    items: &[FormattedString],
+    depth: usize,
+    mut write_item: impl FnMut(&mut dyn Write, &FormattedString) -> fmt::Result,
+) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if depth > 0 || i > 0 {
+            writeln!(f)?;
+        }
+        write_item(f, item)?;
+    }
+
+    if depth == 0 && !items.is_empty() {
+        writeln!(f)?;
+    }
+    Ok(())
+}
+
+/// The length of the longest run of consecutive backticks in the given string.
+fn longest_backtick_run(s: &str) -> usize {
+    s.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// Escapes the characters of the given text that Markdown would otherwise
+/// interpret as formatting.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escapes the characters of the given text that HTML would otherwise
+/// interpret as markup.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,57 @@
+use colored::*;
+
+use crate::{segments, HighlightTag, HighlightedRange};
+
+/// Renders the given source text with ANSI terminal colors.
+///
+/// Just like everything else printed with the `colored` crate, no escape
+/// sequences are emitted if colors have been disabled (e.g. when the `NO_COLOR`
+/// environment variable is set).
+pub fn render_ansi(source: &str, highlights: &[HighlightedRange]) -> String {
+    segments(source, highlights)
+        .map(|(text, tag)| match tag {
+            Some(tag) => colorize(text, tag).to_string(),
+            None => text.to_string(),
+        })
+        .collect()
+}
+
+fn colorize(text: &str, tag: HighlightTag) -> ColoredString {
+    match tag {
+        HighlightTag::Keyword => text.magenta().bold(),
+        HighlightTag::Operator => text.normal(),
+        HighlightTag::Punctuation => text.normal(),
+        HighlightTag::Number => text.yellow(),
+        HighlightTag::String => text.green(),
+        HighlightTag::Character => text.green(),
+        HighlightTag::Comment => text.dimmed(),
+        HighlightTag::DocComment => text.green().dimmed(),
+        HighlightTag::Binding => text.blue().bold(),
+        HighlightTag::Variable => text.blue(),
+        HighlightTag::Placeholder => text.cyan().italic(),
+        HighlightTag::Invalid => text.red().underline(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_ansi() {
+        let source = "let x = 1 # one";
+        let parse = helios_parser::parse(0u8, source);
+        let highlights = crate::highlight(&parse.syntax());
+
+        let expected = format!(
+            "{} {} {} {} {}",
+            "let".magenta().bold(),
+            "x".blue().bold(),
+            "=".normal(),
+            "1".yellow(),
+            "# one".dimmed(),
+        );
+
+        assert_eq!(render_ansi(source, &highlights), expected);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{segments, HighlightedRange};
+
+const STYLESHEET: &str = "\
+body { background: #fdfdfd; color: #24292e; }
+.keyword { color: #a626a4; font-weight: bold; }
+.number { color: #986801; }
+.string, .character { color: #50a14f; }
+.comment { color: #a0a1a7; }
+.doc-comment { color: #50a14f; font-style: italic; }
+.binding { color: #4078f2; font-weight: bold; }
+.variable { color: #4078f2; }
+.placeholder { color: #0184bc; font-style: italic; }
+.invalid { color: #e45649; text-decoration: underline wavy; }";
+
+/// Renders the given source text as a standalone HTML document.
+///
+/// Each highlighted range is wrapped in a `<span>` with the name of its tag
+/// (see [`HighlightTag::name`]) as its class, and a default stylesheet is
+/// embedded in the document.
+///
+/// [`HighlightTag::name`]: crate::HighlightTag::name
+pub fn render_html(source: &str, highlights: &[HighlightedRange]) -> String {
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <style>\n{STYLESHEET}\n</style>\n\
+         </head>\n\
+         <body>\n\
+         {}\n\
+         </body>\n\
+         </html>\n",
+        render_html_snippet(source, highlights)
+    )
+}
+
+/// Renders the given source text as a `<pre>` element, suitable to be embedded
+/// in another HTML document.
+pub fn render_html_snippet(
+    source: &str,
+    highlights: &[HighlightedRange],
+) -> String {
+    let code = segments(source, highlights)
+        .map(|(text, tag)| match tag {
+            Some(tag) => {
+                format!(
+                    "<span class=\"{}\">{}</span>",
+                    tag.name(),
+                    escape(text)
+                )
+            }
+            None => escape(text),
+        })
+        .collect::<String>();
+
+    format!("<pre class=\"helios\"><code>{code}</code></pre>")
+}
+
+/// Escapes the characters in the given text that have a special meaning in
+/// HTML.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_html_snippet() {
+        let source = "let x = a < 2";
+        let parse = helios_parser::parse(0u8, source);
+        let highlights = crate::highlight(&parse.syntax());
+
+        assert_eq!(
+            render_html_snippet(source, &highlights),
+            "<pre class=\"helios\"><code>\
+             <span class=\"keyword\">let</span> \
+             <span class=\"binding\">x</span> \
+             <span class=\"operator\">=</span> \
+             <span class=\"variable\">a</span> \
+             <span class=\"operator\">&lt;</span> \
+             <span class=\"number\">2</span>\
+             </code></pre>"
+        );
+    }
+
+    #[test]
+    fn test_render_html_document() {
+        let html = render_html("x", &[]);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<pre class=\"helios\"><code>x</code></pre>"));
+    }
+}
@@ -0,0 +1,252 @@
+//! Semantic syntax highlighting for Helios source files.
+//!
+//! The [`highlight`] function classifies each token of a syntax tree by the
+//! role it plays in the program, and the result can be rendered for terminals,
+//! HTML or as LSP [`semantic_tokens`].
+
+mod ansi;
+mod html;
+mod semantic;
+
+use helios_syntax::{SyntaxKind, SyntaxNode};
+use rowan::NodeOrToken;
+use std::ops::Range;
+
+pub use crate::ansi::render_ansi;
+pub use crate::html::{render_html, render_html_snippet};
+pub use crate::semantic::{
+    semantic_tokens, SemanticToken, SEMANTIC_TOKEN_MODIFIERS,
+    SEMANTIC_TOKEN_TYPES,
+};
+
+/// The role a highlighted token plays in a Helios program.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HighlightTag {
+    Keyword,
+    Operator,
+    Punctuation,
+    Number,
+    String,
+    Character,
+    Comment,
+    DocComment,
+    /// The name introduced by a binding (e.g. `x` in `let x = 1`).
+    Binding,
+    /// A reference to a binding.
+    Variable,
+    Placeholder,
+    Invalid,
+}
+
+impl HighlightTag {
+    /// The TextMate scope that best describes this tag.
+    ///
+    /// These scopes follow the naming conventions used by most TextMate
+    /// grammars and themes, so that editors can style Helios code consistently
+    /// with other languages.
+    pub fn textmate_scope(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword.other.helios",
+            Self::Operator => "keyword.operator.helios",
+            Self::Punctuation => "punctuation.section.helios",
+            Self::Number => "constant.numeric.helios",
+            Self::String => "string.quoted.double.helios",
+            Self::Character => "constant.character.helios",
+            Self::Comment => "comment.line.number-sign.helios",
+            Self::DocComment => "comment.block.documentation.helios",
+            Self::Binding => "entity.name.variable.helios",
+            Self::Variable => "variable.other.helios",
+            Self::Placeholder => "markup.other.placeholder.helios",
+            Self::Invalid => "invalid.illegal.helios",
+        }
+    }
+
+    /// The name of this tag in kebab-case (used for HTML class names).
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Operator => "operator",
+            Self::Punctuation => "punctuation",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Character => "character",
+            Self::Comment => "comment",
+            Self::DocComment => "doc-comment",
+            Self::Binding => "binding",
+            Self::Variable => "variable",
+            Self::Placeholder => "placeholder",
+            Self::Invalid => "invalid",
+        }
+    }
+}
+
+/// A range of a source text classified with a [`HighlightTag`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HighlightedRange {
+    pub range: Range<usize>,
+    pub tag: HighlightTag,
+}
+
+impl HighlightedRange {
+    pub fn new(range: Range<usize>, tag: HighlightTag) -> Self {
+        Self { range, tag }
+    }
+}
+
+/// Classifies every significant token of the given syntax tree.
+///
+/// The returned ranges are sorted and never overlap. Trivia other than
+/// comments (i.e. whitespace and line feeds) and layout tokens are never
+/// highlighted.
+pub fn highlight(root: &SyntaxNode) -> Vec<HighlightedRange> {
+    root.descendants_with_tokens()
+        .filter_map(NodeOrToken::into_token)
+        .filter_map(|token| {
+            let parent_kind = token.parent().map(|parent| parent.kind());
+            let tag = classify(token.kind(), parent_kind)?;
+            let range = token.text_range();
+            Some(HighlightedRange::new(
+                range.start().into()..range.end().into(),
+                tag,
+            ))
+        })
+        .collect()
+}
+
+/// Splits the given source text into consecutive slices, each paired with the
+/// [`HighlightTag`] of the range it belongs to (if any).
+///
+/// The given ranges must be sorted and must not overlap (as is the case with
+/// the ranges returned by [`highlight`]).
+pub(crate) fn segments<'a>(
+    source: &'a str,
+    highlights: &'a [HighlightedRange],
+) -> impl 'a + Iterator<Item = (&'a str, Option<HighlightTag>)> {
+    let mut segments = Vec::with_capacity(highlights.len() * 2 + 1);
+    let mut last_end = 0;
+
+    for HighlightedRange { range, tag } in highlights {
+        if range.start > last_end {
+            segments.push((&source[last_end..range.start], None));
+        }
+
+        segments.push((&source[range.clone()], Some(*tag)));
+        last_end = range.end;
+    }
+
+    if last_end < source.len() {
+        segments.push((&source[last_end..], None));
+    }
+
+    segments.into_iter()
+}
+
+/// Determines the [`HighlightTag`] of a token of the given kind, taking into
+/// account the kind of the node it is in.
+fn classify(
+    kind: SyntaxKind,
+    parent_kind: Option<SyntaxKind>,
+) -> Option<HighlightTag> {
+    let tag = match kind {
+        kind if kind.is_keyword() => HighlightTag::Keyword,
+        SyntaxKind::Sym_LBrace
+        | SyntaxKind::Sym_RBrace
+        | SyntaxKind::Sym_LBracket
+        | SyntaxKind::Sym_RBracket
+        | SyntaxKind::Sym_LParen
+        | SyntaxKind::Sym_RParen
+        | SyntaxKind::Sym_Colon
+        | SyntaxKind::Sym_Comma
+        | SyntaxKind::Sym_Dot => HighlightTag::Punctuation,
+        kind if kind.is_symbol() => HighlightTag::Operator,
+        SyntaxKind::Lit_Integer | SyntaxKind::Lit_Float => HighlightTag::Number,
+        SyntaxKind::Lit_String => HighlightTag::String,
+        SyntaxKind::Lit_Character => HighlightTag::Character,
+        SyntaxKind::Comment => HighlightTag::Comment,
+        SyntaxKind::DocComment => HighlightTag::DocComment,
+        SyntaxKind::Identifier | SyntaxKind::ReservedIdentifier => {
+            match parent_kind {
+                Some(SyntaxKind::Dec_GlobalBinding) => HighlightTag::Binding,
+                _ => HighlightTag::Variable,
+            }
+        }
+        SyntaxKind::Placeholder => HighlightTag::Placeholder,
+        SyntaxKind::UnknownChar | SyntaxKind::Error => HighlightTag::Invalid,
+        _ => return None,
+    };
+
+    Some(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(source: &str, expected: Vec<(Range<usize>, HighlightTag)>) {
+        let parse = helios_parser::parse(0u8, source);
+        let expected = expected
+            .into_iter()
+            .map(|(range, tag)| HighlightedRange::new(range, tag))
+            .collect::<Vec<_>>();
+        assert_eq!(highlight(&parse.syntax()), expected);
+    }
+
+    #[test]
+    fn test_highlight_global_binding() {
+        use HighlightTag::*;
+        check(
+            "let foo = bar + 1.5 # hi",
+            vec![
+                (0..3, Keyword),
+                (4..7, Binding),
+                (8..9, Operator),
+                (10..13, Variable),
+                (14..15, Operator),
+                (16..19, Number),
+                (20..24, Comment),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_highlight_expression() {
+        use HighlightTag::*;
+        check(
+            "## docs\n-(a * ???) $ ¬",
+            vec![
+                (0..7, DocComment),
+                (8..9, Operator),
+                (9..10, Punctuation),
+                (10..11, Variable),
+                (12..13, Operator),
+                (14..17, Placeholder),
+                (17..18, Punctuation),
+                (19..20, Operator),
+                (21..23, Invalid),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_textmate_scopes_are_namespaced() {
+        use HighlightTag::*;
+        let tags = [
+            Keyword,
+            Operator,
+            Punctuation,
+            Number,
+            String,
+            Character,
+            Comment,
+            DocComment,
+            Binding,
+            Variable,
+            Placeholder,
+            Invalid,
+        ];
+
+        for tag in tags {
+            assert!(tag.textmate_scope().ends_with(".helios"));
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use crate::{HighlightTag, HighlightedRange};
+
+/// The token types used by [`semantic_tokens`], in the order of their indexes.
+///
+/// This is the `tokenTypes` legend that a language server should advertise in
+/// its `SemanticTokensLegend`.
+pub const SEMANTIC_TOKEN_TYPES: &[&str] = &[
+    "keyword", "operator", "number", "string", "comment", "variable",
+];
+
+/// The token modifiers used by [`semantic_tokens`], in the order of their bits.
+///
+/// This is the `tokenModifiers` legend that a language server should advertise
+/// in its `SemanticTokensLegend`.
+pub const SEMANTIC_TOKEN_MODIFIERS: &[&str] = &["declaration", "documentation"];
+
+const DECLARATION: u32 = 1 << 0;
+const DOCUMENTATION: u32 = 1 << 1;
+
+/// A semantic token as described by the Language Server Protocol.
+///
+/// Positions are encoded relative to the previous token, and columns and
+/// lengths are counted in UTF-16 code units (the default position encoding of
+/// the protocol).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SemanticToken {
+    pub delta_line: u32,
+    pub delta_start: u32,
+    pub length: u32,
+    pub token_type: u32,
+    pub token_modifiers_bitset: u32,
+}
+
+impl SemanticToken {
+    /// Flattens this token into the five integers the protocol expects.
+    pub fn to_array(self) -> [u32; 5] {
+        [
+            self.delta_line,
+            self.delta_start,
+            self.length,
+            self.token_type,
+            self.token_modifiers_bitset,
+        ]
+    }
+}
+
+/// Maps a [`HighlightTag`] to its index in [`SEMANTIC_TOKEN_TYPES`] and its
+/// modifiers, if it has a corresponding semantic token type.
+fn token_type(tag: HighlightTag) -> Option<(u32, u32)> {
+    let token_type = match tag {
+        HighlightTag::Keyword => (0, 0),
+        HighlightTag::Operator => (1, 0),
+        HighlightTag::Number => (2, 0),
+        HighlightTag::String | HighlightTag::Character => (3, 0),
+        HighlightTag::Comment => (4, 0),
+        HighlightTag::DocComment => (4, DOCUMENTATION),
+        HighlightTag::Binding => (5, DECLARATION),
+        HighlightTag::Variable => (5, 0),
+        HighlightTag::Punctuation
+        | HighlightTag::Placeholder
+        | HighlightTag::Invalid => return None,
+    };
+
+    Some(token_type)
+}
+
+/// Converts the given highlighted ranges of a source text into a list of
+/// semantic tokens.
+///
+/// Tags without a corresponding semantic token type (such as punctuation) are
+/// skipped, and ranges spanning multiple lines are split into one token per
+/// line.
+pub fn semantic_tokens(
+    source: &str,
+    highlights: &[HighlightedRange],
+) -> Vec<SemanticToken> {
+    let mut tokens = Vec::with_capacity(highlights.len());
+
+    // The line index and UTF-16 column of the current position in the source
+    // text, and of the start of the last emitted token.
+    let (mut line, mut column) = (0, 0);
+    let (mut last_line, mut last_column) = (0, 0);
+    let mut offset = 0;
+
+    let mut advance_to = |target: usize, line: &mut u32, column: &mut u32| {
+        for c in source[offset..target].chars() {
+            if c == '\n' {
+                *line += 1;
+                *column = 0;
+            } else {
+                *column += c.len_utf16() as u32;
+            }
+        }
+        offset = target;
+    };
+
+    for HighlightedRange { range, tag } in highlights {
+        let (token_type, modifiers) = match token_type(*tag) {
+            Some(token_type) => token_type,
+            None => continue,
+        };
+
+        advance_to(range.start, &mut line, &mut column);
+
+        let mut start = range.start;
+        for (i, part) in source[range.clone()].split('\n').enumerate() {
+            if i > 0 {
+                // Skip the line feed before this part.
+                advance_to(start + 1, &mut line, &mut column);
+                start += 1;
+            }
+
+            let length = part.chars().map(char::len_utf16).sum::<usize>();
+            if length > 0 {
+                let delta_line = line - last_line;
+                let delta_start = if delta_line == 0 {
+                    column - last_column
+                } else {
+                    column
+                };
+
+                tokens.push(SemanticToken {
+                    delta_line,
+                    delta_start,
+                    length: length as u32,
+                    token_type,
+                    token_modifiers_bitset: modifiers,
+                });
+
+                last_line = line;
+                last_column = column;
+            }
+
+            advance_to(start + part.len(), &mut line, &mut column);
+            start += part.len();
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(source: &str, expected: Vec<[u32; 5]>) {
+        let parse = helios_parser::parse(0u8, source);
+        let highlights = crate::highlight(&parse.syntax());
+        let tokens = semantic_tokens(source, &highlights)
+            .into_iter()
+            .map(SemanticToken::to_array)
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_semantic_tokens() {
+        check(
+            "## doc\nlet x = (1)\nlet y = x",
+            vec![
+                [0, 0, 6, 4, DOCUMENTATION],
+                [1, 0, 3, 0, 0],
+                [0, 4, 1, 5, DECLARATION],
+                [0, 2, 1, 1, 0],
+                [0, 3, 1, 2, 0],
+                [1, 0, 3, 0, 0],
+                [0, 4, 1, 5, DECLARATION],
+                [0, 2, 1, 1, 0],
+                [0, 2, 1, 5, 0],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_semantic_tokens_utf16_columns() {
+        check(
+            "let 😀 = 1 # 人的",
+            vec![
+                [0, 0, 3, 0, 0],
+                [0, 7, 1, 1, 0],
+                [0, 2, 1, 2, 0],
+                [0, 2, 4, 4, 0],
+            ],
+        );
+    }
+
+    #[test]
+    fn test_semantic_tokens_split_across_lines() {
+        let source = "a\nb";
+        let highlights = [HighlightedRange::new(0..3, HighlightTag::Comment)];
+        let tokens = semantic_tokens(source, &highlights)
+            .into_iter()
+            .map(SemanticToken::to_array)
+            .collect::<Vec<_>>();
+
+        assert_eq!(tokens, vec![[0, 0, 1, 4, 0], [1, 0, 1, 4, 0]]);
+    }
+}
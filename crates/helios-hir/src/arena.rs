@@ -0,0 +1,206 @@
+//! Storing the nodes of the HIR.
+//!
+//! An [`Arena`] is a vector that hands out typed indices ([`Idx`]) instead of
+//! references.
+
+use std::fmt::{self, Debug};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+/// The index of a value allocated in an [`Arena`].
+pub struct Idx<T> {
+    raw: u32,
+    _ty: PhantomData<fn() -> T>,
+}
+
+impl<T> Idx<T> {
+    pub fn from_raw(raw: u32) -> Self {
+        Self {
+            raw,
+            _ty: PhantomData,
+        }
+    }
+
+    pub fn into_raw(self) -> u32 {
+        self.raw
+    }
+}
+
+// The traits are implemented by hand, as deriving them would require `T` to
+// implement them too
+
+impl<T> Clone for Idx<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Idx<T> {}
+
+impl<T> PartialEq for Idx<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.raw == other.raw
+    }
+}
+
+impl<T> Eq for Idx<T> {}
+
+impl<T> PartialOrd for Idx<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Idx<T> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.raw.cmp(&other.raw)
+    }
+}
+
+impl<T> Hash for Idx<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.raw.hash(state);
+    }
+}
+
+impl<T> Debug for Idx<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = std::any::type_name::<T>();
+        let name = name.rsplit("::").next().unwrap_or(name);
+        write!(f, "{name}#{}", self.raw)
+    }
+}
+
+/// A vector of values of the same type, indexed by [`Idx`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct Arena<T> {
+    data: Vec<T>,
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    /// Adds a value to the arena, returning its index.
+    pub fn alloc(&mut self, value: T) -> Idx<T> {
+        let idx = Idx::from_raw(self.data.len() as u32);
+        self.data.push(value);
+        idx
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Every value of the arena along with its index, in the order they were
+    /// allocated.
+    pub fn iter(&self) -> impl Iterator<Item = (Idx<T>, &T)> {
+        self.data
+            .iter()
+            .enumerate()
+            .map(|(raw, value)| (Idx::from_raw(raw as u32), value))
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Debug> Debug for Arena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Index<Idx<T>> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, idx: Idx<T>) -> &T {
+        &self.data[idx.raw as usize]
+    }
+}
+
+impl<T> IndexMut<Idx<T>> for Arena<T> {
+    fn index_mut(&mut self, idx: Idx<T>) -> &mut T {
+        &mut self.data[idx.raw as usize]
+    }
+}
+
+/// Values associated with (some of) the indices of an [`Arena`], stored next
+/// to each other instead of in the arena itself (e.g. the source ranges of
+/// its values).
+#[derive(Clone, PartialEq, Eq)]
+pub struct ArenaMap<T, V> {
+    values: Vec<Option<V>>,
+    _ty: PhantomData<fn() -> T>,
+}
+
+impl<T, V> ArenaMap<T, V> {
+    pub fn new() -> Self {
+        Self {
+            values: Vec::new(),
+            _ty: PhantomData,
+        }
+    }
+
+    pub fn insert(&mut self, idx: Idx<T>, value: V) {
+        let index = idx.raw as usize;
+        if self.values.len() <= index {
+            self.values.resize_with(index + 1, || None);
+        }
+        self.values[index] = Some(value);
+    }
+
+    pub fn get(&self, idx: Idx<T>) -> Option<&V> {
+        self.values.get(idx.raw as usize)?.as_ref()
+    }
+}
+
+impl<T, V> Default for ArenaMap<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, V: Debug> Debug for ArenaMap<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = self.values.iter().enumerate().filter_map(|(raw, v)| {
+            Some((Idx::<T>::from_raw(raw as u32), v.as_ref()?))
+        });
+        f.debug_map().entries(entries).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arena() {
+        let mut arena = Arena::new();
+        let a = arena.alloc("a");
+        let b = arena.alloc("b");
+
+        assert_eq!(arena[a], "a");
+        assert_eq!(arena[b], "b");
+        assert_eq!(arena.len(), 2);
+        assert_eq!(
+            arena.iter().collect::<Vec<_>>(),
+            vec![(a, &"a"), (b, &"b")]
+        );
+        assert_eq!(format!("{a:?}"), "&str#0");
+
+        let mut map = ArenaMap::new();
+        map.insert(b, 1..2);
+        assert_eq!(map.get(a), None);
+        assert_eq!(map.get(b), Some(&(1..2)));
+    }
+}
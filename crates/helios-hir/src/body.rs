@@ -0,0 +1,97 @@
+use crate::arena::{Arena, ArenaMap};
+use crate::{Expr, ExprId, Pat, PatId};
+use helios_diagnostics::Location;
+use std::ops::Range;
+
+/// The lowered contents of a declaration (e.g. the pattern and the value of
+/// a `let` binding).
+///
+/// A body doesn't know where its nodes come from, so that editing the file
+/// around a declaration (or adding whitespace inside it) doesn't change its
+/// body: the ranges of its nodes are kept in a separate [`BodySourceMap`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Body {
+    pub exprs: Arena<Expr>,
+    pub pats: Arena<Pat>,
+    /// The pattern the value is bound to.
+    pub binding: PatId,
+    /// The root expression of the body.
+    pub value: ExprId,
+}
+
+impl Body {
+    /// Every sub-expression of the given expression, from left to right.
+    pub fn children(&self, expr: ExprId) -> Vec<ExprId> {
+        match &self.exprs[expr] {
+            Expr::Missing | Expr::Literal(_) | Expr::Variable(_) => Vec::new(),
+            Expr::Unary { operand, .. } => vec![*operand],
+            Expr::Binary { lhs, rhs, .. } => vec![*lhs, *rhs],
+        }
+    }
+}
+
+/// Where the nodes of a [`Body`] come from in the source text.
+///
+/// The range of a node never includes the trivia around it, so diagnostics
+/// can point exactly at the expression or pattern they're about. A node that
+/// is missing because of a syntax error has an empty range where it was
+/// expected.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BodySourceMap {
+    pub(crate) exprs: ArenaMap<Expr, Range<usize>>,
+    pub(crate) pats: ArenaMap<Pat, Range<usize>>,
+}
+
+impl BodySourceMap {
+    /// The range of an expression of the body.
+    pub fn expr_range(&self, expr: ExprId) -> Range<usize> {
+        self.exprs
+            .get(expr)
+            .cloned()
+            .expect("every expression of a body has a range")
+    }
+
+    /// The range of a pattern of the body.
+    pub fn pat_range(&self, pat: PatId) -> Range<usize> {
+        self.pats
+            .get(pat)
+            .cloned()
+            .expect("every pattern of a body has a range")
+    }
+
+    /// The location of an expression of the body, to point at it in a
+    /// diagnostic.
+    pub fn expr_location<FileId>(
+        &self,
+        file_id: FileId,
+        expr: ExprId,
+    ) -> Location<FileId> {
+        Location::new(file_id, self.expr_range(expr))
+    }
+
+    /// The location of a pattern of the body, to point at it in a diagnostic.
+    pub fn pat_location<FileId>(
+        &self,
+        file_id: FileId,
+        pat: PatId,
+    ) -> Location<FileId> {
+        Location::new(file_id, self.pat_range(pat))
+    }
+
+    /// The innermost expression of the given body that contains the offset,
+    /// if any.
+    pub fn expr_at_offset(
+        &self,
+        body: &Body,
+        byte_offset: usize,
+    ) -> Option<ExprId> {
+        body.exprs
+            .iter()
+            .map(|(expr, _)| (expr, self.expr_range(expr)))
+            .filter(|(_, range)| {
+                range.start <= byte_offset && byte_offset <= range.end
+            })
+            .min_by_key(|(_, range)| range.len())
+            .map(|(expr, _)| expr)
+    }
+}
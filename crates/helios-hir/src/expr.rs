@@ -0,0 +1,73 @@
+use crate::arena::Idx;
+use crate::Name;
+
+pub type ExprId = Idx<Expr>;
+
+/// An expression, whose sub-expressions are stored in the same [`Body`].
+///
+/// Parenthesized and indented expressions are lowered to the expression they
+/// contain, as they only group it.
+///
+/// [`Body`]: crate::Body
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    /// An expression that couldn't be lowered because of a syntax error
+    /// (which the parser already reported).
+    Missing,
+    Literal(Literal),
+    /// A reference to a binding (e.g. `a` in `a + 1`).
+    Variable(Name),
+    Unary {
+        op: UnaryOp,
+        operand: ExprId,
+    },
+    Binary {
+        op: BinaryOp,
+        lhs: ExprId,
+        rhs: ExprId,
+    },
+}
+
+/// A literal, as written in the source (its value is only checked once its
+/// type is known).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Literal {
+    Integer(String),
+    Float(String),
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum UnaryOp {
+    /// `-a`
+    Neg,
+    /// `!a`
+    Not,
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum BinaryOp {
+    /// `a + b`
+    Add,
+    /// `a - b`
+    Sub,
+    /// `a * b`
+    Mul,
+    /// `a / b`
+    Div,
+    /// `a = b`
+    Eq,
+    /// `a != b`
+    NotEq,
+    /// `a < b`
+    Lt,
+    /// `a <= b`
+    LtEq,
+    /// `a > b`
+    Gt,
+    /// `a >= b`
+    GtEq,
+    /// `a <- b`
+    Assign,
+    /// `a; b`
+    Seq,
+}
@@ -0,0 +1,20 @@
+//! The high-level intermediate representation (HIR) of Helios programs.
+//!
+//! A file is lowered in two steps: [`lower_module`] finds its declarations,
+//! then [`lower_body`] lowers each one. Their ranges are kept in separate
+//! source maps, so a body stays the same when its declaration only moves.
+
+pub mod arena;
+pub mod body;
+pub mod expr;
+pub mod lower;
+pub mod module;
+pub mod name;
+pub mod pat;
+
+pub use crate::body::*;
+pub use crate::expr::*;
+pub use crate::lower::*;
+pub use crate::module::*;
+pub use crate::name::*;
+pub use crate::pat::*;
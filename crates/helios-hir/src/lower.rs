@@ -0,0 +1,366 @@
+//! Lowering the syntax tree of a file to its HIR.
+//!
+//! Nodes that are missing because of syntax errors are lowered to `Missing`,
+//! as the parser already reported them.
+
+use crate::arena::Idx;
+use crate::body::{Body, BodySourceMap};
+use crate::module::{
+    Decl, DeclId, DeclKind, DeclSource, Module, ModuleSourceMap,
+};
+use crate::{BinaryOp, Expr, ExprId, Literal, Name, Pat, PatId, UnaryOp};
+use helios_syntax::{SyntaxKind, SyntaxNode, SyntaxNodePtr, SyntaxToken};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Lowers the declarations of a file, given the root of its syntax tree.
+///
+/// Declarations without a name are left out, as they can't be referred to.
+pub fn lower_module(root: &SyntaxNode) -> (Module, ModuleSourceMap) {
+    let mut module = Module::default();
+    let mut source_map = ModuleSourceMap::default();
+    // The number of declarations found so far with each kind and name, keyed
+    // by the id of the first one
+    let mut counts = HashMap::new();
+
+    for node in root.children() {
+        if node.kind() != SyntaxKind::Dec_GlobalBinding {
+            continue;
+        }
+        let name_token = match token(&node, SyntaxKind::Identifier) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        let kind = DeclKind::Global;
+        let name = Name::new(name_token.text());
+        let count = counts.entry(DeclId::new(kind, &name, 0)).or_insert(0);
+        let decl = DeclId::new(kind, &name, *count);
+        *count += 1;
+        module.decls.push((decl, Decl { kind, name }));
+        source_map.decls.insert(
+            decl,
+            DeclSource {
+                node: SyntaxNodePtr::new(&node),
+                range: to_range(node.text_range()),
+                name_range: to_range(name_token.text_range()),
+            },
+        );
+    }
+
+    (module, source_map)
+}
+
+/// Lowers the contents of a declaration, given its syntax node.
+pub fn lower_body(decl: &SyntaxNode) -> (Body, BodySourceMap) {
+    let mut lowerer = Lowerer {
+        body: Body {
+            exprs: Default::default(),
+            pats: Default::default(),
+            binding: Idx::from_raw(0),
+            value: Idx::from_raw(0),
+        },
+        source_map: BodySourceMap::default(),
+    };
+
+    let end = trimmed_range(decl).end;
+    lowerer.body.binding = match token(decl, SyntaxKind::Identifier) {
+        Some(name) => lowerer.alloc_pat(
+            Pat::Bind(Name::new(name.text())),
+            to_range(name.text_range()),
+        ),
+        None => lowerer.alloc_pat(Pat::Missing, end..end),
+    };
+    lowerer.body.value = lowerer.lower_child_expr(decl);
+
+    (lowerer.body, lowerer.source_map)
+}
+
+struct Lowerer {
+    body: Body,
+    source_map: BodySourceMap,
+}
+
+impl Lowerer {
+    fn lower_expr(&mut self, node: &SyntaxNode) -> ExprId {
+        let range = trimmed_range(node);
+        let expr = match node.kind() {
+            SyntaxKind::Exp_Literal => {
+                match node.children_with_tokens().find_map(|it| it.into_token())
+                {
+                    Some(token) => literal(&token),
+                    None => Expr::Missing,
+                }
+            }
+            SyntaxKind::Exp_VariableRef => {
+                match token(node, SyntaxKind::Identifier) {
+                    Some(name) => Expr::Variable(Name::new(name.text())),
+                    None => Expr::Missing,
+                }
+            }
+            // Grouping doesn't change the expression inside
+            SyntaxKind::Exp_Paren | SyntaxKind::Exp_Indented => {
+                return self.lower_child_expr(node);
+            }
+            SyntaxKind::Exp_UnaryPrefix => {
+                let op = operator(node).and_then(|op| unary_op(op.kind()));
+                let operand = self.lower_child_expr(node);
+                match op {
+                    Some(op) => Expr::Unary { op, operand },
+                    None => Expr::Missing,
+                }
+            }
+            SyntaxKind::Exp_Binary => {
+                let op = operator(node).and_then(|op| binary_op(op.kind()));
+                let mut operands = expr_children(node);
+                let lhs = match operands.next() {
+                    Some(lhs) => self.lower_expr(&lhs),
+                    None => self.missing_expr(range.start..range.start),
+                };
+                let rhs = match operands.next() {
+                    Some(rhs) => self.lower_expr(&rhs),
+                    None => self.missing_expr(range.end..range.end),
+                };
+                match op {
+                    Some(op) => Expr::Binary { op, lhs, rhs },
+                    None => Expr::Missing,
+                }
+            }
+            _ => Expr::Missing,
+        };
+
+        self.alloc_expr(expr, range)
+    }
+
+    /// Lowers the first expression among the children of a node, or a missing
+    /// expression at the end of the node if it has none.
+    fn lower_child_expr(&mut self, node: &SyntaxNode) -> ExprId {
+        match expr_children(node).next() {
+            Some(child) => self.lower_expr(&child),
+            None => {
+                let end = trimmed_range(node).end;
+                self.missing_expr(end..end)
+            }
+        }
+    }
+
+    fn missing_expr(&mut self, range: Range<usize>) -> ExprId {
+        self.alloc_expr(Expr::Missing, range)
+    }
+
+    fn alloc_expr(&mut self, expr: Expr, range: Range<usize>) -> ExprId {
+        let id = self.body.exprs.alloc(expr);
+        self.source_map.exprs.insert(id, range);
+        id
+    }
+
+    fn alloc_pat(&mut self, pat: Pat, range: Range<usize>) -> PatId {
+        let id = self.body.pats.alloc(pat);
+        self.source_map.pats.insert(id, range);
+        id
+    }
+}
+
+fn literal(token: &SyntaxToken) -> Expr {
+    let text = token.text().to_string();
+    match token.kind() {
+        SyntaxKind::Lit_Integer => Expr::Literal(Literal::Integer(text)),
+        SyntaxKind::Lit_Float => Expr::Literal(Literal::Float(text)),
+        _ => Expr::Missing,
+    }
+}
+
+fn unary_op(kind: SyntaxKind) -> Option<UnaryOp> {
+    match kind {
+        SyntaxKind::Sym_Minus => Some(UnaryOp::Neg),
+        SyntaxKind::Sym_Bang => Some(UnaryOp::Not),
+        _ => None,
+    }
+}
+
+fn binary_op(kind: SyntaxKind) -> Option<BinaryOp> {
+    let op = match kind {
+        SyntaxKind::Sym_Plus => BinaryOp::Add,
+        SyntaxKind::Sym_Minus => BinaryOp::Sub,
+        SyntaxKind::Sym_Asterisk => BinaryOp::Mul,
+        SyntaxKind::Sym_ForwardSlash => BinaryOp::Div,
+        SyntaxKind::Sym_Eq => BinaryOp::Eq,
+        SyntaxKind::Sym_BangEq => BinaryOp::NotEq,
+        SyntaxKind::Sym_Lt => BinaryOp::Lt,
+        SyntaxKind::Sym_LtEq => BinaryOp::LtEq,
+        SyntaxKind::Sym_Gt => BinaryOp::Gt,
+        SyntaxKind::Sym_GtEq => BinaryOp::GtEq,
+        SyntaxKind::Sym_LThinArrow => BinaryOp::Assign,
+        SyntaxKind::Sym_Semicolon => BinaryOp::Seq,
+        _ => return None,
+    };
+    Some(op)
+}
+
+/// The children of a node that are expressions.
+fn expr_children(node: &SyntaxNode) -> impl Iterator<Item = SyntaxNode> {
+    node.children().filter(|child| is_expr(child.kind()))
+}
+
+fn is_expr(kind: SyntaxKind) -> bool {
+    use SyntaxKind::*;
+    matches!(
+        kind,
+        Exp_Binary
+            | Exp_Indented
+            | Exp_Literal
+            | Exp_Paren
+            | Exp_UnaryPrefix
+            | Exp_UnaryPostfix
+            | Exp_VariableRef
+    )
+}
+
+/// The first token of the given kind among the children of a node.
+fn token(node: &SyntaxNode, kind: SyntaxKind) -> Option<SyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| token.kind() == kind)
+}
+
+/// The operator of a unary or binary expression (i.e. its first symbol).
+fn operator(node: &SyntaxNode) -> Option<SyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| token.kind().is_symbol())
+}
+
+/// The range of a node without the trivia and layout tokens at its edges.
+fn trimmed_range(node: &SyntaxNode) -> Range<usize> {
+    let mut tokens = node
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| !token.kind().is_trivia() && !token.kind().is_layout());
+
+    match tokens.next() {
+        Some(first) => {
+            let last = tokens.last().unwrap_or_else(|| first.clone());
+            let start = usize::from(first.text_range().start());
+            start..usize::from(last.text_range().end())
+        }
+        None => {
+            let start = usize::from(node.text_range().start());
+            start..start
+        }
+    }
+}
+
+fn to_range(range: rowan::TextRange) -> Range<usize> {
+    range.start().into()..range.end().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expect_test::{expect, Expect};
+
+    /// Lowers every declaration of the source and prints the nodes of their
+    /// bodies, each with the source text of its range.
+    fn check(source: &str, expected: Expect) {
+        let root = helios_parser::parse((), source).syntax();
+        let (module, module_map) = lower_module(&root);
+
+        let mut actual = String::new();
+        for (id, decl) in module.decls() {
+            let range = module_map.decl(id).range.clone();
+            let node = root
+                .children()
+                .find(|node| to_range(node.text_range()) == range)
+                .unwrap();
+            let (body, body_map) = lower_body(&node);
+
+            let index = id.index();
+            actual += &format!("{:?} {}#{index}\n", decl.kind, decl.name);
+            for (pat, data) in body.pats.iter() {
+                let text = &source[body_map.pat_range(pat)];
+                actual += &format!("  {pat:?} = {data:?} {text:?}\n");
+            }
+            for (expr, data) in body.exprs.iter() {
+                let text = &source[body_map.expr_range(expr)];
+                actual += &format!("  {expr:?} = {data:?} {text:?}\n");
+            }
+            actual += &format!("  value: {:?}\n", body.value);
+        }
+
+        expected.assert_eq(&actual);
+    }
+
+    #[test]
+    fn test_lower_literals_and_variables() {
+        check(
+            "let a = 1\nlet b = a\nlet c = 2.5\n",
+            expect![[r#"
+            Global a#0
+              Pat#0 = Bind(Name("a")) "a"
+              Expr#0 = Literal(Integer("1")) "1"
+              value: Expr#0
+            Global b#0
+              Pat#0 = Bind(Name("b")) "b"
+              Expr#0 = Variable(Name("a")) "a"
+              value: Expr#0
+            Global c#0
+              Pat#0 = Bind(Name("c")) "c"
+              Expr#0 = Literal(Float("2.5")) "2.5"
+              value: Expr#0
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_lower_operators() {
+        check(
+            "let a = -(1 + b) * !c\n",
+            expect![[r#"
+            Global a#0
+              Pat#0 = Bind(Name("a")) "a"
+              Expr#0 = Literal(Integer("1")) "1"
+              Expr#1 = Variable(Name("b")) "b"
+              Expr#2 = Binary { op: Add, lhs: Expr#0, rhs: Expr#1 } "1 + b"
+              Expr#3 = Unary { op: Neg, operand: Expr#2 } "-(1 + b)"
+              Expr#4 = Variable(Name("c")) "c"
+              Expr#5 = Unary { op: Not, operand: Expr#4 } "!c"
+              Expr#6 = Binary { op: Mul, lhs: Expr#3, rhs: Expr#5 } "-(1 + b) * !c"
+              value: Expr#6
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_lower_parenthesized_expressions() {
+        check(
+            "let a = ((1)) + (b\n",
+            expect![[r#"
+            Global a#0
+              Pat#0 = Bind(Name("a")) "a"
+              Expr#0 = Literal(Integer("1")) "1"
+              Expr#1 = Variable(Name("b")) "b"
+              Expr#2 = Binary { op: Add, lhs: Expr#0, rhs: Expr#1 } "((1)) + (b"
+              value: Expr#2
+        "#]],
+        );
+    }
+
+    #[test]
+    fn test_lower_missing_nodes() {
+        check(
+            "let a = 1 +\nlet = 2\nlet b =\n",
+            expect![[r#"
+            Global a#0
+              Pat#0 = Bind(Name("a")) "a"
+              Expr#0 = Literal(Integer("1")) "1"
+              Expr#1 = Missing ""
+              Expr#2 = Binary { op: Add, lhs: Expr#0, rhs: Expr#1 } "1 +"
+              value: Expr#2
+            Global b#0
+              Pat#0 = Bind(Name("b")) "b"
+              Expr#0 = Missing ""
+              value: Expr#0
+        "#]],
+        );
+    }
+}
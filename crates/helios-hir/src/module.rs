@@ -0,0 +1,117 @@
+use crate::Name;
+use helios_syntax::SyntaxNodePtr;
+use std::collections::HashMap;
+use std::fmt::{self, Debug};
+use std::ops::Range;
+
+/// Identifies a declaration of a module.
+///
+/// An id isn't the position of its declaration, but its kind, a hash of its
+/// name and how many declarations with the same kind and hash come before it.
+/// Adding or removing a declaration only changes the ids of the declarations
+/// with the same name that come after it, so that what was computed for the
+/// others stays valid.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeclId {
+    kind: DeclKind,
+    name_hash: u32,
+    index: u32,
+}
+
+impl DeclId {
+    /// The id of the declaration with the given kind and name that comes
+    /// after `index` others with the same kind and name.
+    pub fn new(kind: DeclKind, name: &Name, index: u32) -> Self {
+        // FNV-1a, which doesn't change between builds like the standard
+        // library's hasher may
+        let name_hash =
+            name.as_str().bytes().fold(0x811c_9dc5, |hash, byte| {
+                (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+            });
+        Self {
+            kind,
+            name_hash,
+            index,
+        }
+    }
+
+    /// How many declarations with the same kind and name (or rather, the same
+    /// hash of their name) come before this one.
+    pub fn index(self) -> u32 {
+        self.index
+    }
+}
+
+impl Debug for DeclId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}#{:08x}#{}", self.kind, self.name_hash, self.index)
+    }
+}
+
+/// The declarations of a file, in the order they're declared.
+///
+/// Only what identifies each declaration is kept here; their contents are
+/// lowered separately (see [`Body`](crate::Body)), so that editing one of them
+/// doesn't change the others.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Module {
+    pub(crate) decls: Vec<(DeclId, Decl)>,
+}
+
+impl Module {
+    /// The declarations along with their ids, in the order they're declared.
+    pub fn decls(&self) -> impl Iterator<Item = (DeclId, &Decl)> + '_ {
+        self.decls.iter().map(|(id, decl)| (*id, decl))
+    }
+
+    /// The position of a declaration in the module.
+    pub fn position(&self, decl: DeclId) -> Option<usize> {
+        self.decls.iter().position(|(id, _)| *id == decl)
+    }
+
+    /// The declarations with the given name, in the order they're declared.
+    pub fn decls_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = DeclId> + 'a {
+        self.decls()
+            .filter(move |(_, decl)| decl.name.as_str() == name)
+            .map(|(id, _)| id)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decl {
+    pub kind: DeclKind,
+    pub name: Name,
+}
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeclKind {
+    /// A binding declared at the top level of a file (e.g. `let a = 1`).
+    Global,
+}
+
+/// Where the declarations of a [`Module`] come from in the source text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModuleSourceMap {
+    pub(crate) decls: HashMap<DeclId, DeclSource>,
+}
+
+impl ModuleSourceMap {
+    pub fn decl(&self, decl: DeclId) -> &DeclSource {
+        self.decls
+            .get(&decl)
+            .expect("every declaration of a module has a source")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeclSource {
+    /// The syntax node of the declaration, in the tree it was lowered from.
+    pub node: SyntaxNodePtr,
+    /// The range of the whole declaration (i.e. of its syntax node).
+    pub range: Range<usize>,
+    /// The range of the name of the declaration.
+    pub name_range: Range<usize>,
+}
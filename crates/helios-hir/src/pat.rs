@@ -0,0 +1,14 @@
+use crate::arena::Idx;
+use crate::Name;
+
+pub type PatId = Idx<Pat>;
+
+/// A pattern, which binds the names of the values it matches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pat {
+    /// A pattern that couldn't be lowered because of a syntax error (which
+    /// the parser already reported).
+    Missing,
+    /// A name bound to the whole value (e.g. `a` in `let a = 1`).
+    Bind(Name),
+}
@@ -0,0 +1,224 @@
+### The messages of the Helios compiler, in English (United States).
+###
+### Text between backticks is shown as code. Every message must also appear
+### in the catalogs of the other locales.
+
+## Syntax kinds
+##
+## The name of each kind of token or node, without an article. The `article`
+## attribute is put in front of the name when it's used on its own (or
+## `syntax-article` if there is no such attribute).
+
+syntax-article = a
+syntax-phrase = { $article } { $kind }
+syntax-with-code = { $phrase } (`{ $code }`)
+syntax-with-example = { $phrase } (such as `{ $example }`)
+
+syntax-keyword = { $keyword } keyword
+    .article = the
+
+syntax-sym-ampersand = ampersand symbol
+    .article = an
+syntax-sym-asterisk = asterisk symbol
+    .article = an
+syntax-sym-at = at symbol
+    .article = an
+syntax-sym-backslash = backslash symbol
+syntax-sym-bang = exclamation mark symbol
+    .article = an
+syntax-sym-bangeq = not equal symbol
+syntax-sym-caret = caret symbol
+syntax-sym-colon = colon symbol
+syntax-sym-comma = comma symbol
+syntax-sym-dollar = dollar symbol
+syntax-sym-dot = dot symbol
+syntax-sym-emdash = em-dash symbol
+    .article = an
+syntax-sym-endash = en-dash symbol
+    .article = an
+syntax-sym-eq = equals symbol
+    .article = an
+syntax-sym-forwardslash = forward slash symbol
+syntax-sym-minus = minus symbol
+syntax-sym-percent = percent symbol
+syntax-sym-pipe = pipe symbol
+syntax-sym-plus = plus symbol
+syntax-sym-question = question mark symbol
+syntax-sym-semicolon = semicolon symbol
+syntax-sym-sterling = sterling symbol
+syntax-sym-tilde = tilde symbol
+syntax-sym-lt = less than symbol
+syntax-sym-lteq = less than equal symbol
+syntax-sym-gt = greater than symbol
+syntax-sym-gteq = greater than equal symbol
+syntax-sym-lthinarrow = leftwards thin arrow symbol
+syntax-sym-rthinarrow = rightwards thin arrow symbol
+syntax-sym-thickarrow = thick arrow symbol
+syntax-sym-walrus = walrus symbol
+syntax-sym-lbrace = opening curly brace symbol
+    .article = an
+syntax-sym-rbrace = closing curly brace symbol
+syntax-sym-lbracket = opening square bracket symbol
+    .article = an
+syntax-sym-rbracket = closing square bracket symbol
+syntax-sym-lparen = opening parenthesis symbol
+    .article = an
+syntax-sym-rparen = closing parenthesis symbol
+
+syntax-lit-character = character literal
+syntax-lit-float = float literal
+syntax-lit-integer = integer literal
+    .article = an
+syntax-lit-string = string literal
+
+syntax-exp-binary = binary expression
+syntax-exp-indented = indented expression
+    .article = an
+syntax-exp-literal = literal expression
+syntax-exp-paren = parenthesized expression
+syntax-exp-unaryprefix = prefixed unary expression
+syntax-exp-unarypostfix = postfixed unary expression
+syntax-exp-variableref = variable reference expression
+syntax-exp-unnamed = expression
+    .article = an
+
+syntax-dec-globalbinding = global binding declaration
+
+syntax-comment = comment
+syntax-doccomment = documentation comment
+syntax-whitespace = whitespace
+syntax-indent = indent
+    .article = an
+syntax-dedent = dedent
+syntax-newline = new line
+syntax-identifier = identifier
+    .article = an
+syntax-reservedidentifier = reserved identifier
+syntax-placeholder = placeholder
+syntax-unknownchar = unknown character
+    .article = an
+syntax-error = error
+    .article = an
+
+## Syntax categories
+##
+## The general name of related kinds (e.g. every keyword), for the kinds that
+## belong to one.
+
+category-keyword = keyword
+category-symbol = symbol
+category-literal = literal
+category-expression = expression
+category-declaration = declaration
+category-comment = comment
+category-identifier = identifier
+
+## ASCII symbols
+##
+## The names of the symbols that other characters may be confused with.
+
+ascii-apostrophe = apostrophe
+    .article = an
+ascii-quotation-mark = quotation mark
+ascii-semicolon = semicolon
+ascii-hyphen-minus = hyphen-minus
+ascii-asterisk = asterisk
+    .article = an
+ascii-slash = slash
+ascii-exclamation-mark = exclamation mark
+    .article = an
+ascii-space = space
+ascii-percent-sign = percent sign
+ascii-ampersand = ampersand
+    .article = an
+ascii-left-parenthesis = left parenthesis
+ascii-right-parenthesis = right parenthesis
+ascii-plus-sign = plus sign
+ascii-comma = comma
+ascii-full-stop = full stop
+ascii-colon = colon
+ascii-less-than-sign = less-than sign
+ascii-equals-sign = equals sign
+    .article = an
+ascii-greater-than-sign = greater-than sign
+ascii-question-mark = question mark
+ascii-at-sign = at sign
+    .article = an
+ascii-left-square-bracket = left square bracket
+ascii-backslash = backslash
+ascii-right-square-bracket = right square bracket
+ascii-caret = caret
+ascii-left-curly-bracket = left curly bracket
+ascii-vertical-bar = vertical bar
+ascii-right-curly-bracket = right curly bracket
+ascii-tilde = tilde
+
+## Diagnostics
+##
+## The messages of each diagnostic, prefixed with its code. Suggestions are
+## shown after `emit-help-message`.
+
+context-unknown = something
+paren-suggestion = inserting the missing closing parenthesis
+
+e0001-title = Missing { $kind }
+e0001-description = I was partway through { $context } when I got stuck here:
+e0001-message = I expected { $expected } here.
+
+e0002-title = Unexpected { $kind }
+e0002-title-end-of-file = Unexpected end of file
+e0002-description = I was partway through { $context } when I got stuck here:
+e0002-message = I expected { $expected } here.
+e0002-message-one-of = I expected one of the following here:
+e0002-keyword-hint = It looks like you're trying to use the reserved keyword `{ $keyword }` as an identifier! Try using a different name instead.
+e0002-keyword-suggestion = renaming it to `{ $name }`
+
+e0003-title = Unknown character
+e0003-description = I encountered a token I don't know how to handle:
+e0003-message = The character `{ $character }` is not a valid token. Did you mean to write it?
+e0003-suggestion = removing the character
+
+e0005-title = Confusable character
+e0005-description = I found a character that looks like a symbol, but isn't:
+e0005-message = The character `{ $character }` (U+{ $codepoint }, { $name }) looks like `{ $symbol }` ({ $symbol-name }), but it's a different character.
+e0005-suggestion = replacing it with { $symbol-phrase }
+
+e0006-title = Unresolved name
+e0006-description = I couldn't find what this name refers to:
+e0006-message = There is no binding named `{ $name }` in scope.
+e0006-hint = Did you forget to declare it with `let`?
+e0006-suggestion = using `{ $name }` instead
+
+e0007-title = Mismatched types
+e0007-description = This expression doesn't have the type I expected:
+e0007-expected = I expected it to be of type:
+e0007-found = But it is of type:
+e0007-not-numeric = I expected it to be a number (`Int` or `Float`), but it is of type:
+
+## Emitting diagnostics
+
+emit-bug = Bug
+emit-error = Error
+emit-warning = Warning
+emit-note = Note
+emit-hint = Hint
+emit-help = Help
+emit-help-message = try { $suggestion }:
+emit-more-information = For more information about this diagnostic, try `helios explain { $code }`.
+
+## Building
+
+build-failed = Failed to build due to { $count } previous { $count ->
+        [one] error
+       *[other] errors
+    }
+build-io-error = Failed to build due to an IO error: { $error }
+build-errors-omitted = { $count } more { $count ->
+        [one] error
+       *[other] errors
+    } not shown (the error limit is { $limit })
+build-watch-summary = Checked { $count } changed { $count ->
+        [one] file
+       *[other] files
+    } in { $elapsed } ({ $new } new, { $fixed } fixed)
+build-watch-read-error = Couldn't read `{ $path }`: { $error }
@@ -0,0 +1,219 @@
+### Les messages du compilateur Helios, en français.
+###
+### Le texte entre accents graves est affiché comme du code. Chaque message
+### doit aussi apparaître dans les catalogues des autres langues.
+
+## Genres syntaxiques
+
+syntax-article = un
+syntax-phrase = { $article } { $kind }
+syntax-with-code = { $phrase } (`{ $code }`)
+syntax-with-example = { $phrase } (comme `{ $example }`)
+
+syntax-keyword = mot-clé { $keyword }
+    .article = le
+
+syntax-sym-ampersand = symbole esperluette
+syntax-sym-asterisk = symbole astérisque
+syntax-sym-at = symbole arobase
+syntax-sym-backslash = symbole barre oblique inversée
+syntax-sym-bang = symbole point d'exclamation
+syntax-sym-bangeq = symbole différent
+syntax-sym-caret = symbole accent circonflexe
+syntax-sym-colon = symbole deux-points
+syntax-sym-comma = symbole virgule
+syntax-sym-dollar = symbole dollar
+syntax-sym-dot = symbole point
+syntax-sym-emdash = symbole tiret cadratin
+syntax-sym-endash = symbole tiret demi-cadratin
+syntax-sym-eq = symbole égal
+syntax-sym-forwardslash = symbole barre oblique
+syntax-sym-minus = symbole moins
+syntax-sym-percent = symbole pour cent
+syntax-sym-pipe = symbole barre verticale
+syntax-sym-plus = symbole plus
+syntax-sym-question = symbole point d'interrogation
+syntax-sym-semicolon = symbole point-virgule
+syntax-sym-sterling = symbole livre sterling
+syntax-sym-tilde = symbole tilde
+syntax-sym-lt = symbole inférieur à
+syntax-sym-lteq = symbole inférieur ou égal à
+syntax-sym-gt = symbole supérieur à
+syntax-sym-gteq = symbole supérieur ou égal à
+syntax-sym-lthinarrow = symbole flèche fine vers la gauche
+syntax-sym-rthinarrow = symbole flèche fine vers la droite
+syntax-sym-thickarrow = symbole flèche épaisse
+syntax-sym-walrus = symbole morse
+syntax-sym-lbrace = symbole accolade ouvrante
+syntax-sym-rbrace = symbole accolade fermante
+syntax-sym-lbracket = symbole crochet ouvrant
+syntax-sym-rbracket = symbole crochet fermant
+syntax-sym-lparen = symbole parenthèse ouvrante
+syntax-sym-rparen = symbole parenthèse fermante
+
+syntax-lit-character = littéral caractère
+syntax-lit-float = littéral flottant
+syntax-lit-integer = littéral entier
+syntax-lit-string = littéral chaîne
+
+syntax-exp-binary = expression binaire
+    .article = une
+syntax-exp-indented = expression indentée
+    .article = une
+syntax-exp-literal = expression littérale
+    .article = une
+syntax-exp-paren = expression entre parenthèses
+    .article = une
+syntax-exp-unaryprefix = expression unaire préfixe
+    .article = une
+syntax-exp-unarypostfix = expression unaire postfixe
+    .article = une
+syntax-exp-variableref = expression de référence à une variable
+    .article = une
+syntax-exp-unnamed = expression
+    .article = une
+
+syntax-dec-globalbinding = déclaration de liaison globale
+    .article = une
+
+syntax-comment = commentaire
+syntax-doccomment = commentaire de documentation
+syntax-whitespace = espace blanc
+syntax-indent = indentation
+    .article = une
+syntax-dedent = désindentation
+    .article = une
+syntax-newline = nouvelle ligne
+    .article = une
+syntax-identifier = identifiant
+syntax-reservedidentifier = identifiant réservé
+syntax-placeholder = espace réservé
+syntax-unknownchar = caractère inconnu
+syntax-error = erreur
+    .article = une
+
+## Catégories syntaxiques
+
+category-keyword = mot-clé
+category-symbol = symbole
+category-literal = littéral
+category-expression = expression
+category-declaration = déclaration
+category-comment = commentaire
+category-identifier = identifiant
+
+## Symboles ASCII
+
+ascii-apostrophe = apostrophe
+    .article = une
+ascii-quotation-mark = guillemet droit
+ascii-semicolon = point-virgule
+ascii-hyphen-minus = trait d'union
+ascii-asterisk = astérisque
+ascii-slash = barre oblique
+    .article = une
+ascii-exclamation-mark = point d'exclamation
+ascii-space = espace
+ascii-percent-sign = signe pour cent
+ascii-ampersand = esperluette
+    .article = une
+ascii-left-parenthesis = parenthèse ouvrante
+    .article = une
+ascii-right-parenthesis = parenthèse fermante
+    .article = une
+ascii-plus-sign = signe plus
+ascii-comma = virgule
+    .article = une
+ascii-full-stop = point
+ascii-colon = deux-points
+ascii-less-than-sign = signe inférieur à
+ascii-equals-sign = signe égal
+ascii-greater-than-sign = signe supérieur à
+ascii-question-mark = point d'interrogation
+ascii-at-sign = arobase
+    .article = une
+ascii-left-square-bracket = crochet ouvrant
+ascii-backslash = barre oblique inversée
+    .article = une
+ascii-right-square-bracket = crochet fermant
+ascii-caret = accent circonflexe
+ascii-left-curly-bracket = accolade ouvrante
+    .article = une
+ascii-vertical-bar = barre verticale
+    .article = une
+ascii-right-curly-bracket = accolade fermante
+    .article = une
+ascii-tilde = tilde
+
+## Diagnostics
+
+context-unknown = quelque chose
+paren-suggestion = essayez d'insérer la parenthèse fermante manquante
+
+e0001-title = Élément manquant : { $kind }
+e0001-description = J'étais en train d'analyser { $context } quand je me suis retrouvé bloqué ici :
+e0001-message = J'attendais { $expected } ici.
+
+e0002-title = Élément inattendu : { $kind }
+e0002-title-end-of-file = Fin de fichier inattendue
+e0002-description = J'étais en train d'analyser { $context } quand je me suis retrouvé bloqué ici :
+e0002-message = J'attendais { $expected } ici.
+e0002-message-one-of = J'attendais l'un des éléments suivants ici :
+e0002-keyword-hint = On dirait que vous essayez d'utiliser le mot-clé réservé `{ $keyword }` comme identifiant ! Essayez plutôt un autre nom.
+e0002-keyword-suggestion = essayez de le renommer en `{ $name }`
+
+e0003-title = Caractère inconnu
+e0003-description = J'ai rencontré un lexème que je ne sais pas traiter :
+e0003-message = Le caractère `{ $character }` n'est pas un lexème valide. Vouliez-vous vraiment l'écrire ?
+e0003-suggestion = essayez de supprimer le caractère
+
+e0005-title = Caractère trompeur
+e0005-description = J'ai trouvé un caractère qui ressemble à un symbole, mais qui n'en est pas un :
+e0005-message = Le caractère `{ $character }` (U+{ $codepoint }, { $name }) ressemble à `{ $symbol }` ({ $symbol-name }), mais c'est un caractère différent.
+e0005-suggestion = essayez de le remplacer par { $symbol-phrase }
+
+e0006-title = Nom non résolu
+e0006-description = Je n'ai pas trouvé ce à quoi ce nom fait référence :
+e0006-message = Aucune liaison nommée `{ $name }` n'est visible ici.
+e0006-hint = Avez-vous oublié de la déclarer avec `let` ?
+e0006-suggestion = essayez plutôt d'utiliser `{ $name }`
+
+e0007-title = Types incompatibles
+e0007-description = Cette expression n'a pas le type que j'attendais :
+e0007-expected = Je m'attendais à ce qu'elle soit de type :
+e0007-found = Mais elle est de type :
+e0007-not-numeric = Je m'attendais à ce qu'elle soit un nombre (`Int` ou `Float`), mais elle est de type :
+
+## Affichage des diagnostics
+
+emit-bug = Bogue
+emit-error = Erreur
+emit-warning = Avertissement
+emit-note = Note
+emit-hint = Indice
+emit-help = Aide
+emit-help-message = { $suggestion } :
+emit-more-information = Pour plus d'informations sur ce diagnostic, essayez `helios explain { $code }`.
+
+## Compilation
+
+build-failed = La compilation a échoué à cause { $count ->
+        [one] de { $count } erreur précédente
+       *[other] de { $count } erreurs précédentes
+    }
+build-io-error = La compilation a échoué à cause d'une erreur d'entrée-sortie : { $error }
+build-errors-omitted = { $count ->
+        [one] { $count } autre erreur n'est pas affichée
+       *[other] { $count } autres erreurs ne sont pas affichées
+    } (la limite d'erreurs est de { $limit })
+build-watch-summary = { $count ->
+        [one] { $count } fichier modifié vérifié
+       *[other] { $count } fichiers modifiés vérifiés
+    } en { $elapsed } ({ $new } { $new ->
+        [one] nouveau
+       *[other] nouveaux
+    }, { $fixed } { $fixed ->
+        [one] corrigé
+       *[other] corrigés
+    })
+build-watch-read-error = Impossible de lire `{ $path }` : { $error }
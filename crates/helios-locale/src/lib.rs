@@ -0,0 +1,514 @@
+//! Localizing the messages of the compiler.
+//!
+//! Every message shown to users has an id in the [Fluent] catalog of each
+//! [`Locale`], falling back to [`Locale::EnUs`] when a catalog lacks it.
+//!
+//! [Fluent]: https://projectfluent.org
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use fluent_syntax::ast::Pattern;
+use helios_formatting::{FormattedString, FormattedStringSegment};
+use std::borrow::Cow;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+/// Marks the start of an argument placeholder (followed by the index of the
+/// argument and [`PLACEHOLDER_END`]).
+///
+/// Every argument but numbers is passed to Fluent as a placeholder and only
+/// substituted once the markup of the message has been read, so the text of
+/// an argument (which may contain backticks or these characters) is never
+/// interpreted.
+const PLACEHOLDER_START: char = '\u{E000}';
+const PLACEHOLDER_END: char = '\u{E001}';
+
+/// A locale the messages of the compiler are available in.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Locale {
+    /// English (United States), which every message is available in.
+    #[default]
+    EnUs,
+    /// French.
+    Fr,
+}
+
+/// The locale used when none is given explicitly.
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+impl Locale {
+    /// Every supported locale.
+    pub const ALL: &'static [Locale] = &[Locale::EnUs, Locale::Fr];
+
+    /// The BCP 47 language tag of this locale (e.g. `en-US`).
+    pub fn tag(self) -> &'static str {
+        match self {
+            Self::EnUs => "en-US",
+            Self::Fr => "fr",
+        }
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Self::EnUs => include_str!("../locales/en-US/helios.ftl"),
+            Self::Fr => include_str!("../locales/fr/helios.ftl"),
+        }
+    }
+
+    /// The locale used when none is given explicitly, which is
+    /// [`Locale::EnUs`] unless it was changed with [`Locale::set_current`].
+    pub fn current() -> Self {
+        Self::ALL[CURRENT.load(Ordering::Relaxed) as usize]
+    }
+
+    /// Changes the locale used when none is given explicitly.
+    pub fn set_current(self) {
+        let index = Self::ALL.iter().position(|it| *it == self).unwrap_or(0);
+        CURRENT.store(index as u8, Ordering::Relaxed);
+    }
+
+    /// Finds the supported locale closest to the given language tag or POSIX
+    /// locale name (e.g. `fr-CA` or `fr_CA.UTF-8`), if any.
+    pub fn negotiate(requested: &str) -> Option<Self> {
+        // POSIX locale names may end with an encoding or a modifier
+        let requested = requested
+            .split(['.', '@'])
+            .next()
+            .unwrap_or_default()
+            .replace('_', "-");
+        let requested = requested.parse::<LanguageIdentifier>().ok()?;
+
+        let exact = Self::ALL.iter().find(|locale| {
+            locale.identifier().language == requested.language
+                && locale.identifier().region == requested.region
+        });
+        let same_language = Self::ALL
+            .iter()
+            .find(|locale| locale.identifier().language == requested.language);
+
+        exact.or(same_language).copied()
+    }
+
+    /// Finds the locale requested by the environment, looking at `LC_ALL`,
+    /// `LC_MESSAGES` and `LANG` (in that order), or [`Locale::EnUs`] if none
+    /// of them names a supported locale.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::negotiate(&value))
+            .unwrap_or_default()
+    }
+
+    fn identifier(self) -> LanguageIdentifier {
+        self.tag().parse().expect("locale tags should be valid")
+    }
+
+    fn bundle(self) -> &'static FluentBundle<FluentResource> {
+        static BUNDLES: [OnceLock<FluentBundle<FluentResource>>; 2] =
+            [OnceLock::new(), OnceLock::new()];
+
+        let index = Self::ALL.iter().position(|it| *it == self).unwrap_or(0);
+        BUNDLES[index].get_or_init(|| {
+            let resource = FluentResource::try_new(self.source().to_string())
+                .unwrap_or_else(|(_, errors)| {
+                    panic!("Invalid catalog for {}: {errors:?}", self.tag())
+                });
+
+            let mut bundle =
+                FluentBundle::new_concurrent(vec![self.identifier()]);
+            // Unicode isolation marks would end up in the terminal output
+            bundle.set_use_isolating(false);
+            bundle
+                .add_resource(resource)
+                .expect("catalog messages should be unique");
+            bundle
+        })
+    }
+
+    /// Determines if the catalog of this locale (or of [`Locale::EnUs`]) has
+    /// the given message (or attribute, written as `message.attribute`).
+    pub fn has_message(self, id: &str) -> bool {
+        self.pattern(id).is_some()
+    }
+
+    /// The article put in front of the given message when it's used on its
+    /// own, which is its `article` attribute (or `syntax-article` if it has
+    /// none).
+    pub fn article(self, id: &str) -> String {
+        let article_id = format!("{id}.article");
+        if self.has_message(&article_id) {
+            self.text(&article_id, &[])
+        } else {
+            self.text("syntax-article", &[])
+        }
+    }
+
+    /// Finds the pattern of the given message (or attribute) and the bundle
+    /// it comes from.
+    fn pattern(
+        self,
+        id: &str,
+    ) -> Option<(
+        &'static FluentBundle<FluentResource>,
+        &'static Pattern<&'static str>,
+    )> {
+        let (message_id, attribute) = match id.split_once('.') {
+            Some((message_id, attribute)) => (message_id, Some(attribute)),
+            None => (id, None),
+        };
+
+        // The attributes of a message (e.g. its article) only make sense in
+        // the locale the message itself comes from
+        let bundle = [self, Self::EnUs]
+            .into_iter()
+            .map(Self::bundle)
+            .find(|bundle| bundle.has_message(message_id))?;
+        let message = bundle.get_message(message_id)?;
+        let pattern = match attribute {
+            Some(attribute) => message.get_attribute(attribute)?.value(),
+            None => message.value()?,
+        };
+
+        Some((bundle, pattern))
+    }
+
+    /// Formats the message (or an attribute of the message, if the id is
+    /// written as `message.attribute`) with the given arguments, or returns
+    /// `None` if there is no such message.
+    fn format(self, id: &str, args: &[(&str, Arg)]) -> Option<String> {
+        let (bundle, pattern) = self.pattern(id)?;
+
+        let mut fluent_args = FluentArgs::new();
+        for (index, (name, arg)) in args.iter().enumerate() {
+            // Numbers are given as is, so that messages can select a plural
+            // form with them
+            let value = match arg {
+                Arg::Number(number) => FluentValue::from(*number),
+                _ => FluentValue::from(format!(
+                    "{PLACEHOLDER_START}{index}{PLACEHOLDER_END}"
+                )),
+            };
+            fluent_args.set(*name, value);
+        }
+
+        let mut errors = Vec::new();
+        let text =
+            bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+        debug_assert!(errors.is_empty(), "{id}: {errors:?}");
+        Some(text.into_owned())
+    }
+
+    /// Formats the given message as a [`FormattedString`], where the text
+    /// between backticks is code.
+    ///
+    /// If the message is missing from every catalog, its id is returned
+    /// instead (which makes the missing message easy to spot).
+    pub fn formatted(self, id: &str, args: &[(&str, Arg)]) -> FormattedString {
+        match self.format(id, args) {
+            Some(text) => parse_markup(&text, args),
+            None => FormattedString::new().text(id),
+        }
+    }
+
+    /// Formats the given message as text, where code is still wrapped in
+    /// backticks (e.g. for the title of a diagnostic).
+    pub fn text(self, id: &str, args: &[(&str, Arg)]) -> String {
+        let text = match self.format(id, args) {
+            Some(text) => text,
+            None => return id.to_string(),
+        };
+
+        let mut output = String::with_capacity(text.len());
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                PLACEHOLDER_START => {
+                    let arg = placeholder_arg(&mut chars, args);
+                    output.push_str(&arg.to_string());
+                }
+                c => output.push(c),
+            }
+        }
+        output
+    }
+}
+
+impl Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.tag())
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::negotiate(s).ok_or_else(|| {
+            let supported = Self::ALL
+                .iter()
+                .map(|locale| format!("`{locale}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("unsupported language `{s}` (expected one of {supported})")
+        })
+    }
+}
+
+/// A named argument of a message.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Arg {
+    /// Text inserted as is.
+    Text(String),
+    /// A number, which messages may choose a plural form with (e.g.
+    /// `{ $count -> [one] error *[other] errors }`).
+    Number(i64),
+    /// A formatted string, whose segments are kept when the message is
+    /// formatted with [`Locale::formatted`].
+    Formatted(FormattedString),
+}
+
+impl Display for Arg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) => f.write_str(text),
+            Self::Number(number) => write!(f, "{number}"),
+            Self::Formatted(formatted) => write!(f, "{formatted}"),
+        }
+    }
+}
+
+impl From<&str> for Arg {
+    fn from(text: &str) -> Self {
+        Self::Text(text.to_string())
+    }
+}
+
+impl From<String> for Arg {
+    fn from(text: String) -> Self {
+        Self::Text(text)
+    }
+}
+
+impl From<Cow<'_, str>> for Arg {
+    fn from(text: Cow<'_, str>) -> Self {
+        Self::Text(text.into_owned())
+    }
+}
+
+impl From<i64> for Arg {
+    fn from(number: i64) -> Self {
+        Self::Number(number)
+    }
+}
+
+impl From<usize> for Arg {
+    fn from(number: usize) -> Self {
+        Self::Number(i64::try_from(number).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<FormattedString> for Arg {
+    fn from(formatted: FormattedString) -> Self {
+        Self::Formatted(formatted)
+    }
+}
+
+/// Reads the index of a placeholder (right after its start) and returns the
+/// argument it stands for.
+fn placeholder_arg<'a>(
+    chars: &mut std::str::Chars,
+    args: &'a [(&str, Arg)],
+) -> &'a Arg {
+    let index = chars
+        .take_while(|c| *c != PLACEHOLDER_END)
+        .collect::<String>()
+        .parse::<usize>()
+        .expect("placeholders should have an index");
+    &args[index].1
+}
+
+/// Turns a formatted message into a [`FormattedString`], where the text
+/// between backticks is code and placeholders are replaced with their
+/// arguments.
+fn parse_markup(text: &str, args: &[(&str, Arg)]) -> FormattedString {
+    let mut formatted = FormattedString::new();
+    let mut current = String::new();
+    let mut is_code = false;
+
+    let flush =
+        |formatted: &mut FormattedString, current: &mut String, is_code| {
+            if !current.is_empty() {
+                let text = std::mem::take(current);
+                formatted.push(if is_code {
+                    FormattedStringSegment::Code(text)
+                } else {
+                    FormattedStringSegment::Text(text)
+                });
+            }
+        };
+
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '`' => {
+                flush(&mut formatted, &mut current, is_code);
+                is_code = !is_code;
+            }
+            PLACEHOLDER_START => match placeholder_arg(&mut chars, args) {
+                Arg::Formatted(arg) if !is_code => {
+                    // Text is merged with the text around the placeholder
+                    for segment in arg.segments() {
+                        match segment {
+                            FormattedStringSegment::Text(text) => {
+                                current.push_str(text)
+                            }
+                            segment => {
+                                flush(&mut formatted, &mut current, is_code);
+                                formatted.push(segment.clone());
+                            }
+                        }
+                    }
+                }
+                arg => current.push_str(&arg.to_string()),
+            },
+            c => current.push(c),
+        }
+    }
+    flush(&mut formatted, &mut current, is_code);
+
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(Locale::negotiate("en-US"), Some(Locale::EnUs));
+        assert_eq!(Locale::negotiate("en_GB.UTF-8"), Some(Locale::EnUs));
+        assert_eq!(Locale::negotiate("fr_CA.UTF-8@euro"), Some(Locale::Fr));
+        assert_eq!(Locale::negotiate("fr"), Some(Locale::Fr));
+        assert_eq!(Locale::negotiate("C"), None);
+        assert_eq!(Locale::negotiate("de-DE"), None);
+        assert!("klingon".parse::<Locale>().is_err());
+    }
+
+    #[test]
+    fn test_catalogs_have_the_same_messages() {
+        let ids = |locale: Locale| {
+            let resource = FluentResource::try_new(locale.source().to_string())
+                .expect("catalogs should be valid");
+            let mut ids = resource
+                .entries()
+                .filter_map(|entry| match entry {
+                    fluent_syntax::ast::Entry::Message(message) => {
+                        Some(message.id.name.to_string())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            ids.sort_unstable();
+            ids
+        };
+
+        let english = ids(Locale::EnUs);
+        for locale in Locale::ALL {
+            assert_eq!(ids(*locale), english, "{locale}");
+        }
+    }
+
+    #[test]
+    fn test_formatted() {
+        let character = Arg::from("'`'");
+        let string = Locale::EnUs
+            .formatted("e0003-message", &[("character", character)]);
+        assert_eq!(
+            string.segments(),
+            &[
+                FormattedStringSegment::text("The character "),
+                FormattedStringSegment::code("'`'"),
+                FormattedStringSegment::text(
+                    " is not a valid token. Did you mean to write it?"
+                ),
+            ]
+        );
+
+        let string = Locale::Fr
+            .formatted("e0003-message", &[("character", Arg::from("'$'"))]);
+        assert_eq!(
+            string.render_plain(),
+            "Le caractère `'$'` n'est pas un lexème valide. Vouliez-vous \
+             vraiment l'écrire ?"
+        );
+    }
+
+    #[test]
+    fn test_formatted_arguments() {
+        let kind = FormattedString::new()
+            .text("a symbol (")
+            .code("+")
+            .text(")");
+        let string = Locale::EnUs
+            .formatted("e0001-message", &[("expected", Arg::from(kind))]);
+        assert_eq!(string.render_plain(), "I expected a symbol (`+`) here.");
+        assert_eq!(
+            string.segments()[0],
+            FormattedStringSegment::text("I expected a symbol (")
+        );
+        assert_eq!(string.segments()[1], FormattedStringSegment::code("+"));
+    }
+
+    #[test]
+    fn test_arguments_are_not_interpreted() {
+        let character = "'\u{E000}1\u{E001}`\u{E002}'";
+        let string = Locale::EnUs
+            .formatted("e0003-message", &[("character", Arg::from(character))]);
+        assert_eq!(
+            string.segments()[1],
+            FormattedStringSegment::code(character)
+        );
+
+        let title = Locale::EnUs
+            .text("e0003-message", &[("character", Arg::from(character))]);
+        assert!(title.contains(&format!("`{character}`")));
+    }
+
+    #[test]
+    fn test_plurals() {
+        let failed = |locale: Locale, count: usize| {
+            locale.text("build-failed", &[("count", count.into())])
+        };
+        assert_eq!(
+            failed(Locale::EnUs, 1),
+            "Failed to build due to 1 previous error"
+        );
+        assert_eq!(
+            failed(Locale::EnUs, 2),
+            "Failed to build due to 2 previous errors"
+        );
+        assert_eq!(
+            failed(Locale::Fr, 2),
+            "La compilation a échoué à cause de 2 erreurs précédentes"
+        );
+    }
+
+    #[test]
+    fn test_missing_messages() {
+        assert_eq!(Locale::Fr.text("no-such-message", &[]), "no-such-message");
+        assert!(Locale::Fr.has_message("e0001-title"));
+        assert!(!Locale::Fr.has_message("no-such-message"));
+        assert!(Locale::EnUs.has_message("syntax-keyword.article"));
+        assert!(!Locale::EnUs.has_message("syntax-sym-at.no-such-attribute"));
+        // The article of a French message is never taken from English
+        assert!(Locale::EnUs.has_message("syntax-sym-at.article"));
+        assert!(!Locale::Fr.has_message("syntax-sym-at.article"));
+        assert_eq!(Locale::EnUs.article("syntax-sym-at"), "an");
+        assert_eq!(Locale::Fr.article("syntax-sym-at"), "un");
+    }
+}
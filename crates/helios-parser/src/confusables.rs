@@ -0,0 +1,113 @@
+//! Characters that look like ASCII symbols.
+//!
+//! The lexer uses [`confusable`] to tell which symbol was meant by a character
+//! such as a smart quote or a full-width operator.
+
+/// A character that looks like an ASCII symbol.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Confusable {
+    /// The Unicode name of the confusable character.
+    pub name: &'static str,
+    /// The ASCII symbol it looks like.
+    pub ascii: char,
+    /// The name of the ASCII symbol.
+    pub ascii_name: &'static str,
+}
+
+#[rustfmt::skip]
+const CONFUSABLES: &[(char, &str, char, &str)] = &[
+    // Quotation marks
+    ('\u{2018}', "LEFT SINGLE QUOTATION MARK", '\'', "apostrophe"),
+    ('\u{2019}', "RIGHT SINGLE QUOTATION MARK", '\'', "apostrophe"),
+    ('\u{201B}', "SINGLE HIGH-REVERSED-9 QUOTATION MARK", '\'', "apostrophe"),
+    ('\u{2032}', "PRIME", '\'', "apostrophe"),
+    ('\u{201C}', "LEFT DOUBLE QUOTATION MARK", '"', "quotation mark"),
+    ('\u{201D}', "RIGHT DOUBLE QUOTATION MARK", '"', "quotation mark"),
+    ('\u{201F}', "DOUBLE HIGH-REVERSED-9 QUOTATION MARK", '"', "quotation mark"),
+    ('\u{2033}', "DOUBLE PRIME", '"', "quotation mark"),
+
+    // Punctuation and operators
+    ('\u{037E}', "GREEK QUESTION MARK", ';', "semicolon"),
+    ('\u{2212}', "MINUS SIGN", '-', "hyphen-minus"),
+    ('\u{2010}', "HYPHEN", '-', "hyphen-minus"),
+    ('\u{00D7}', "MULTIPLICATION SIGN", '*', "asterisk"),
+    ('\u{2217}', "ASTERISK OPERATOR", '*', "asterisk"),
+    ('\u{2215}', "DIVISION SLASH", '/', "slash"),
+    ('\u{2044}', "FRACTION SLASH", '/', "slash"),
+    ('\u{01C3}', "LATIN LETTER RETROFLEX CLICK", '!', "exclamation mark"),
+
+    // Spaces
+    ('\u{00A0}', "NO-BREAK SPACE", ' ', "space"),
+    ('\u{2002}', "EN SPACE", ' ', "space"),
+    ('\u{2003}', "EM SPACE", ' ', "space"),
+    ('\u{2007}', "FIGURE SPACE", ' ', "space"),
+    ('\u{2009}', "THIN SPACE", ' ', "space"),
+    ('\u{202F}', "NARROW NO-BREAK SPACE", ' ', "space"),
+    ('\u{3000}', "IDEOGRAPHIC SPACE", ' ', "space"),
+
+    // Full-width forms
+    ('\u{FF01}', "FULLWIDTH EXCLAMATION MARK", '!', "exclamation mark"),
+    ('\u{FF02}', "FULLWIDTH QUOTATION MARK", '"', "quotation mark"),
+    ('\u{FF05}', "FULLWIDTH PERCENT SIGN", '%', "percent sign"),
+    ('\u{FF06}', "FULLWIDTH AMPERSAND", '&', "ampersand"),
+    ('\u{FF07}', "FULLWIDTH APOSTROPHE", '\'', "apostrophe"),
+    ('\u{FF08}', "FULLWIDTH LEFT PARENTHESIS", '(', "left parenthesis"),
+    ('\u{FF09}', "FULLWIDTH RIGHT PARENTHESIS", ')', "right parenthesis"),
+    ('\u{FF0A}', "FULLWIDTH ASTERISK", '*', "asterisk"),
+    ('\u{FF0B}', "FULLWIDTH PLUS SIGN", '+', "plus sign"),
+    ('\u{FF0C}', "FULLWIDTH COMMA", ',', "comma"),
+    ('\u{FF0D}', "FULLWIDTH HYPHEN-MINUS", '-', "hyphen-minus"),
+    ('\u{FF0E}', "FULLWIDTH FULL STOP", '.', "full stop"),
+    ('\u{FF0F}', "FULLWIDTH SOLIDUS", '/', "slash"),
+    ('\u{FF1A}', "FULLWIDTH COLON", ':', "colon"),
+    ('\u{FF1B}', "FULLWIDTH SEMICOLON", ';', "semicolon"),
+    ('\u{FF1C}', "FULLWIDTH LESS-THAN SIGN", '<', "less-than sign"),
+    ('\u{FF1D}', "FULLWIDTH EQUALS SIGN", '=', "equals sign"),
+    ('\u{FF1E}', "FULLWIDTH GREATER-THAN SIGN", '>', "greater-than sign"),
+    ('\u{FF1F}', "FULLWIDTH QUESTION MARK", '?', "question mark"),
+    ('\u{FF20}', "FULLWIDTH COMMERCIAL AT", '@', "at sign"),
+    ('\u{FF3B}', "FULLWIDTH LEFT SQUARE BRACKET", '[', "left square bracket"),
+    ('\u{FF3C}', "FULLWIDTH REVERSE SOLIDUS", '\\', "backslash"),
+    ('\u{FF3D}', "FULLWIDTH RIGHT SQUARE BRACKET", ']', "right square bracket"),
+    ('\u{FF3E}', "FULLWIDTH CIRCUMFLEX ACCENT", '^', "caret"),
+    ('\u{FF5B}', "FULLWIDTH LEFT CURLY BRACKET", '{', "left curly bracket"),
+    ('\u{FF5C}', "FULLWIDTH VERTICAL LINE", '|', "vertical bar"),
+    ('\u{FF5D}', "FULLWIDTH RIGHT CURLY BRACKET", '}', "right curly bracket"),
+    ('\u{FF5E}', "FULLWIDTH TILDE", '~', "tilde"),
+];
+
+/// Returns the ASCII symbol the given character looks like, if any.
+pub fn confusable(c: char) -> Option<Confusable> {
+    CONFUSABLES.iter().find(|(it, ..)| *it == c).map(
+        |&(_, name, ascii, ascii_name)| Confusable {
+            name,
+            ascii,
+            ascii_name,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confusables_are_unique_and_not_ascii() {
+        for (i, (c, _, ascii, _)) in CONFUSABLES.iter().enumerate() {
+            assert!(!c.is_ascii() && ascii.is_ascii(), "{c:?}");
+            assert!(
+                CONFUSABLES[i + 1..].iter().all(|(it, ..)| it != c),
+                "{c:?} appears twice"
+            );
+        }
+    }
+
+    #[test]
+    fn test_confusable() {
+        let greek = confusable('\u{037E}').unwrap();
+        assert_eq!(greek.ascii, ';');
+        assert_eq!(greek.name, "GREEK QUESTION MARK");
+        assert_eq!(confusable(';'), None);
+        assert_eq!(confusable('a'), None);
+    }
+}
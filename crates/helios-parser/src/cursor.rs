@@ -38,9 +38,8 @@ impl<'source> Cursor<'source> {
 
     /// Advance to the next character in the iterator.
     pub fn advance(&mut self) -> Option<char> {
-        self.chars.next().map(|next_char| {
+        self.chars.next().inspect(|next_char| {
             self.pos += next_char.len_utf8();
-            next_char
         })
     }
 
@@ -12,7 +12,7 @@ pub(crate) fn root<FileId>(p: &mut Parser<FileId>) -> CompletedMarker
 where
     FileId: Clone + Default,
 {
-    let m = p.start();
+    let m = p.start("root");
 
     while !p.is_at_end() {
         decl::decl(p);
@@ -16,7 +16,7 @@ where
     FileId: Clone + Default,
 {
     assert!(p.is_at(SyntaxKind::Kwd_Let));
-    let m = p.start();
+    let m = p.start("global_binding");
     p.bump();
 
     p.expect(SyntaxKind::Identifier, SyntaxKind::Dec_GlobalBinding);
@@ -65,7 +65,7 @@ where
         // Consume the operator token
         p.bump();
 
-        let m = lhs.precede(p);
+        let m = lhs.precede(p, "expr");
         let parsed_rhs = expr(p, right_bp).is_some();
         lhs = m.complete(p, SyntaxKind::Exp_Binary);
 
@@ -120,7 +120,7 @@ where
     use SyntaxKind::*;
     assert!(p.is_at(Lit_Integer) || p.is_at(Lit_Float));
 
-    let m = p.start();
+    let m = p.start("literal");
     p.bump();
     m.complete(p, Exp_Literal)
 }
@@ -132,7 +132,7 @@ where
 {
     assert!(p.is_at(SyntaxKind::Identifier));
 
-    let m = p.start();
+    let m = p.start("variable_ref");
     p.bump();
     m.complete(p, SyntaxKind::Exp_VariableRef)
 }
@@ -142,7 +142,7 @@ fn unary_prefix_expr<FileId>(p: &mut Parser<FileId>) -> CompletedMarker
 where
     FileId: Clone + Default,
 {
-    let m = p.start();
+    let m = p.start("unary_prefix_expr");
 
     // Get the right binding power of the operator
     let operator = SyntaxKind::Sym_Minus;
@@ -162,7 +162,7 @@ where
 {
     assert!(p.is_at(SyntaxKind::Sym_LParen));
 
-    let m = p.start();
+    let m = p.start("paren_expr");
 
     // Consume the opening parenthesis and the expression inside
     p.bump();
@@ -181,7 +181,7 @@ where
 {
     assert!(p.is_at(SyntaxKind::Indent));
 
-    let m = p.start();
+    let m = p.start("indented_expr");
 
     // Consume the indent and the expression inside
     p.bump();
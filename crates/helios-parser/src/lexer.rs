@@ -19,6 +19,7 @@ use helios_syntax::{self, SyntaxKind};
 use std::ops::Range;
 use unicode_xid::UnicodeXID;
 
+use crate::confusables;
 use crate::cursor::Cursor;
 use crate::message::{LexerMessage, Message};
 
@@ -27,10 +28,7 @@ use crate::message::{LexerMessage, Message};
 /// satisfies the `XID_Start` property.
 fn is_identifier_start(c: char) -> bool {
     // Fast-path for ASCII characters
-    c == '_'
-        || ('a'..='z').contains(&c)
-        || ('A'..='Z').contains(&c)
-        || c.is_xid_start()
+    c == '_' || c.is_ascii_alphabetic() || c.is_xid_start()
 }
 
 /// Determines whether or not the given character is a valid continuation of an
@@ -38,11 +36,7 @@ fn is_identifier_start(c: char) -> bool {
 /// that satisfies the `XID_Continue` property.
 fn is_identifier_continue(c: char) -> bool {
     // Fast-path for ASCII characters
-    c == '_'
-        || ('a'..='z').contains(&c)
-        || ('A'..='Z').contains(&c)
-        || ('0'..='9').contains(&c)
-        || c.is_xid_continue()
+    c == '_' || c.is_ascii_alphanumeric() || c.is_xid_continue()
 }
 
 /// Determines whether or not the given character is a recognised symbol.
@@ -58,7 +52,7 @@ fn is_symbol(c: char) -> bool {
 
 /// Determines whether or not the given character is a digit.
 fn is_digit(c: char) -> bool {
-    matches!(c, '0'..='9')
+    c.is_ascii_digit()
 }
 
 /// Checks whether or not the given character is a whitespace delimiter.
@@ -129,13 +123,39 @@ where
 
     /// Returns a [`SyntaxKind::UnknownChar`] with an error message detailing
     /// the provided unknown character and its location in the file.
+    ///
+    /// If the character looks like an ASCII symbol (see [`confusables`]), the
+    /// message names that symbol instead, and the character is tokenized as
+    /// the symbol (or whitespace) it was meant to be so that parsing can carry
+    /// on as if it was written correctly.
     fn unknown(&self, character: char, start: usize) -> LexerReturn<FileId> {
-        let message = Message::new(
-            LexerMessage::UnknownCharacter(character),
-            Location::new(self.file_id.clone(), start..(start + 1)),
+        let location = Location::new(
+            self.file_id.clone(),
+            start..(start + character.len_utf8()),
         );
 
-        (SyntaxKind::UnknownChar, Some(message))
+        match confusables::confusable(character) {
+            Some(confusable) => {
+                let kind = match confusable.ascii {
+                    c if is_symbol(c) => helios_syntax::symbol_from_char(c),
+                    c if is_whitespace(c) => SyntaxKind::Whitespace,
+                    _ => SyntaxKind::UnknownChar,
+                };
+                let message = LexerMessage::ConfusableCharacter {
+                    character,
+                    confusable,
+                };
+
+                (kind, Some(Message::new(message, location)))
+            }
+            None => {
+                let message = LexerMessage::UnknownCharacter(character);
+                (
+                    SyntaxKind::UnknownChar,
+                    Some(Message::new(message, location)),
+                )
+            }
+        }
     }
 }
 
@@ -378,6 +398,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::message::MessageKind;
 
     fn check(input: &str, kind: SyntaxKind) {
         let mut lexer = Lexer::new(0u8, input);
@@ -609,4 +630,37 @@ mod tests {
         check("ମାନବ", SyntaxKind::Identifier); // Odia
         check("มนุษย์", SyntaxKind::Identifier); // Thai
     }
+
+    #[test]
+    fn test_lex_unknown_characters() {
+        // The message covers the whole character, not just its first byte
+        for input in ["`", "😀"] {
+            let mut lexer = Lexer::new(0u8, input);
+            let (token, message) = lexer.next().unwrap();
+            assert_eq!(token.kind, SyntaxKind::UnknownChar);
+            assert_eq!(message.unwrap().location.range, 0..input.len());
+        }
+    }
+
+    #[test]
+    fn test_lex_confusable_characters() {
+        let check_confusable = |input: &str, kind: SyntaxKind| {
+            let mut lexer = Lexer::new(0u8, input);
+            let (token, message) = lexer.next().unwrap();
+            assert_eq!(token.kind, kind);
+            assert_eq!(token.text, input);
+
+            let message = message.unwrap();
+            assert!(matches!(
+                message.kind,
+                MessageKind::Lexer(LexerMessage::ConfusableCharacter { .. })
+            ));
+            assert_eq!(message.location.range, 0..input.len());
+        };
+
+        check_confusable("\u{037E}", SyntaxKind::Sym_Semicolon);
+        check_confusable("\u{FF0B}", SyntaxKind::Sym_Plus);
+        check_confusable("\u{00A0}", SyntaxKind::Whitespace);
+        check_confusable("\u{201C}", SyntaxKind::UnknownChar);
+    }
 }
@@ -6,27 +6,34 @@
 //!
 //! [`rowan`]: https://docs.rs/rowan/0.15.3/rowan
 
+pub mod confusables;
 mod cursor;
 mod grammar;
 mod lexer;
 pub mod message;
 mod parser;
+mod reparse;
+pub mod tokens;
+pub mod trace;
 
-use helios_syntax::{SyntaxKind, SyntaxNode};
+use helios_syntax::SyntaxNode;
 use rowan::GreenNode;
-use std::cmp::Ordering;
 
-use crate::lexer::{Lexer, Token};
+use crate::lexer::Lexer;
 pub use crate::message::*;
 use crate::parser::sink::Sink;
 use crate::parser::source::Source;
 use crate::parser::Parser;
+pub use crate::reparse::TextEdit;
+use crate::tokens::Layout;
+pub use crate::tokens::Token;
+use crate::trace::Trace;
 
 /// Tokenizes the given source text.
 pub fn tokenize<FileId>(
     file_id: FileId,
     source: &str,
-) -> (Vec<Token>, Vec<Message<FileId>>)
+) -> (Vec<Token<'_>>, Vec<Message<FileId>>)
 where
     FileId: Clone + Default,
 {
@@ -53,129 +60,16 @@ where
 /// cannot use their outputs. This function is able to act as an intermediary by
 /// processing these indentations for you. It is ideal to call this function
 /// right after tokenizing.
+///
+/// To process indentation lazily, use a [`TokenStream`] with layout tokens
+/// enabled instead.
+///
+/// [`TokenStream`]: crate::tokens::TokenStream
 pub fn process_indents<'source>(
     source: &'source str,
     tokens: Vec<Token<'source>>,
 ) -> Vec<Token<'source>> {
-    // Our resulting vector will have at least the same size as the input vector
-    // (in the case that there is no indentation to be processed).
-    let mut processed_tokens = Vec::with_capacity(tokens.capacity());
-    let mut indent_stack = vec![0];
-
-    let mut i = 0;
-    while i < tokens.len() {
-        // TODO: assert!(indent_stack.is_sorted());
-        let curr_token = tokens[i].clone();
-
-        if curr_token.kind == SyntaxKind::Newline {
-            // Skip the newline character and count the number of spaces.
-            let curr_indent = curr_token.text[1..].len();
-            let last_indent = indent_stack.last().unwrap_or(&0);
-
-            match curr_indent.cmp(last_indent) {
-                // We haven't indented or dedented, so push the token as-is.
-                Ordering::Equal => {
-                    processed_tokens.push(curr_token);
-                    i += 1;
-                }
-                // We've indented, so we'll push an `Indent` token.
-                Ordering::Greater => {
-                    indent_stack.push(curr_indent);
-                    processed_tokens.push(Token {
-                        kind: SyntaxKind::Indent,
-                        ..curr_token
-                    });
-                    i += 1;
-                }
-                // We've dedented, so we'll push as many `Dedent` tokens
-                // necessary to get the new indentation level.
-                Ordering::Less => {
-                    'emit_dedents: loop {
-                        // We won't push a dedent token just yet because we need
-                        // to make sure the current indent is NOT greater than
-                        // the second-last indent (`new_last_indent`).
-                        let old_indent = indent_stack.pop().unwrap();
-                        let new_last_indent = indent_stack.last().unwrap_or(&0);
-
-                        match curr_indent.cmp(new_last_indent) {
-                            // We can emit a dedent token for the old indent and
-                            // continue this loop.
-                            Ordering::Less => {
-                                processed_tokens.push(Token {
-                                    kind: SyntaxKind::Dedent,
-                                    ..curr_token.clone()
-                                });
-                                continue 'emit_dedents;
-                            }
-                            // We can emit a dedent token for the old indent and
-                            // break out of this loop.
-                            Ordering::Equal => {
-                                processed_tokens.push(Token {
-                                    kind: SyntaxKind::Dedent,
-                                    ..curr_token.clone()
-                                });
-
-                                // We've finished dealing with the current
-                                // token, so we'll increment `i`.
-                                i += 1;
-                                break 'emit_dedents;
-                            }
-                            // The current indent is between the second-last
-                            // and the last indents, signifying an incorrect
-                            // dedent. Thus, we'll invalidate the whole line and
-                            // emit an error token instead.
-                            Ordering::Greater => {
-                                let start = curr_token.range.start;
-                                let mut end = curr_token.range.end;
-
-                                // Skip the current newline token.
-                                i += 1;
-
-                                // Skip until we find the next newline token.
-                                while i < tokens.len() {
-                                    if tokens[i].kind == SyntaxKind::Newline {
-                                        break;
-                                    }
-
-                                    end = tokens[i].range.end;
-                                    i += 1;
-                                }
-
-                                processed_tokens.push(Token {
-                                    kind: SyntaxKind::Error,
-                                    text: &source[start..end],
-                                    range: start..end,
-                                });
-
-                                // Put the old indent back as an indentation
-                                // error doesn't indicate a dedent.
-                                indent_stack.push(old_indent);
-                                break 'emit_dedents;
-                            }
-                        }
-                    }
-                }
-            }
-        } else {
-            // Push the token as-is.
-            processed_tokens.push(curr_token);
-            i += 1;
-        }
-    }
-
-    // Emit any remainder dedents required.
-    let end = processed_tokens.last().map(|t| t.range.end).unwrap_or(0);
-    while let Some(indent) = indent_stack.pop() {
-        // We won't emit a dedent token for the first column.
-        if indent == 0 {
-            break;
-        }
-
-        // Zero-width dedent token.
-        processed_tokens.push(Token::new(SyntaxKind::Dedent, "", end..end));
-    }
-
-    processed_tokens
+    Layout::new(source, tokens.into_iter()).collect()
 }
 
 /// The entry point of the parsing process.
@@ -184,6 +78,30 @@ pub fn process_indents<'source>(
 /// [`Parse`], which holds a [`GreenNode`] tree describing the structure of a
 /// Helios program.
 pub fn parse<FileId>(file_id: FileId, source: &str) -> Parse<FileId>
+where
+    FileId: Clone + Default,
+{
+    parse_inner(file_id, source, false).0
+}
+
+/// Parses the given source text like [`parse`], while recording every step
+/// taken by the parser in a [`Trace`].
+pub fn parse_with_trace<FileId>(
+    file_id: FileId,
+    source: &str,
+) -> (Parse<FileId>, Trace)
+where
+    FileId: Clone + Default,
+{
+    let (parse, trace) = parse_inner(file_id, source, true);
+    (parse, trace.unwrap_or_default())
+}
+
+fn parse_inner<FileId>(
+    file_id: FileId,
+    source: &str,
+    with_trace: bool,
+) -> (Parse<FileId>, Option<Trace>)
 where
     FileId: Clone + Default,
 {
@@ -191,17 +109,23 @@ where
     let tokens = process_indents(source, tokens);
     let source = Source::new(&tokens);
 
-    let parser = Parser::new(file_id, source);
-    let (events, parser_messages) = parser.parse();
+    let mut parser = Parser::new(file_id.clone(), source);
+    if with_trace {
+        parser = parser.with_trace();
+    }
+
+    let (events, parser_messages, trace) = parser.parse();
     let sink = Sink::new(&tokens, events);
 
     messages.extend(parser_messages);
-    sink.finish(messages)
+    (sink.finish(file_id, messages), trace)
 }
 
 /// The result of parsing a source text.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Parse<FileId> {
+    /// The file the syntax tree was parsed from.
+    file_id: FileId,
     /// The root green node of the syntax tree.
     green_node: GreenNode,
     messages: Vec<Message<FileId>>,
@@ -209,13 +133,22 @@ pub struct Parse<FileId> {
 
 impl<FileId> Parse<FileId> {
     /// Construct a [`Parse`] with the given [`GreenNode`].
-    pub fn new(green_node: GreenNode, messages: Vec<Message<FileId>>) -> Self {
+    pub fn new(
+        file_id: FileId,
+        green_node: GreenNode,
+        messages: Vec<Message<FileId>>,
+    ) -> Self {
         Self {
+            file_id,
             green_node,
             messages,
         }
     }
 
+    pub fn file_id(&self) -> &FileId {
+        &self.file_id
+    }
+
     pub fn syntax(&self) -> SyntaxNode {
         SyntaxNode::new_root(self.green_node.clone())
     }
@@ -240,6 +173,7 @@ fn check(input: &str, expected_tree: expect_test::Expect) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use helios_syntax::SyntaxKind;
 
     fn check(input: &str, expected_tokens: Vec<Token>) {
         let (tokens, _) = tokenize(0u8, input);
@@ -1,11 +1,14 @@
-use helios_diagnostics::{Diagnostic, Location};
-use helios_formatting::FormattedString;
+use crate::confusables::Confusable;
+use helios_diagnostics::{
+    codes, Applicability, Diagnostic, Edit, Location, Suggestion,
+};
+use helios_locale::{Arg, Locale};
 use helios_syntax::SyntaxKind;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Message<FileId> {
-    kind: MessageKind,
-    location: Location<FileId>,
+    pub(crate) kind: MessageKind,
+    pub(crate) location: Location<FileId>,
 }
 
 impl<FileId> Message<FileId>
@@ -22,10 +25,17 @@ where
         }
     }
 
+    /// Generates the diagnostic of the message in the current locale.
     pub fn generate_diagnostic(&self) -> Diagnostic<FileId> {
+        self.generate_diagnostic_in(Locale::current())
+    }
+
+    /// Generates the diagnostic of the message in the given locale.
+    pub fn generate_diagnostic_in(&self, locale: Locale) -> Diagnostic<FileId> {
+        let location = self.location.clone();
         match &self.kind {
-            MessageKind::Lexer(it) => it.diagnostic(self.location.clone()),
-            MessageKind::Parser(it) => it.diagnostic(self.location.clone()),
+            MessageKind::Lexer(it) => it.diagnostic(location, locale),
+            MessageKind::Parser(it) => it.diagnostic(location, locale),
         }
     }
 }
@@ -69,31 +79,99 @@ impl From<ParserMessage> for MessageKind {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum LexerMessage {
     UnknownCharacter(char),
-    InvalidIndentation { expected: usize, found: usize },
+    ConfusableCharacter {
+        character: char,
+        confusable: Confusable,
+    },
+    InvalidIndentation {
+        expected: usize,
+        found: usize,
+    },
 }
 
 impl LexerMessage {
     pub fn diagnostic<FileId>(
         &self,
         location: Location<FileId>,
+        locale: Locale,
     ) -> Diagnostic<FileId>
     where
-        FileId: Default,
+        FileId: Clone + Default,
     {
         match self {
             LexerMessage::UnknownCharacter(character) => {
-                let description = FormattedString::default()
-                    .text("I encountered a token I don't know how to handle:");
+                let description = locale.formatted("e0003-description", &[]);
+
+                let message = locale.formatted(
+                    "e0003-message",
+                    &[("character", format!("{character:?}").into())],
+                );
+
+                let suggestion = Suggestion::new(
+                    locale.text("e0003-suggestion", &[]),
+                    Applicability::MaybeIncorrect,
+                )
+                .edit(Edit::delete(location.clone()));
+
+                Diagnostic::error(locale.text("e0003-title", &[]))
+                    .code(codes::UNKNOWN_CHARACTER)
+                    .location(location)
+                    .description(description)
+                    .message(message)
+                    .suggestion(suggestion)
+            }
+            LexerMessage::ConfusableCharacter {
+                character,
+                confusable,
+            } => {
+                let description = locale.formatted("e0005-description", &[]);
+
+                let symbol_id = format!(
+                    "ascii-{}",
+                    confusable.ascii_name.replace(' ', "-")
+                );
+                let symbol_name = locale.text(&symbol_id, &[]);
+                let article = locale.article(&symbol_id);
+                let symbol_phrase = locale.text(
+                    "syntax-phrase",
+                    &[
+                        ("article", article.into()),
+                        ("kind", symbol_name.clone().into()),
+                    ],
+                );
+
+                let message = locale.formatted(
+                    "e0005-message",
+                    &[
+                        ("character", format!("{character:?}").into()),
+                        (
+                            "codepoint",
+                            format!("{:04X}", *character as u32).into(),
+                        ),
+                        ("name", confusable.name.into()),
+                        ("symbol", format!("{:?}", confusable.ascii).into()),
+                        ("symbol-name", symbol_name.into()),
+                    ],
+                );
 
-                let message = FormattedString::default()
-                    .text("The character ")
-                    .code(format!("{:?}", character))
-                    .text(" is not a valid token. Did you mean to write it?");
+                let suggestion = Suggestion::new(
+                    locale.text(
+                        "e0005-suggestion",
+                        &[("symbol-phrase", symbol_phrase.into())],
+                    ),
+                    Applicability::MachineApplicable,
+                )
+                .edit(Edit::new(
+                    location.clone(),
+                    confusable.ascii.to_string(),
+                ));
 
-                Diagnostic::error("Unknown character")
+                Diagnostic::error(locale.text("e0005-title", &[]))
+                    .code(codes::CONFUSABLE_CHARACTER)
                     .location(location)
                     .description(description)
                     .message(message)
+                    .suggestion(suggestion)
             }
             LexerMessage::InvalidIndentation { .. } => {
                 todo!()
@@ -106,6 +184,8 @@ impl LexerMessage {
 pub enum ParserMessage {
     MissingKind {
         context: Option<SyntaxKind>,
+        /// The kind found instead, or `None` at the end of the file.
+        given: Option<SyntaxKind>,
         expected: SyntaxKind,
     },
     UnexpectedKind {
@@ -119,108 +199,285 @@ impl ParserMessage {
     pub fn diagnostic<FileId>(
         &self,
         location: Location<FileId>,
+        locale: Locale,
     ) -> Diagnostic<FileId>
     where
-        FileId: Default,
+        FileId: Clone + Default,
     {
+        // What the parser was partway through (e.g. "a binary expression")
+        let context_arg = |context: &Option<SyntaxKind>| -> Arg {
+            match context {
+                Some(context) => context.human_readable_repr_in(locale).into(),
+                None => locale.formatted("context-unknown", &[]).into(),
+            }
+        };
+
         match self {
-            ParserMessage::MissingKind { context, expected } => {
-                let error = format!(
-                    "Missing {}{}",
-                    expected.description().map(|s| s + " ").unwrap_or_default(),
-                    expected.kind()
+            ParserMessage::MissingKind {
+                context,
+                given,
+                expected,
+            } => {
+                let error = locale.text(
+                    "e0001-title",
+                    &[("kind", expected.name_in(locale).into())],
                 );
 
-                let description = FormattedString::default().text(format!(
-                    "I was partway through {} when I got stuck here:",
-                    context.map_or("something".to_string(), |context| {
-                        context.to_string()
-                    })
-                ));
+                let description = locale.formatted(
+                    "e0001-description",
+                    &[("context", context_arg(context))],
+                );
+
+                let message = locale.formatted(
+                    "e0001-message",
+                    &[(
+                        "expected",
+                        expected.human_readable_repr_in(locale).into(),
+                    )],
+                );
 
-                let message = FormattedString::default()
-                    .text(format!("I expected {} here.", expected));
+                let suggestion = closing_paren_suggestion(
+                    *context,
+                    *given,
+                    &[*expected],
+                    &location,
+                    locale,
+                );
 
-                Diagnostic::error(error)
+                let diagnostic = Diagnostic::error(error)
+                    .code(codes::MISSING_KIND)
                     .location(location)
                     .description(description)
-                    .message(message)
+                    .message(message);
+
+                match suggestion {
+                    Some(suggestion) => diagnostic.suggestion(suggestion),
+                    None => diagnostic,
+                }
             }
             ParserMessage::UnexpectedKind {
                 context,
                 given,
                 expected,
             } => {
-                let title = format!(
-                    "Unexpected {}",
-                    given.map_or("end of file".to_string(), |given| {
-                        given.kind()
-                    })
-                );
+                let title = match given {
+                    Some(given) => locale.text(
+                        "e0002-title",
+                        &[("kind", given.category_in(locale).into())],
+                    ),
+                    None => locale.text("e0002-title-end-of-file", &[]),
+                };
 
-                let description = FormattedString::default().text(format!(
-                    "I was partway through {} when I got stuck here:",
-                    context.map_or("something".to_string(), |context| {
-                        context.to_string()
-                    })
-                ));
+                let description = locale.formatted(
+                    "e0002-description",
+                    &[("context", context_arg(context))],
+                );
 
-                let (message, hint) = {
+                let (message, hint, suggestion) = {
                     if expected.len() == 1 {
                         let expected = expected[0];
 
-                        let message = FormattedString::default()
-                            .text(format!("I expected {} here.", expected));
+                        let message = locale.formatted(
+                            "e0002-message",
+                            &[(
+                                "expected",
+                                expected.human_readable_repr_in(locale).into(),
+                            )],
+                        );
 
-                        let hint = match (expected, given) {
+                        let (hint, suggestion) = match (expected, given) {
                             (SyntaxKind::Identifier, Some(kind))
                                 if kind.is_keyword() =>
                             {
-                                let description = kind.description().expect(
-                                    "keywords should have descriptions",
+                                let keyword = kind
+                                    .keyword()
+                                    .expect("keywords should have their text");
+
+                                let hint = locale.formatted(
+                                    "e0002-keyword-hint",
+                                    &[("keyword", keyword.into())],
                                 );
 
-                                Some(format!(
-                                    "It looks like you're trying to use the \
-                                     reserved keyword {} as an identifier! Try \
-                                     using a different name instead.",
-                                    FormattedString::default()
-                                        .code(description)
-                                ))
+                                // Appending an underscore keeps the name
+                                // recognizable, but other uses of it won't be
+                                // renamed along with it
+                                let name = format!("{keyword}_");
+                                let suggestion = Suggestion::new(
+                                    locale.text(
+                                        "e0002-keyword-suggestion",
+                                        &[("name", name.clone().into())],
+                                    ),
+                                    Applicability::MaybeIncorrect,
+                                )
+                                .edit(Edit::new(location.clone(), name));
+
+                                (Some(hint), Some(suggestion))
                             }
-                            _ => None,
+                            _ => (None, None),
                         };
 
-                        (message, hint)
+                        (message, hint, suggestion)
                     } else {
-                        let message = FormattedString::default()
-                            .text("I expected one of the following here:")
-                            .list(
+                        let message =
+                            locale.formatted("e0002-message-one-of", &[]).list(
                                 expected
                                     .iter()
                                     .map(|kind| {
-                                        kind.human_readable_repr().into()
+                                        kind.human_readable_repr_in(locale)
+                                            .into()
                                     })
                                     .collect::<Vec<_>>(),
                             );
 
-                        (message, None)
+                        let suggestion = closing_paren_suggestion(
+                            *context, *given, expected, &location, locale,
+                        );
+
+                        (message, None, suggestion)
                     }
                 };
 
+                let mut diagnostic = Diagnostic::error(title)
+                    .code(codes::UNEXPECTED_KIND)
+                    .location(location)
+                    .description(description)
+                    .message(message);
+
                 if let Some(hint) = hint {
-                    Diagnostic::error(title)
-                        .location(location)
-                        .description(description)
-                        .message(message)
-                        .hint(hint)
-                } else {
-                    Diagnostic::error(title)
-                        .location(location)
-                        .description(description)
-                        .message(message)
+                    diagnostic = diagnostic.hint(hint);
+                }
+
+                if let Some(suggestion) = suggestion {
+                    diagnostic = diagnostic.suggestion(suggestion);
                 }
+
+                diagnostic
             }
         }
     }
 }
+
+/// Suggests closing a parenthesized expression that is still open at the end
+/// of the file.
+///
+/// Errors at the end of the file are reported right after the last token that
+/// isn't trivia, so inserting the parenthesis there can't change the meaning
+/// of anything else in the file.
+fn closing_paren_suggestion<FileId: Clone>(
+    context: Option<SyntaxKind>,
+    given: Option<SyntaxKind>,
+    expected: &[SyntaxKind],
+    location: &Location<FileId>,
+    locale: Locale,
+) -> Option<Suggestion<FileId>> {
+    let is_unclosed = context == Some(SyntaxKind::Exp_Paren)
+        && given.is_none()
+        && expected.contains(&SyntaxKind::Sym_RParen);
+
+    is_unclosed.then(|| {
+        Suggestion::new(
+            locale.text("paren-suggestion", &[]),
+            Applicability::MachineApplicable,
+        )
+        .edit(Edit::insert(
+            location.file_id.clone(),
+            location.range.start,
+            ")",
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code_of(source: &str) -> Vec<Option<&'static str>> {
+        crate::parse(0u8, source)
+            .messages()
+            .iter()
+            .map(|message| message.generate_diagnostic_in(Locale::EnUs).code)
+            .collect()
+    }
+
+    #[test]
+    fn test_diagnostic_codes() {
+        assert_eq!(code_of("let")[0], Some(codes::MISSING_KIND));
+        assert_eq!(
+            code_of("¬"),
+            vec![Some(codes::UNKNOWN_CHARACTER), Some(codes::UNEXPECTED_KIND),]
+        );
+    }
+
+    fn suggestions_of(source: &str) -> Vec<Suggestion<u8>> {
+        crate::parse(0u8, source)
+            .messages()
+            .iter()
+            .flat_map(|message| {
+                message.generate_diagnostic_in(Locale::EnUs).suggestions
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_suggest_closing_paren_at_end_of_file() {
+        let suggestions = suggestions_of(
+            "(1 + 2 # comment
+",
+        );
+        assert_eq!(
+            suggestions,
+            vec![Suggestion::new(
+                "inserting the missing closing parenthesis",
+                Applicability::MachineApplicable,
+            )
+            .edit(Edit::insert(0, 6, ")"))]
+        );
+    }
+
+    #[test]
+    fn test_suggest_renaming_keyword() {
+        let suggestions = suggestions_of("let let = 1");
+        assert_eq!(suggestions[0].applicability, Applicability::MaybeIncorrect);
+        assert_eq!(
+            suggestions[0].edits,
+            vec![Edit::new(Location::new(0, 4..7), "let_")]
+        );
+    }
+
+    #[test]
+    fn test_suggest_replacing_confusable_character() {
+        let suggestions = suggestions_of("let a = 1 \u{FF0B} 2");
+        assert_eq!(
+            suggestions,
+            vec![Suggestion::new(
+                "replacing it with a plus sign",
+                Applicability::MachineApplicable,
+            )
+            .edit(Edit::new(Location::new(0, 10..13), "+"))]
+        );
+        assert_eq!(
+            code_of("let a = 1\u{037E}")[0],
+            Some(codes::CONFUSABLE_CHARACTER)
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_in_french() {
+        let diagnostics = crate::parse(0u8, "let a = 1 \u{FF0B} 2")
+            .messages()
+            .iter()
+            .map(|message| message.generate_diagnostic_in(Locale::Fr))
+            .collect::<Vec<_>>();
+
+        assert_eq!(diagnostics[0].title, "Caractère trompeur");
+        assert_eq!(
+            diagnostics[0].message.render_plain(),
+            "Le caractère `'＋'` (U+FF0B, FULLWIDTH PLUS SIGN) ressemble à \
+             `'+'` (signe plus), mais c'est un caractère différent."
+        );
+        assert_eq!(
+            diagnostics[0].suggestions[0].message,
+            "essayez de le remplacer par un signe plus"
+        );
+    }
+}
@@ -12,6 +12,7 @@ use self::event::Event;
 use self::marker::Marker;
 use self::source::Source;
 use crate::lexer::Token;
+use crate::trace::{Trace, TraceEvent, LOG_TARGET};
 use crate::{Message, ParserMessage};
 
 const RECOVERY_SET: [SyntaxKind; 1] = [SyntaxKind::Kwd_Let];
@@ -23,6 +24,11 @@ pub struct Parser<'source, 'tokens, FileId> {
     events: Vec<Event>,
     expected_kinds: Vec<SyntaxKind>,
     messages: Vec<Message<FileId>>,
+    trace: Option<Trace>,
+    log_trace: bool,
+    /// The grammar rules of the nodes that are started but not completed yet
+    /// (only kept when tracing).
+    rules: Vec<&'static str>,
 }
 
 impl<'source, 'tokens, FileId> Parser<'source, 'tokens, FileId>
@@ -38,9 +44,19 @@ where
             events: Vec::new(),
             expected_kinds: Vec::new(),
             messages: Vec::new(),
+            trace: None,
+            log_trace: log::log_enabled!(target: LOG_TARGET, log::Level::Trace),
+            rules: Vec::new(),
         }
     }
 
+    /// Records every step taken by the parser in a [`Trace`], which is
+    /// returned once parsing is done.
+    pub fn with_trace(mut self) -> Self {
+        self.trace = Some(Trace::default());
+        self
+    }
+
     /// Starts the parsing process.
     ///
     /// This function will attempt to build a concrete syntax tree with the
@@ -48,9 +64,29 @@ where
     /// return a [`Parse`] containing a root green node.
     ///
     /// [`Parse`]: crate::Parse
-    pub fn parse(mut self) -> (Vec<Event>, Vec<Message<FileId>>) {
+    pub fn parse(
+        mut self,
+    ) -> (Vec<Event>, Vec<Message<FileId>>, Option<Trace>) {
         crate::grammar::root(&mut self);
-        (self.events, self.messages)
+        (self.events, self.messages, self.trace)
+    }
+}
+
+impl<'source, 'tokens, FileId> Parser<'source, 'tokens, FileId> {
+    /// Determines if the steps taken by the parser should be traced.
+    pub(crate) fn is_tracing(&self) -> bool {
+        self.log_trace || self.trace.is_some()
+    }
+
+    /// Records the given step in the trace (if tracing is enabled).
+    pub(crate) fn record(&mut self, event: TraceEvent) {
+        if self.log_trace {
+            log::trace!(target: LOG_TARGET, "{}", event);
+        }
+
+        if let Some(trace) = &mut self.trace {
+            trace.push(event);
+        }
     }
 }
 
@@ -81,17 +117,38 @@ where
     /// Adds the next token to the syntax tree (via the [`GreenNodeBuilder`]).
     pub(crate) fn bump(&mut self) {
         self.expected_kinds.clear();
-        self.source.next_token().unwrap();
-        self.events.push(Event::AddToken)
+        let token = self.source.next_token().unwrap();
+        self.events.push(Event::AddToken);
+
+        if self.is_tracing() {
+            self.record(TraceEvent::Token {
+                kind: token.kind,
+                text: token.text.to_string(),
+                range: token.range.clone(),
+            });
+        }
     }
 
-    /// Starts a new node, returning a [`Marker`].
-    pub(crate) fn start(&mut self) -> Marker {
+    /// Starts a new node, returning a [`Marker`]. The rule is the name of the
+    /// grammar function parsing the node, which is only used when tracing.
+    #[track_caller]
+    pub(crate) fn start(&mut self, rule: &'static str) -> Marker {
         let pos = self.events.len();
         self.events.push(Event::Placeholder);
+
+        if self.is_tracing() {
+            self.rules.push(rule);
+            self.record(TraceEvent::Start {
+                node: pos,
+                rule,
+                location: std::panic::Location::caller(),
+            });
+        }
+
         Marker::new(pos)
     }
 
+    #[track_caller]
     pub(crate) fn expect(
         &mut self,
         kind: SyntaxKind,
@@ -104,30 +161,52 @@ where
         }
     }
 
+    #[track_caller]
     pub(crate) fn error(&mut self, context: impl Into<Option<SyntaxKind>>) {
+        let context = context.into();
         let current_token = self.source.peek_token();
 
         let (given, range) =
             if let Some(Token { kind, range, .. }) = current_token {
                 (Some(*kind), range.clone())
             } else {
-                (None, self.source.last_token_range().unwrap())
+                (None, self.source.end_of_input_range())
             };
 
         let expected = std::mem::take(&mut self.expected_kinds);
         let expected_len = expected.len();
 
-        let message_kind = if expected_len == 1 {
-            ParserMessage::MissingKind {
-                context: context.into(),
-                expected: expected[0],
+        if self.is_tracing() {
+            self.record(TraceEvent::Error {
+                context,
+                expected: expected.clone(),
+                given,
+                range: range.clone(),
+                rule: self.rules.last().copied(),
+                location: std::panic::Location::caller(),
+            });
+        }
+
+        // A keyword where a name is expected is reported as unexpected rather
+        // than missing, so that the diagnostic can point out the keyword
+        let message_kind = match (expected.as_slice(), given) {
+            ([SyntaxKind::Identifier], Some(given)) if given.is_keyword() => {
+                ParserMessage::UnexpectedKind {
+                    context,
+                    given: Some(given),
+                    expected,
+                }
             }
-        } else {
-            ParserMessage::UnexpectedKind {
-                context: context.into(),
+            ([expected], _) => ParserMessage::MissingKind {
+                context,
+                given,
+                expected: *expected,
+            },
+            _ => ParserMessage::UnexpectedKind {
+                context,
                 given,
                 expected,
-            }
+            },
         };
 
         self.messages.push(Message::new(
@@ -139,14 +218,14 @@ where
             && !self.is_at_set(&RECOVERY_SET)
             && !self.is_at_end()
         {
-            let m = self.start();
+            let m = self.start("error");
             self.bump();
             m.complete(self, SyntaxKind::Error);
         }
     }
 
     fn is_at_set(&mut self, set: &[SyntaxKind]) -> bool {
-        self.peek().map_or(false, |kind| set.contains(&kind))
+        self.peek().is_some_and(|kind| set.contains(&kind))
     }
 
     pub(crate) fn is_at_end(&mut self) -> bool {
@@ -3,6 +3,7 @@ use helios_syntax::SyntaxKind;
 
 use super::Event;
 use super::Parser;
+use crate::trace::TraceEvent;
 
 pub(crate) struct Marker {
     pos: usize,
@@ -17,6 +18,7 @@ impl Marker {
         }
     }
 
+    #[track_caller]
     pub(crate) fn complete<FileId>(
         mut self,
         parser: &mut Parser<FileId>,
@@ -34,6 +36,15 @@ impl Marker {
 
         parser.events.push(Event::FinishNode);
 
+        if parser.is_tracing() {
+            parser.rules.pop();
+            parser.record(TraceEvent::Complete {
+                node: self.pos,
+                kind,
+                location: std::panic::Location::caller(),
+            });
+        }
+
         CompletedMarker { pos: self.pos }
     }
 }
@@ -43,11 +54,16 @@ pub(crate) struct CompletedMarker {
 }
 
 impl CompletedMarker {
-    pub(crate) fn precede<FileId>(self, p: &mut Parser<FileId>) -> Marker
+    #[track_caller]
+    pub(crate) fn precede<FileId>(
+        self,
+        p: &mut Parser<FileId>,
+        rule: &'static str,
+    ) -> Marker
     where
         FileId: Clone + Default,
     {
-        let new_m = p.start();
+        let new_m = p.start(rule);
 
         if let Event::StartNode {
             ref mut forward_parent,
@@ -59,6 +75,15 @@ impl CompletedMarker {
             unreachable!();
         }
 
+        if p.is_tracing() {
+            p.record(TraceEvent::Precede {
+                node: self.pos,
+                parent: new_m.pos,
+                rule,
+                location: std::panic::Location::caller(),
+            });
+        }
+
         new_m
     }
 }
@@ -24,6 +24,7 @@ impl<'tokens, 'source> Sink<'tokens, 'source> {
 
     pub fn finish<FileId>(
         mut self,
+        file_id: FileId,
         messages: Vec<Message<FileId>>,
     ) -> Parse<FileId> {
         use std::mem;
@@ -70,7 +71,7 @@ impl<'tokens, 'source> Sink<'tokens, 'source> {
             self.eat_trivia();
         }
 
-        Parse::new(self.builder.finish(), messages)
+        Parse::new(file_id, self.builder.finish(), messages)
     }
 
     fn eat_trivia(&mut self) {
@@ -38,8 +38,19 @@ impl<'source, 'tokens> Source<'source, 'tokens> {
         Some(token)
     }
 
-    pub(crate) fn last_token_range(&self) -> Option<Range<usize>> {
-        self.tokens.last().map(|Token { range, .. }| range.clone())
+    /// The empty range right after the last token that isn't trivia or a
+    /// layout token (i.e. where a missing token at the end of the file would
+    /// be written), or the end of the source if there's no such token.
+    pub(crate) fn end_of_input_range(&self) -> Range<usize> {
+        let end = self
+            .tokens
+            .iter()
+            .rev()
+            .find(|Token { kind, .. }| !kind.is_trivia() && !kind.is_layout())
+            .or_else(|| self.tokens.last())
+            .map_or(0, |Token { range, .. }| range.end);
+
+        end..end
     }
 
     pub fn peek_kind(&mut self) -> Option<SyntaxKind> {
@@ -47,7 +58,7 @@ impl<'source, 'tokens> Source<'source, 'tokens> {
         self.peek_kind_raw()
     }
 
-    pub fn peek_token(&mut self) -> Option<&Token> {
+    pub fn peek_token(&mut self) -> Option<&Token<'source>> {
         self.eat_trivia();
         self.peek_token_raw()
     }
@@ -59,14 +70,14 @@ impl<'source, 'tokens> Source<'source, 'tokens> {
     }
 
     fn at_trivia(&self) -> bool {
-        self.peek_kind_raw().map_or(false, SyntaxKind::is_trivia)
+        self.peek_kind_raw().is_some_and(SyntaxKind::is_trivia)
     }
 
     fn peek_kind_raw(&self) -> Option<SyntaxKind> {
         self.peek_token_raw().map(|Token { kind, .. }| *kind)
     }
 
-    fn peek_token_raw(&self) -> Option<&Token> {
+    fn peek_token_raw(&self) -> Option<&Token<'source>> {
         self.tokens.get(self.cursor)
     }
 }
@@ -0,0 +1,561 @@
+//! Incremental reparsing of edited Helios source files.
+//!
+//! Editors tend to send small edits (such as typing a character or renaming an
+//! identifier), so it would be wasteful to lex and parse the whole file again
+//! after every keystroke. This module provides [`Parse::reparse`], which tries
+//! to reuse as much of the existing syntax tree as possible.
+//!
+//! There are two strategies attempted before falling back to a full reparse:
+//!
+//! 1. **Token reparsing**: if the edit lies inside a single token (such as an
+//!    identifier, literal, comment or whitespace), only that token is relexed.
+//!    If relexing produces exactly one token of the same kind, the token is
+//!    swapped in place and the rest of the tree is left untouched.
+//!
+//! 2. **Block reparsing**: if the edit lies inside the delimiters of a
+//!    parenthesized expression, only that expression is relexed and reparsed.
+//!    Because the parentheses are left intact, nothing outside of the block is
+//!    able to observe the change. Other nodes, such as declarations, aren't
+//!    delimited, so they are never reparsed on their own.
+//!
+//! Since Helios is indentation-sensitive, line feeds inside a block are turned
+//! into layout tokens depending on the lines before the block, and may change
+//! the layout of the lines after it. A block with line feeds is therefore only
+//! reparsed alone if the lines around it aren't indented.
+
+use helios_syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
+use rowan::{GreenToken, NodeOrToken, TextRange, TextSize};
+use std::ops::Range;
+
+use crate::lexer::Lexer;
+use crate::message::{Message, MessageKind};
+use crate::Parse;
+
+/// An edit to a source text: the text in `range` is replaced with `insert`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub insert: String,
+}
+
+impl TextEdit {
+    /// Constructs a new [`TextEdit`] that replaces the text in the given range
+    /// with the given text.
+    pub fn replace(range: Range<usize>, insert: impl Into<String>) -> Self {
+        Self {
+            range,
+            insert: insert.into(),
+        }
+    }
+
+    /// Constructs a new [`TextEdit`] that inserts the given text at `offset`.
+    pub fn insert(offset: usize, insert: impl Into<String>) -> Self {
+        Self::replace(offset..offset, insert)
+    }
+
+    /// Constructs a new [`TextEdit`] that deletes the text in the given range.
+    pub fn delete(range: Range<usize>) -> Self {
+        Self::replace(range, "")
+    }
+
+    /// Applies this edit to the given text, returning the edited text.
+    pub fn apply(&self, text: &str) -> String {
+        let mut edited = text.to_string();
+        edited.replace_range(self.range.clone(), &self.insert);
+        edited
+    }
+
+    /// The difference in length of the text after this edit is applied.
+    fn delta(&self) -> isize {
+        self.insert.len() as isize - self.range.len() as isize
+    }
+
+    /// Determines if this edit may change the indentation structure of the
+    /// text it is applied to.
+    fn touches_line_feed(&self, text: &str) -> bool {
+        self.insert.contains('\n') || text[self.range.clone()].contains('\n')
+    }
+}
+
+impl<FileId> Parse<FileId>
+where
+    FileId: Clone + Default,
+{
+    /// Applies the given [`TextEdit`] to the source text of this [`Parse`] and
+    /// returns the resulting [`Parse`].
+    ///
+    /// The returned value is always identical to what calling [`parse`] on the
+    /// edited source text would return, but this method will only relex and
+    /// reparse the smallest part of the tree affected by the edit when it is
+    /// safe to do so. Refer to the [module documentation](self) for more
+    /// information.
+    ///
+    /// This method panics if the range of the edit is out of bounds.
+    ///
+    /// [`parse`]: crate::parse
+    pub fn reparse(&self, edit: TextEdit) -> Parse<FileId> {
+        let text = self.syntax().text().to_string();
+        assert!(
+            edit.range.end <= text.len(),
+            "Edit range {:?} is out of bounds for a text of length {}",
+            edit.range,
+            text.len()
+        );
+
+        if !edit.touches_line_feed(&text) {
+            if let Some(parse) = self.reparse_token(&edit) {
+                return parse;
+            }
+        }
+
+        if let Some(parse) = self.reparse_block(&edit) {
+            return parse;
+        }
+
+        crate::parse(self.file_id.clone(), &edit.apply(&text))
+    }
+
+    /// Attempts to reparse the single token that contains the given edit.
+    fn reparse_token(&self, edit: &TextEdit) -> Option<Parse<FileId>> {
+        let token = self.covering_token(&edit.range)?;
+
+        if !matches!(
+            token.kind(),
+            SyntaxKind::Identifier
+                | SyntaxKind::Lit_Integer
+                | SyntaxKind::Lit_Float
+                | SyntaxKind::Comment
+                | SyntaxKind::DocComment
+                | SyntaxKind::Whitespace
+        ) {
+            return None;
+        }
+
+        let token_range = to_range(token.text_range());
+        let relative_edit = TextEdit::replace(
+            (edit.range.start - token_range.start)
+                ..(edit.range.end - token_range.start),
+            edit.insert.clone(),
+        );
+        let new_text = relative_edit.apply(token.text());
+
+        // The edited text must still be lexed as exactly one token of the same
+        // kind, otherwise the surrounding tokens (and thus the tree) may change.
+        let mut lexer = Lexer::new(self.file_id.clone(), &new_text);
+        match (lexer.next(), lexer.next()) {
+            (Some((new_token, None)), None)
+                if new_token.kind == token.kind() => {}
+            _ => return None,
+        }
+
+        let green_token = GreenToken::new(token.kind().into(), &new_text);
+        let green_node = token.replace_with(green_token);
+
+        // Token boundaries other than the edited token's end are unchanged, so
+        // every message can simply be moved around the edited token.
+        let messages = self
+            .messages
+            .iter()
+            .cloned()
+            .map(|message| shift_message(message, &token_range, edit.delta()))
+            .collect();
+
+        Some(Parse::new(self.file_id.clone(), green_node, messages))
+    }
+
+    /// Attempts to reparse the innermost parenthesized expression whose
+    /// delimiters surround the given edit.
+    ///
+    /// Only `Exp_Paren` nodes are reparsed on their own: declarations have no
+    /// delimiters, so an edit inside one may change where it ends (e.g. by
+    /// changing the indentation of its last line) and always falls back to a
+    /// full reparse.
+    fn reparse_block(&self, edit: &TextEdit) -> Option<Parse<FileId>> {
+        let (node, node_range) = self
+            .covering_node(&edit.range)
+            .ancestors()
+            .filter(|node| node.kind() == SyntaxKind::Exp_Paren)
+            .find_map(|node| {
+                let inner_range = paren_inner_range(&node)?;
+                let contains_edit = inner_range.start <= edit.range.start
+                    && edit.range.end <= inner_range.end;
+                contains_edit.then(|| {
+                    let range = to_range(node.text_range());
+                    (node, range)
+                })
+            })?;
+
+        let relative_edit = TextEdit::replace(
+            (edit.range.start - node_range.start)
+                ..(edit.range.end - node_range.start),
+            edit.insert.clone(),
+        );
+        let new_text = relative_edit.apply(&node.text().to_string());
+
+        // Parsing the block on its own must produce exactly one node of the
+        // same kind that still ends with its closing parenthesis. Anything
+        // else means the edit has leaked out of the block.
+        let block_parse = crate::parse(self.file_id.clone(), &new_text);
+        let block_root = block_parse.syntax();
+        let mut children = block_root.children_with_tokens();
+        let new_node = match (children.next(), children.next()) {
+            (Some(NodeOrToken::Node(new_node)), None)
+                if new_node.kind() == SyntaxKind::Exp_Paren
+                    && paren_inner_range(&new_node).is_some() =>
+            {
+                new_node
+            }
+            _ => return None,
+        };
+
+        // Parsing the block on its own starts at the first column, so its line
+        // feeds only give the same layout tokens if the block is on a line
+        // that isn't indented. The lines after the block keep their layout if
+        // its last line isn't indented either, before and after the edit.
+        let has_line_feed = |node: &SyntaxNode| node.text().contains_char('\n');
+        if (has_line_feed(&node) || has_line_feed(&new_node))
+            && !(starts_unindented(&node)
+                && ends_unindented(&node)
+                && ends_unindented(&new_node))
+        {
+            return None;
+        }
+
+        let green_node = node.replace_with(new_node.green().into_owned());
+        let messages = splice_messages(
+            &self.messages,
+            block_parse.messages,
+            &node_range,
+            edit.delta(),
+        );
+
+        Some(Parse::new(self.file_id.clone(), green_node, messages))
+    }
+
+    /// Returns the token whose range contains the given range, if any.
+    fn covering_token(&self, range: &Range<usize>) -> Option<SyntaxToken> {
+        match self.syntax().covering_element(to_text_range(range)) {
+            NodeOrToken::Token(token) => {
+                let token_range = to_range(token.text_range());
+                let contains_range = token_range.start <= range.start
+                    && range.end <= token_range.end;
+                contains_range.then_some(token)
+            }
+            NodeOrToken::Node(_) => None,
+        }
+    }
+
+    /// Returns the innermost node whose range contains the given range.
+    fn covering_node(&self, range: &Range<usize>) -> SyntaxNode {
+        match self.syntax().covering_element(to_text_range(range)) {
+            NodeOrToken::Token(token) => token.parent().unwrap(),
+            NodeOrToken::Node(node) => node,
+        }
+    }
+}
+
+/// Returns the range between the opening and closing parentheses of the given
+/// `Exp_Paren` node, if the node has both of them.
+fn paren_inner_range(node: &SyntaxNode) -> Option<Range<usize>> {
+    let first = node.first_child_or_token()?.into_token()?;
+
+    // The closing parenthesis must be the last non-trivia element of the node.
+    let last = node
+        .children_with_tokens()
+        .filter(|element| !element.kind().is_trivia())
+        .last()?
+        .into_token()?;
+
+    if first.kind() != SyntaxKind::Sym_LParen
+        || last.kind() != SyntaxKind::Sym_RParen
+    {
+        return None;
+    }
+
+    let start = to_range(first.text_range()).end;
+    let end = to_range(last.text_range()).start;
+    Some(start..end)
+}
+
+/// Determines if the line the given node starts on isn't indented, i.e. if
+/// the last line feed before the node isn't followed by any space.
+fn starts_unindented(node: &SyntaxNode) -> bool {
+    let first = match node.first_token() {
+        Some(token) => token,
+        None => return false,
+    };
+
+    std::iter::successors(first.prev_token(), SyntaxToken::prev_token)
+        .find(|token| token.text().contains('\n'))
+        .is_none_or(|token| token.text() == "\n")
+}
+
+/// Determines if the last line of the given node isn't indented, i.e. if the
+/// last line feed in the node isn't followed by any space.
+fn ends_unindented(node: &SyntaxNode) -> bool {
+    node.descendants_with_tokens()
+        .filter_map(NodeOrToken::into_token)
+        .filter(|token| token.text().contains('\n'))
+        .last()
+        .is_none_or(|token| token.text() == "\n")
+}
+
+/// Moves a single offset past an edited range that has grown or shrunk by
+/// `delta` bytes.
+fn shift_offset(offset: usize, edited: &Range<usize>, delta: isize) -> usize {
+    if offset >= edited.end && offset > edited.start {
+        (offset as isize + delta) as usize
+    } else {
+        offset
+    }
+}
+
+/// Moves the location of the given message past an edited range that has
+/// grown or shrunk by `delta` bytes.
+fn shift_message<FileId>(
+    mut message: Message<FileId>,
+    edited: &Range<usize>,
+    delta: isize,
+) -> Message<FileId> {
+    let range = &mut message.location.range;
+    *range = shift_offset(range.start, edited, delta)
+        ..shift_offset(range.end, edited, delta);
+    message
+}
+
+/// Replaces the messages located inside the reparsed range with the messages
+/// from reparsing it, keeping lexer messages ahead of parser messages in the
+/// same way [`parse`](crate::parse) does.
+///
+/// Messages that start exactly at the reparsed range were reported before the
+/// reparsed node was entered (i.e. while peeking its first token), so they are
+/// kept as-is.
+fn splice_messages<FileId: Clone>(
+    old_messages: &[Message<FileId>],
+    new_messages: Vec<Message<FileId>>,
+    reparsed: &Range<usize>,
+    delta: isize,
+) -> Vec<Message<FileId>> {
+    let is_lexer = |message: &Message<FileId>| {
+        matches!(message.kind, MessageKind::Lexer(_))
+    };
+
+    let new_messages = new_messages
+        .into_iter()
+        .map(|mut message| {
+            let range = &mut message.location.range;
+            *range =
+                (range.start + reparsed.start)..(range.end + reparsed.start);
+            message
+        })
+        .collect::<Vec<_>>();
+
+    let mut messages = Vec::with_capacity(old_messages.len());
+
+    for lexer_messages in [true, false] {
+        let is_before = |message: &Message<FileId>| {
+            let range = &message.location.range;
+            range.end <= reparsed.start || range.start == reparsed.start
+        };
+
+        let (before, after): (Vec<_>, Vec<_>) = old_messages
+            .iter()
+            .filter(|message| is_lexer(message) == lexer_messages)
+            .filter(|message| {
+                is_before(message)
+                    || message.location.range.start >= reparsed.end
+            })
+            .cloned()
+            .partition(is_before);
+
+        messages.extend(before);
+        messages.extend(
+            new_messages
+                .iter()
+                .filter(|message| is_lexer(message) == lexer_messages)
+                .cloned(),
+        );
+        messages.extend(
+            after
+                .into_iter()
+                .map(|message| shift_message(message, reparsed, delta)),
+        );
+    }
+
+    messages
+}
+
+fn to_range(range: TextRange) -> Range<usize> {
+    range.start().into()..range.end().into()
+}
+
+fn to_text_range(range: &Range<usize>) -> TextRange {
+    TextRange::new(
+        TextSize::from(range.start as u32),
+        TextSize::from(range.end as u32),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that incrementally reparsing `before` with the given edit gives
+    /// the exact same result as parsing the edited text from scratch.
+    fn check(before: &str, edit: TextEdit, after: &str) {
+        assert_eq!(edit.apply(before), after);
+
+        let incremental = crate::parse(0u8, before).reparse(edit);
+        let full = crate::parse(0u8, after);
+
+        assert_eq!(incremental.debug_tree(), full.debug_tree());
+        assert_eq!(incremental.messages(), full.messages());
+    }
+
+    /// Like [`check`], but also checks that only a token or a block was
+    /// reparsed (instead of falling back to parsing the whole text).
+    fn check_incremental(before: &str, edit: TextEdit, after: &str) {
+        let parse = crate::parse(0u8, before);
+        let reparsed = parse
+            .reparse_token(&edit)
+            .or_else(|| parse.reparse_block(&edit));
+        assert!(reparsed.is_some(), "{edit:?} wasn't reparsed incrementally");
+
+        check(before, edit, after);
+    }
+
+    #[test]
+    fn test_reparse_identifier() {
+        check_incremental(
+            "let foo = bar",
+            TextEdit::replace(4..7, "quux"),
+            "let quux = bar",
+        );
+        check_incremental(
+            "let foo = bar",
+            TextEdit::insert(13, "s"),
+            "let foo = bars",
+        );
+        check_incremental(
+            "let foo = bar",
+            TextEdit::delete(4..5),
+            "let oo = bar",
+        );
+    }
+
+    #[test]
+    fn test_reparse_identifier_into_keyword() {
+        check(
+            "let foo = bar",
+            TextEdit::replace(10..13, "in"),
+            "let foo = in",
+        );
+    }
+
+    #[test]
+    fn test_reparse_literal() {
+        check_incremental("1 + 23", TextEdit::insert(5, "4"), "1 + 243");
+        check("1 + 23", TextEdit::insert(6, ".5"), "1 + 23.5");
+    }
+
+    #[test]
+    fn test_reparse_comment() {
+        check_incremental(
+            "a # hello\nb",
+            TextEdit::insert(9, "!"),
+            "a # hello!\nb",
+        );
+        check("a # hello\nb", TextEdit::insert(3, "#"), "a ## hello\nb");
+    }
+
+    #[test]
+    fn test_reparse_whitespace() {
+        check("1 + 2", TextEdit::insert(1, "  "), "1   + 2");
+        check("1 + 2", TextEdit::delete(3..4), "1 +2");
+    }
+
+    #[test]
+    fn test_reparse_paren_block() {
+        check_incremental(
+            "5*(2+1)",
+            TextEdit::replace(3..6, "10-4"),
+            "5*(10-4)",
+        );
+        check(
+            "5*(2+1) - 3",
+            TextEdit::insert(5, " * 7"),
+            "5*(2+ * 71) - 3",
+        );
+        check_incremental(
+            "(1)+((2))",
+            TextEdit::insert(7, "+3"),
+            "(1)+((2+3))",
+        );
+    }
+
+    #[test]
+    fn test_reparse_paren_block_with_errors() {
+        check(
+            "x * (1 +) + y",
+            TextEdit::insert(8, " 2"),
+            "x * (1 + 2) + y",
+        );
+        check("x * (1 + 2) + y", TextEdit::delete(8..10), "x * (1 +) + y");
+        check("x * (1 2) + y", TextEdit::delete(6..8), "x * (1) + y");
+    }
+
+    #[test]
+    fn test_reparse_messages_after_edit() {
+        check_incremental(
+            "let a = (1)\n$ + %",
+            TextEdit::insert(10, "0"),
+            "let a = (10)\n$ + %",
+        );
+        check(
+            "let a = (1)\n$ + %",
+            TextEdit::insert(4, "bc"),
+            "let bca = (1)\n$ + %",
+        );
+    }
+
+    #[test]
+    fn test_reparse_indentation_change() {
+        check("let a =\n  1", TextEdit::insert(8, "  "), "let a =\n    1");
+        check("let a = 1", TextEdit::insert(7, "\n "), "let a =\n  1");
+        check_incremental(
+            "let a =\n  (1)\nb",
+            TextEdit::insert(12, "+2"),
+            "let a =\n  (1+2)\nb",
+        );
+    }
+
+    #[test]
+    fn test_reparse_paren_block_with_line_feed() {
+        check_incremental(
+            "let a = (1 + 2)\nlet b = a\n",
+            TextEdit::replace(12..13, "\n"),
+            "let a = (1 +\n2)\nlet b = a\n",
+        );
+        check_incremental(
+            "let a = (1 +\n2)\nlet b = a\n",
+            TextEdit::delete(12..13),
+            "let a = (1 +2)\nlet b = a\n",
+        );
+        check(
+            "let a = (1 + 2)\nlet b = a\n",
+            TextEdit::replace(12..13, "\n  "),
+            "let a = (1 +\n  2)\nlet b = a\n",
+        );
+        check(
+            "let a =\n  (1 + 2)\nb",
+            TextEdit::replace(14..15, "\n"),
+            "let a =\n  (1 +\n2)\nb",
+        );
+    }
+
+    #[test]
+    fn test_reparse_unbalanced_parens() {
+        check("(1 + 2) * 3", TextEdit::delete(6..7), "(1 + 2 * 3");
+        check("(1 + 2) * 3", TextEdit::insert(4, ")"), "(1 +) 2) * 3");
+    }
+}
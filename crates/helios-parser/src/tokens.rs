@@ -0,0 +1,456 @@
+//! A public, streaming view over the tokens of a Helios source file.
+//!
+//! A [`TokenStream`] lazily lexes a source text for tools that don't need a
+//! whole syntax tree (such as syntax highlighters).
+
+use helios_syntax::SyntaxKind;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use text_size::{TextRange, TextSize};
+
+use crate::lexer::Lexer;
+pub use crate::lexer::Token;
+use crate::message::Message;
+
+/// The different kinds of trivia a [`Token`] may be.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Trivia {
+    Whitespace,
+    Newline,
+    Comment,
+    DocComment,
+}
+
+impl<'source> Token<'source> {
+    /// The range of this token in the source text as a [`TextRange`].
+    pub fn text_range(&self) -> TextRange {
+        TextRange::new(
+            TextSize::from(self.range.start as u32),
+            TextSize::from(self.range.end as u32),
+        )
+    }
+
+    /// Determines if this token is trivia (i.e. a token that is not
+    /// significant to the parser).
+    pub fn is_trivia(&self) -> bool {
+        self.kind.is_trivia()
+    }
+
+    /// Determines if this token is a layout token (i.e. `Indent` or `Dedent`).
+    pub fn is_layout(&self) -> bool {
+        self.kind.is_layout()
+    }
+
+    /// Classifies this token as the kind of trivia it is, if any.
+    pub fn trivia(&self) -> Option<Trivia> {
+        match self.kind {
+            SyntaxKind::Whitespace => Some(Trivia::Whitespace),
+            SyntaxKind::Newline => Some(Trivia::Newline),
+            SyntaxKind::Comment => Some(Trivia::Comment),
+            SyntaxKind::DocComment => Some(Trivia::DocComment),
+            _ => None,
+        }
+    }
+}
+
+/// A lazy iterator over the [`Token`]s of a Helios source text.
+///
+/// By default, it yields exactly the tokens produced by the lexer (trivia
+/// included).
+///
+/// # Examples
+///
+/// ```rust
+/// use helios_parser::tokens::TokenStream;
+/// use helios_syntax::SyntaxKind;
+///
+/// let kinds = TokenStream::new((), "let x =\n  1 # one")
+///     .with_layout(true)
+///     .with_trivia(false)
+///     .map(|token| token.kind)
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(
+///     kinds,
+///     vec![
+///         SyntaxKind::Kwd_Let,
+///         SyntaxKind::Identifier,
+///         SyntaxKind::Sym_Eq,
+///         SyntaxKind::Indent,
+///         SyntaxKind::Lit_Integer,
+///         SyntaxKind::Dedent,
+///     ]
+/// );
+/// ```
+pub struct TokenStream<'source, FileId> {
+    tokens: Layout<'source, Lexed<'source, FileId>>,
+    layout: bool,
+    trivia: bool,
+}
+
+impl<'source, FileId> TokenStream<'source, FileId>
+where
+    FileId: Clone + Default,
+{
+    /// Constructs a new [`TokenStream`] over the given source text.
+    ///
+    /// The stream will yield trivia tokens, but not layout tokens.
+    pub fn new(file_id: FileId, source: &'source str) -> Self {
+        let lexed = Lexed {
+            lexer: Lexer::new(file_id, source),
+            messages: Vec::new(),
+        };
+
+        Self {
+            tokens: Layout::new(source, lexed),
+            layout: false,
+            trivia: true,
+        }
+    }
+
+    /// Sets whether or not the stream should yield `Indent` and `Dedent`
+    /// tokens in place of line feeds that change the indentation level.
+    ///
+    /// This should be set before the stream is iterated over.
+    pub fn with_layout(mut self, layout: bool) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Sets whether or not the stream should yield trivia tokens (whitespace,
+    /// line feeds and comments).
+    pub fn with_trivia(mut self, trivia: bool) -> Self {
+        self.trivia = trivia;
+        self
+    }
+}
+
+impl<'source, FileId> TokenStream<'source, FileId> {
+    /// The messages reported by the lexer for the tokens yielded so far.
+    pub fn messages(&self) -> &[Message<FileId>] {
+        &self.tokens.inner.messages
+    }
+
+    /// Consumes the stream, returning the messages reported by the lexer for
+    /// the tokens yielded so far.
+    pub fn into_messages(self) -> Vec<Message<FileId>> {
+        self.tokens.inner.messages
+    }
+}
+
+impl<'source, FileId> Iterator for TokenStream<'source, FileId>
+where
+    FileId: Clone + Default,
+{
+    type Item = Token<'source>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let token = if self.layout {
+                self.tokens.next()?
+            } else {
+                self.tokens.next_raw()?
+            };
+
+            if self.trivia || !token.is_trivia() {
+                return Some(token);
+            }
+        }
+    }
+}
+
+/// The tokens of a [`Lexer`], with its messages set aside.
+struct Lexed<'source, FileId> {
+    lexer: Lexer<'source, FileId>,
+    messages: Vec<Message<FileId>>,
+}
+
+impl<'source, FileId> Iterator for Lexed<'source, FileId>
+where
+    FileId: Clone + Default,
+{
+    type Item = Token<'source>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (token, message) = self.lexer.next()?;
+        self.messages.extend(message);
+        Some(token)
+    }
+}
+
+/// An iterator adapter that processes the indentation of a stream of tokens by
+/// replacing `Newline` tokens with `Indent` and `Dedent` tokens where
+/// appropriate.
+///
+/// Since the [`Lexer`] only emits `Newline` tokens on every line feed it
+/// encounters, the parser needs this adapter to figure out where indented
+/// blocks start and end. Any line that is dedented to a level between two
+/// known indentation levels is invalidated and turned into a single `Error`
+/// token.
+pub(crate) struct Layout<'source, I> {
+    source: &'source str,
+    inner: I,
+    peeked: Option<Token<'source>>,
+    pending: VecDeque<Token<'source>>,
+    indent_stack: Vec<usize>,
+    last_end: usize,
+}
+
+impl<'source, I> Layout<'source, I>
+where
+    I: Iterator<Item = Token<'source>>,
+{
+    pub(crate) fn new(source: &'source str, inner: I) -> Self {
+        Self {
+            source,
+            inner,
+            peeked: None,
+            pending: VecDeque::new(),
+            indent_stack: vec![0],
+            last_end: 0,
+        }
+    }
+
+    /// Retrieves the next token without processing its indentation.
+    fn next_raw(&mut self) -> Option<Token<'source>> {
+        self.peeked.take().or_else(|| self.inner.next())
+    }
+
+    /// Peeks the next token without processing its indentation.
+    fn peek_raw(&mut self) -> Option<&Token<'source>> {
+        if self.peeked.is_none() {
+            self.peeked = self.inner.next();
+        }
+
+        self.peeked.as_ref()
+    }
+
+    /// Processes the indentation of the given `Newline` token, queueing the
+    /// resulting tokens in `pending`.
+    fn process_newline(&mut self, token: Token<'source>) {
+        // Skip the newline character and count the number of spaces.
+        let curr_indent = token.text[1..].len();
+        let last_indent = *self.indent_stack.last().unwrap_or(&0);
+
+        match curr_indent.cmp(&last_indent) {
+            // We haven't indented or dedented, so push the token as-is.
+            Ordering::Equal => self.pending.push_back(token),
+            // We've indented, so we'll push an `Indent` token.
+            Ordering::Greater => {
+                self.indent_stack.push(curr_indent);
+                self.pending.push_back(Token {
+                    kind: SyntaxKind::Indent,
+                    ..token
+                });
+            }
+            // We've dedented, so we'll push as many `Dedent` tokens necessary
+            // to get the new indentation level.
+            Ordering::Less => loop {
+                // We won't push a dedent token just yet because we need to
+                // make sure the current indent is NOT greater than the
+                // second-last indent (`new_last_indent`).
+                let old_indent = self.indent_stack.pop().unwrap();
+                let new_last_indent = *self.indent_stack.last().unwrap_or(&0);
+
+                match curr_indent.cmp(&new_last_indent) {
+                    // We can emit a dedent token for the old indent and
+                    // continue this loop.
+                    Ordering::Less => {
+                        self.pending.push_back(Token {
+                            kind: SyntaxKind::Dedent,
+                            ..token.clone()
+                        });
+                    }
+                    // We can emit a dedent token for the old indent and break
+                    // out of this loop.
+                    Ordering::Equal => {
+                        self.pending.push_back(Token {
+                            kind: SyntaxKind::Dedent,
+                            ..token
+                        });
+                        break;
+                    }
+                    // The current indent is between the second-last and the
+                    // last indents, signifying an incorrect dedent. Thus,
+                    // we'll invalidate the whole line and emit an error token
+                    // instead.
+                    Ordering::Greater => {
+                        let start = token.range.start;
+                        let mut end = token.range.end;
+
+                        // Skip until we find the next newline token.
+                        while let Some(next) = self.peek_raw() {
+                            if next.kind == SyntaxKind::Newline {
+                                break;
+                            }
+
+                            end = next.range.end;
+                            self.next_raw();
+                        }
+
+                        self.pending.push_back(Token {
+                            kind: SyntaxKind::Error,
+                            text: &self.source[start..end],
+                            range: start..end,
+                        });
+
+                        // Put the old indent back as an indentation error
+                        // doesn't indicate a dedent.
+                        self.indent_stack.push(old_indent);
+                        break;
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl<'source, I> Iterator for Layout<'source, I>
+where
+    I: Iterator<Item = Token<'source>>,
+{
+    type Item = Token<'source>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pending.is_empty() {
+            match self.next_raw() {
+                Some(token) if token.kind == SyntaxKind::Newline => {
+                    self.process_newline(token)
+                }
+                Some(token) => self.pending.push_back(token),
+                None => {
+                    // Emit any remainder dedents required, except for the
+                    // first column.
+                    return match self.indent_stack.pop() {
+                        Some(0) | None => None,
+                        Some(_) => Some(Token::new(
+                            SyntaxKind::Dedent,
+                            "",
+                            self.last_end..self.last_end,
+                        )),
+                    };
+                }
+            }
+        }
+
+        let token = self.pending.pop_front()?;
+        self.last_end = token.range.end;
+        Some(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(stream: TokenStream<u8>, expected: Vec<Token>) {
+        assert_eq!(stream.collect::<Vec<_>>(), expected);
+    }
+
+    const SOURCE: &str = "let x =\n  1 # one\ny";
+
+    #[test]
+    fn test_token_stream_default() {
+        check(
+            TokenStream::new(0, SOURCE),
+            vec![
+                Token::new(SyntaxKind::Kwd_Let, "let", 0..3),
+                Token::new(SyntaxKind::Whitespace, " ", 3..4),
+                Token::new(SyntaxKind::Identifier, "x", 4..5),
+                Token::new(SyntaxKind::Whitespace, " ", 5..6),
+                Token::new(SyntaxKind::Sym_Eq, "=", 6..7),
+                Token::new(SyntaxKind::Newline, "\n  ", 7..10),
+                Token::new(SyntaxKind::Lit_Integer, "1", 10..11),
+                Token::new(SyntaxKind::Whitespace, " ", 11..12),
+                Token::new(SyntaxKind::Comment, "# one", 12..17),
+                Token::new(SyntaxKind::Newline, "\n", 17..18),
+                Token::new(SyntaxKind::Identifier, "y", 18..19),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_token_stream_with_layout() {
+        check(
+            TokenStream::new(0, SOURCE).with_layout(true),
+            vec![
+                Token::new(SyntaxKind::Kwd_Let, "let", 0..3),
+                Token::new(SyntaxKind::Whitespace, " ", 3..4),
+                Token::new(SyntaxKind::Identifier, "x", 4..5),
+                Token::new(SyntaxKind::Whitespace, " ", 5..6),
+                Token::new(SyntaxKind::Sym_Eq, "=", 6..7),
+                Token::new(SyntaxKind::Indent, "\n  ", 7..10),
+                Token::new(SyntaxKind::Lit_Integer, "1", 10..11),
+                Token::new(SyntaxKind::Whitespace, " ", 11..12),
+                Token::new(SyntaxKind::Comment, "# one", 12..17),
+                Token::new(SyntaxKind::Dedent, "\n", 17..18),
+                Token::new(SyntaxKind::Identifier, "y", 18..19),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_token_stream_without_trivia() {
+        check(
+            TokenStream::new(0, SOURCE).with_trivia(false),
+            vec![
+                Token::new(SyntaxKind::Kwd_Let, "let", 0..3),
+                Token::new(SyntaxKind::Identifier, "x", 4..5),
+                Token::new(SyntaxKind::Sym_Eq, "=", 6..7),
+                Token::new(SyntaxKind::Lit_Integer, "1", 10..11),
+                Token::new(SyntaxKind::Identifier, "y", 18..19),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_token_stream_with_invalid_dedent() {
+        check(
+            TokenStream::new(0, "a\n    b\n  c d\n    e")
+                .with_layout(true)
+                .with_trivia(false),
+            vec![
+                Token::new(SyntaxKind::Identifier, "a", 0..1),
+                Token::new(SyntaxKind::Indent, "\n    ", 1..6),
+                Token::new(SyntaxKind::Identifier, "b", 6..7),
+                Token::new(SyntaxKind::Error, "\n  c d", 7..13),
+                Token::new(SyntaxKind::Identifier, "e", 18..19),
+                Token::new(SyntaxKind::Dedent, "", 19..19),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_token_stream_messages() {
+        let mut stream = TokenStream::new(0u8, "a $ ¬ b");
+        assert!(stream.messages().is_empty());
+
+        assert_eq!(stream.by_ref().count(), 7);
+        assert_eq!(stream.messages().len(), 1);
+    }
+
+    #[test]
+    fn test_token_trivia() {
+        let trivia = TokenStream::new(0u8, SOURCE)
+            .filter_map(|token| token.trivia())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            trivia,
+            vec![
+                Trivia::Whitespace,
+                Trivia::Whitespace,
+                Trivia::Newline,
+                Trivia::Whitespace,
+                Trivia::Comment,
+                Trivia::Newline,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_token_text_range() {
+        let token = Token::new(SyntaxKind::Identifier, "foo", 4..7);
+        assert_eq!(token.text_range(), TextRange::new(4.into(), 7.into()));
+    }
+}
@@ -0,0 +1,466 @@
+//! Tracing the decisions made by the parser.
+//!
+//! A [`Trace`] records the rules, tokens and errors of a parse (see
+//! [`parse_with_trace`]), and can be rendered as a log or as a Graphviz graph.
+//!
+//! [`parse_with_trace`]: crate::parse_with_trace
+
+use helios_syntax::SyntaxKind;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Write};
+use std::ops::Range;
+use std::panic::Location;
+
+/// The log target used when tracing is enabled through the `log` crate.
+pub const LOG_TARGET: &str = "helios_parser::trace";
+
+/// A single step taken by the parser.
+///
+/// Nodes are identified by the index of the event that starts them in the
+/// parser's list of events. Rules are the names of the grammar functions that
+/// took each step (e.g. `paren_expr`), and locations are where they took it in
+/// the source of the parser.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TraceEvent {
+    /// A new node was started (i.e. a `Marker` was created).
+    Start {
+        node: usize,
+        rule: &'static str,
+        location: &'static Location<'static>,
+    },
+    /// A started node was completed with the given kind.
+    Complete {
+        node: usize,
+        kind: SyntaxKind,
+        location: &'static Location<'static>,
+    },
+    /// A completed node was given a new parent that was started after it.
+    Precede {
+        node: usize,
+        parent: usize,
+        rule: &'static str,
+        location: &'static Location<'static>,
+    },
+    /// A token was added to the current node.
+    Token {
+        kind: SyntaxKind,
+        text: String,
+        range: Range<usize>,
+    },
+    /// An error was reported, in the rule of the innermost node started.
+    Error {
+        context: Option<SyntaxKind>,
+        expected: Vec<SyntaxKind>,
+        given: Option<SyntaxKind>,
+        range: Range<usize>,
+        rule: Option<&'static str>,
+        location: &'static Location<'static>,
+    },
+}
+
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Start {
+                node,
+                rule,
+                location,
+            } => write!(
+                f,
+                "start #{node} in {rule} ({})",
+                short_location(location)
+            ),
+            Self::Complete {
+                node,
+                kind,
+                location,
+            } => write!(
+                f,
+                "complete #{node} as {kind:?} ({})",
+                short_location(location)
+            ),
+            Self::Precede {
+                node,
+                parent,
+                rule,
+                location,
+            } => write!(
+                f,
+                "precede #{node} with #{parent} in {rule} ({})",
+                short_location(location)
+            ),
+            Self::Token { kind, text, range } => {
+                write!(f, "token {kind:?}@{range:?} {text:?}")
+            }
+            Self::Error {
+                context,
+                expected,
+                given,
+                range,
+                rule,
+                location,
+            } => {
+                write_error(f, expected, *given, *context, range, *rule)?;
+                write!(f, " ({})", short_location(location))
+            }
+        }
+    }
+}
+
+/// Writes an error event without its location.
+fn write_error(
+    f: &mut impl Write,
+    expected: &[SyntaxKind],
+    given: Option<SyntaxKind>,
+    context: Option<SyntaxKind>,
+    range: &Range<usize>,
+    rule: Option<&str>,
+) -> fmt::Result {
+    let expected = unique(expected);
+    write!(f, "error@{range:?} expected {expected:?}")?;
+    if let Some(given) = given {
+        write!(f, ", given {given:?}")?;
+    }
+    if let Some(context) = context {
+        write!(f, " in {context:?}")?;
+    }
+    if let Some(rule) = rule {
+        write!(f, ", reported by {rule}")?;
+    }
+    Ok(())
+}
+
+/// A record of every step taken by the parser while parsing a source text.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Trace {
+    events: Vec<TraceEvent>,
+}
+
+impl Trace {
+    pub(crate) fn push(&mut self, event: TraceEvent) {
+        self.events.push(event);
+    }
+
+    /// The recorded steps in the order they were taken.
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// The kind each node was completed with.
+    fn kinds(&self) -> HashMap<usize, SyntaxKind> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                TraceEvent::Complete { node, kind, .. } => Some((*node, *kind)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Renders the trace as a log, indented by the depth of the nodes that
+    /// were open at each step.
+    pub fn render_log(&self) -> String {
+        let kinds = self.kinds();
+        let kind_of = |node: &usize| {
+            kinds
+                .get(node)
+                .map_or("?".to_string(), |kind| format!("{kind:?}"))
+        };
+
+        let mut output = String::new();
+        let mut depth = 0;
+
+        for event in &self.events {
+            if let TraceEvent::Complete { .. } = event {
+                depth -= 1;
+            }
+
+            let indent = "  ".repeat(depth);
+            let _ = match event {
+                TraceEvent::Start { node, rule, .. } => writeln!(
+                    output,
+                    "{indent}start {} #{node} in {rule}",
+                    kind_of(node),
+                ),
+                TraceEvent::Complete { node, kind, .. } => {
+                    writeln!(output, "{indent}finish {kind:?} #{node}")
+                }
+                TraceEvent::Precede { node, parent, .. } => writeln!(
+                    output,
+                    "{indent}#{parent} is the forward parent of {} #{node}",
+                    kind_of(node),
+                ),
+                TraceEvent::Token { .. } => {
+                    writeln!(output, "{indent}{event}")
+                }
+                TraceEvent::Error {
+                    context,
+                    expected,
+                    given,
+                    range,
+                    rule,
+                    ..
+                } => {
+                    output.push_str(&indent);
+                    let _ = write_error(
+                        &mut output,
+                        expected,
+                        *given,
+                        *context,
+                        range,
+                        *rule,
+                    );
+                    writeln!(output)
+                }
+            };
+
+            if let TraceEvent::Start { .. } = event {
+                depth += 1;
+            }
+        }
+
+        output
+    }
+
+    /// Renders the trace as a Graphviz graph in the `dot` language.
+    ///
+    /// Each node of the syntax tree is drawn with the kind it was completed
+    /// with and the grammar rule that started it, along with the location of
+    /// that rule as a tooltip. Tokens
+    /// and errors are drawn as leaves, and forward-parent links are drawn with
+    /// dashed edges.
+    pub fn render_dot(&self) -> String {
+        let kinds = self.kinds();
+        let mut output = String::from("digraph parse {\n");
+        output.push_str("  node [fontname=\"monospace\"];\n");
+
+        let mut stack: Vec<usize> = Vec::new();
+        let mut edges: Vec<(String, String, bool)> = Vec::new();
+        let mut leaf_count = 0;
+
+        for event in &self.events {
+            let _ = match event {
+                TraceEvent::Start {
+                    node,
+                    rule,
+                    location,
+                } => {
+                    if let Some(parent) = stack.last() {
+                        edges.push((
+                            format!("n{parent}"),
+                            format!("n{node}"),
+                            false,
+                        ));
+                    }
+                    stack.push(*node);
+
+                    let kind = kinds
+                        .get(node)
+                        .map_or("?".to_string(), |kind| format!("{kind:?}"));
+                    writeln!(
+                        output,
+                        "  n{node} [label=\"{kind}\\n{rule}\", \
+                         tooltip=\"{}\"];",
+                        escape(&short_location(location))
+                    )
+                }
+                TraceEvent::Complete { .. } => {
+                    stack.pop();
+                    Ok(())
+                }
+                TraceEvent::Precede { node, parent, .. } => {
+                    // The preceded node is no longer a child of the node that
+                    // was open when it was completed.
+                    let child = format!("n{node}");
+                    edges.retain(|(_, to, _)| *to != child);
+                    edges.push((format!("n{parent}"), child, true));
+                    Ok(())
+                }
+                TraceEvent::Token { kind, text, .. } => {
+                    leaf_count += 1;
+                    let leaf = format!("t{leaf_count}");
+                    if let Some(parent) = stack.last() {
+                        edges.push((format!("n{parent}"), leaf.clone(), false));
+                    }
+                    writeln!(
+                        output,
+                        "  {leaf} [shape=box, label=\"{kind:?}\\n{}\"];",
+                        escape(&format!("{text:?}"))
+                    )
+                }
+                TraceEvent::Error {
+                    expected, given, ..
+                } => {
+                    leaf_count += 1;
+                    let leaf = format!("e{leaf_count}");
+                    if let Some(parent) = stack.last() {
+                        edges.push((format!("n{parent}"), leaf.clone(), false));
+                    }
+                    let given = given.map_or("nothing".to_string(), |kind| {
+                        format!("{kind:?}")
+                    });
+                    writeln!(
+                        output,
+                        "  {leaf} [shape=box, color=red, label=\"expected \
+                         {}\\ngiven {given}\"];",
+                        escape(&format!("{:?}", unique(expected)))
+                    )
+                }
+            };
+        }
+
+        for (from, to, is_forward_parent) in edges {
+            let style = if is_forward_parent {
+                " [style=dashed, label=\"forward parent\"]"
+            } else {
+                ""
+            };
+            let _ = writeln!(output, "  {from} -> {to}{style};");
+        }
+
+        output.push_str("}\n");
+        output
+    }
+}
+
+/// Shortens the path of a source location to the part after `src/`.
+fn short_location(location: &Location<'_>) -> String {
+    let file = location.file().replace('\\', "/");
+    let file = file.rsplit_once("src/").map_or(&*file, |(_, file)| file);
+    format!("{file}:{}", location.line())
+}
+
+/// The given kinds without the ones that were already expected before (the
+/// same kind can be checked more than once before an error is reported).
+fn unique(kinds: &[SyntaxKind]) -> Vec<SyntaxKind> {
+    let mut unique = Vec::with_capacity(kinds.len());
+    for kind in kinds {
+        if !unique.contains(kind) {
+            unique.push(*kind);
+        }
+    }
+    unique
+}
+
+/// Escapes a string to be used inside a quoted Graphviz label.
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+
+    fn check_log(source: &str, expected: expect_test::Expect) {
+        let (_, trace) = crate::parse_with_trace(0u8, source);
+        expected.assert_eq(&trace.render_log());
+    }
+
+    #[test]
+    fn test_trace_global_binding() {
+        check_log(
+            "let a = 1",
+            expect![[r#"
+                start Root #0 in root
+                  start Dec_GlobalBinding #1 in global_binding
+                    token Kwd_Let@0..3 "let"
+                    token Identifier@4..5 "a"
+                    token Sym_Eq@6..7 "="
+                    start Exp_Literal #5 in literal
+                      token Lit_Integer@8..9 "1"
+                    finish Exp_Literal #5
+                    error@9..9 expected [Sym_Asterisk, Sym_BangEq, Sym_Eq, Sym_ForwardSlash, Sym_Gt, Sym_GtEq, Sym_Lt, Sym_LtEq, Sym_LThinArrow, Sym_Minus, Sym_Plus, Sym_Semicolon, Newline] in Dec_GlobalBinding, reported by global_binding
+                  finish Dec_GlobalBinding #1
+                finish Root #0
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_trace_forward_parent() {
+        check_log(
+            "1 + 2",
+            expect![[r#"
+                start Root #0 in root
+                  start Exp_Literal #1 in literal
+                    token Lit_Integer@0..1 "1"
+                  finish Exp_Literal #1
+                  token Sym_Plus@2..3 "+"
+                  start Exp_Binary #5 in expr
+                    #5 is the forward parent of Exp_Literal #1
+                    start Exp_Literal #6 in literal
+                      token Lit_Integer@4..5 "2"
+                    finish Exp_Literal #6
+                  finish Exp_Binary #5
+                finish Root #0
+            "#]],
+        );
+    }
+
+    #[test]
+    fn test_trace_event_display() {
+        let (_, trace) = crate::parse_with_trace(0u8, "(");
+        let events = trace
+            .events()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        // Events logged one by one also tell where the rule is
+        assert!(
+            events[1].starts_with("start #1 in paren_expr (grammar/expr.rs:")
+        );
+        assert!(events.iter().any(|event| {
+            event.contains("in Exp_Paren, reported by paren_expr (grammar/")
+        }));
+    }
+
+    #[test]
+    fn test_trace_error_without_duplicates() {
+        use super::TraceEvent;
+        use helios_syntax::SyntaxKind;
+
+        let event = TraceEvent::Error {
+            context: None,
+            expected: vec![
+                SyntaxKind::Sym_Plus,
+                SyntaxKind::Newline,
+                SyntaxKind::Sym_Plus,
+            ],
+            given: Some(SyntaxKind::Sym_Minus),
+            range: 1..2,
+            rule: None,
+            location: std::panic::Location::caller(),
+        };
+        assert!(event.to_string().starts_with(
+            "error@1..2 expected [Sym_Plus, Newline], given Sym_Minus ("
+        ));
+    }
+
+    #[test]
+    fn test_trace_dot() {
+        let (_, trace) = crate::parse_with_trace(0u8, "-a");
+        let dot = trace.render_dot();
+
+        assert!(dot.starts_with("digraph parse {\n"));
+        assert!(dot.contains(
+            "n1 [label=\"Exp_UnaryPrefix\\nunary_prefix_expr\", \
+             tooltip=\"grammar/expr.rs:"
+        ));
+        assert!(
+            dot.contains("t1 [shape=box, label=\"Sym_Minus\\n\\\"-\\\"\"];")
+        );
+        assert!(dot.contains("n0 -> n1;"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_trace_dot_forward_parent() {
+        let (_, trace) = crate::parse_with_trace(0u8, "1 * 2");
+        let dot = trace.render_dot();
+
+        assert!(dot.contains("n5 -> n1 [style=dashed"));
+        assert!(!dot.contains("n0 -> n1;"));
+        assert!(dot.contains("n0 -> n5;"));
+    }
+}
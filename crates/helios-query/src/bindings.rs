@@ -0,0 +1,110 @@
+//! The bindings declared in a file.
+//!
+//! See the [`all_bindings`](Bindings::all_bindings) query.
+
+use crate::interner::{BindingData, BindingId};
+use crate::{FileId, Input};
+use helios_syntax::{SyntaxKind, SyntaxNode};
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Where a binding is declared.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum BindingKind {
+    /// A binding declared at the top level of a file (e.g. `let a = 1`).
+    Global,
+}
+
+/// A binding declared in a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Binding {
+    pub id: BindingId,
+    pub kind: BindingKind,
+    /// The range of the name of the binding (i.e. where it's defined).
+    pub name_range: Range<usize>,
+    /// The range of the whole declaration of the binding.
+    pub range: Range<usize>,
+}
+
+#[salsa::query_group(BindingDatabase)]
+pub trait Bindings: Input {
+    /// Every binding declared in a file, in the order they're declared.
+    ///
+    /// A name that is bound more than once has a different [`BindingId`]
+    /// each time.
+    fn all_bindings(&self, file_id: FileId) -> Arc<Vec<Binding>>;
+
+    /// The binding whose name is at the given offset, if any.
+    fn binding_at_offset(
+        &self,
+        file_id: FileId,
+        byte_offset: usize,
+    ) -> Option<Binding>;
+}
+
+fn all_bindings(db: &dyn Bindings, file_id: FileId) -> Arc<Vec<Binding>> {
+    let root = db.parse(file_id).syntax();
+    let mut occurrences = HashMap::new();
+
+    let bindings = root
+        .descendants()
+        .filter(|node| node.kind() == SyntaxKind::Dec_GlobalBinding)
+        // Declarations without a name are syntax errors, which the parser
+        // already reports
+        .filter_map(|node| {
+            binding(db, file_id, &node, BindingKind::Global, &mut occurrences)
+        })
+        .collect();
+
+    Arc::new(bindings)
+}
+
+fn binding_at_offset(
+    db: &dyn Bindings,
+    file_id: FileId,
+    byte_offset: usize,
+) -> Option<Binding> {
+    db.all_bindings(file_id)
+        .iter()
+        .find(|binding| {
+            binding.name_range.start <= byte_offset
+                && byte_offset <= binding.name_range.end
+        })
+        .cloned()
+}
+
+/// Interns the binding declared by the given node, if it has a name, counting
+/// it in the occurrences of its name.
+fn binding(
+    db: &dyn Bindings,
+    file_id: FileId,
+    node: &SyntaxNode,
+    kind: BindingKind,
+    occurrences: &mut HashMap<String, usize>,
+) -> Option<Binding> {
+    let name = node
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| token.kind() == SyntaxKind::Identifier)?;
+
+    let identifier = name.text().to_string();
+    let occurrence = occurrences.entry(identifier.clone()).or_default();
+    let id = db.intern_binding(BindingData {
+        file_id,
+        identifier,
+        occurrence: *occurrence,
+    });
+    *occurrence += 1;
+
+    Some(Binding {
+        id,
+        kind,
+        name_range: to_range(name.text_range()),
+        range: to_range(node.text_range()),
+    })
+}
+
+pub(crate) fn to_range(range: rowan::TextRange) -> Range<usize> {
+    range.start().into()..range.end().into()
+}
@@ -1,11 +1,53 @@
+//! Cancelling queries.
+//!
+//! Queries running on a snapshot check [`unwind_if_cancelled`] and unwind with
+//! [`Cancelled`] as soon as an input of the database is about to change.
+
 use std::error::Error;
 use std::fmt::{self, Display};
+use std::panic::{self, AssertUnwindSafe};
 
 pub type Cancelable<T> = Result<T, Cancelled>;
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Cancelled;
 
+impl Cancelled {
+    /// Unwinds the current thread with [`Cancelled`].
+    ///
+    /// [`std::panic::resume_unwind`] is used (rather than [`panic!`]), so the
+    /// panic hook isn't called and nothing is printed.
+    pub fn throw() -> ! {
+        panic::resume_unwind(Box::new(Cancelled))
+    }
+
+    /// Runs the given function, returning `Err(Cancelled)` if it unwound with
+    /// [`Cancelled`]. Any other panic is resumed.
+    ///
+    /// The function is assumed to be unwind safe, as salsa makes sure that
+    /// the database is left in a consistent state when a query unwinds.
+    pub fn catch<F, T>(f: F) -> Cancelable<T>
+    where
+        F: FnOnce() -> T,
+    {
+        match panic::catch_unwind(AssertUnwindSafe(f)) {
+            Ok(value) => Ok(value),
+            Err(payload) => match payload.downcast::<Cancelled>() {
+                Ok(cancelled) => Err(*cancelled),
+                Err(payload) => panic::resume_unwind(payload),
+            },
+        }
+    }
+}
+
+/// Unwinds with [`Cancelled`] if an input of the database is about to change
+/// (i.e. if the result of the current query would be out of date anyway).
+pub fn unwind_if_cancelled(db: &dyn salsa::Database) {
+    if db.salsa_runtime().is_current_revision_canceled() {
+        Cancelled::throw();
+    }
+}
+
 impl Display for Cancelled {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Cancelled")
@@ -13,3 +55,23 @@ impl Display for Cancelled {
 }
 
 impl Error for Cancelled {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catch() {
+        assert_eq!(Cancelled::catch(|| 1), Ok(1));
+        assert_eq!(
+            Cancelled::catch(|| -> i32 { Cancelled::throw() }),
+            Err(Cancelled)
+        );
+
+        // Other panics aren't swallowed
+        let result = panic::catch_unwind(|| {
+            Cancelled::catch(|| panic::resume_unwind(Box::new("oops")))
+        });
+        assert!(result.is_err());
+    }
+}
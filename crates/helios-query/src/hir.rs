@@ -0,0 +1,159 @@
+//! The HIR of a file, as queries.
+//!
+//! Each lowering query has a `_with_source_map` variant that returns the
+//! source map too.
+
+use crate::{FileId, Input};
+use helios_hir::{
+    lower_body, lower_module, Body, BodySourceMap, DeclId, Module,
+    ModuleSourceMap,
+};
+use std::sync::Arc;
+
+#[salsa::query_group(HirDatabase)]
+pub trait Hir: Input {
+    /// The declarations of a file, along with their source map.
+    fn module_with_source_map(
+        &self,
+        file_id: FileId,
+    ) -> (Arc<Module>, Arc<ModuleSourceMap>);
+
+    /// The declarations of a file.
+    fn module(&self, file_id: FileId) -> Arc<Module>;
+
+    /// The contents of a declaration, along with their source map.
+    fn body_with_source_map(
+        &self,
+        file_id: FileId,
+        decl: DeclId,
+    ) -> (Arc<Body>, Arc<BodySourceMap>);
+
+    /// The contents of a declaration.
+    fn body(&self, file_id: FileId, decl: DeclId) -> Arc<Body>;
+
+    /// Where the contents of a declaration come from in the source text.
+    fn body_source_map(
+        &self,
+        file_id: FileId,
+        decl: DeclId,
+    ) -> Arc<BodySourceMap>;
+}
+
+fn module_with_source_map(
+    db: &dyn Hir,
+    file_id: FileId,
+) -> (Arc<Module>, Arc<ModuleSourceMap>) {
+    let root = db.parse(file_id).syntax();
+    let (module, source_map) = lower_module(&root);
+    (Arc::new(module), Arc::new(source_map))
+}
+
+fn module(db: &dyn Hir, file_id: FileId) -> Arc<Module> {
+    db.module_with_source_map(file_id).0
+}
+
+fn body_with_source_map(
+    db: &dyn Hir,
+    file_id: FileId,
+    decl: DeclId,
+) -> (Arc<Body>, Arc<BodySourceMap>) {
+    crate::cancel::unwind_if_cancelled(db);
+    let source_map = db.module_with_source_map(file_id).1;
+    let root = db.parse(file_id).syntax();
+    let node = source_map.decl(decl).node.to_node(&root);
+
+    let (body, source_map) = lower_body(&node);
+    (Arc::new(body), Arc::new(source_map))
+}
+
+fn body(db: &dyn Hir, file_id: FileId, decl: DeclId) -> Arc<Body> {
+    db.body_with_source_map(file_id, decl).0
+}
+
+fn body_source_map(
+    db: &dyn Hir,
+    file_id: FileId,
+    decl: DeclId,
+) -> Arc<BodySourceMap> {
+    db.body_with_source_map(file_id, decl).1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HeliosDatabase;
+    use helios_hir::{Expr, Literal, Name};
+
+    const FILE_ID: FileId = FileId(0);
+
+    #[test]
+    fn test_body() {
+        let mut db = HeliosDatabase::default();
+        db.set_source(FILE_ID, Arc::from("let a = 1\nlet b = a + 2\n"));
+
+        let module = db.module(FILE_ID);
+        let b = module.decls_named("b").next().unwrap();
+        let body = db.body(FILE_ID, b);
+        let source_map = db.body_source_map(FILE_ID, b);
+
+        let Expr::Binary { lhs, rhs, .. } = body.exprs[body.value] else {
+            panic!("expected a binary expression");
+        };
+        assert_eq!(body.exprs[lhs], Expr::Variable(Name::new("a")));
+        assert_eq!(
+            body.exprs[rhs],
+            Expr::Literal(Literal::Integer("2".to_string()))
+        );
+        assert_eq!(source_map.expr_range(body.value), 18..23);
+        assert_eq!(source_map.expr_range(rhs), 22..23);
+        assert_eq!(source_map.expr_at_offset(&body, 22), Some(rhs));
+    }
+
+    #[test]
+    fn test_decl_ids_are_stable() {
+        let mut db = HeliosDatabase::default();
+        db.set_source(FILE_ID, Arc::from("let a = 1\nlet b = a\nlet a = 2\n"));
+        let module = db.module(FILE_ID);
+        let ids = module.decls().map(|(id, _)| id).collect::<Vec<_>>();
+        let body = db.body(FILE_ID, ids[1]);
+
+        // Declarations keep their ids when others are added before them,
+        // unless they have the same name
+        db.set_source(
+            FILE_ID,
+            Arc::from("let c = 3\nlet a = 1\nlet b = a\nlet a = 2\n"),
+        );
+        let module = db.module(FILE_ID);
+        let new_ids = module.decls().map(|(id, _)| id).collect::<Vec<_>>();
+        assert_eq!(new_ids[1..], ids);
+        assert_eq!(db.body(FILE_ID, ids[1]), body);
+
+        db.set_source(
+            FILE_ID,
+            Arc::from("let a = 0\nlet a = 1\nlet b = a\nlet a = 2\n"),
+        );
+        let module = db.module(FILE_ID);
+        let new_ids = module.decls().map(|(id, _)| id).collect::<Vec<_>>();
+        assert_eq!(new_ids[0], ids[0]);
+        assert_eq!(new_ids[2], ids[1]);
+        assert_eq!(new_ids[3].index(), 2);
+    }
+
+    #[test]
+    fn test_body_is_unchanged_when_moved() {
+        let mut db = HeliosDatabase::default();
+        db.set_source(FILE_ID, Arc::from("let a = 1\nlet b = a + 2\n"));
+        let b = db.module(FILE_ID).decls_named("b").next().unwrap();
+        let body = db.body(FILE_ID, b);
+        let source_map = db.body_source_map(FILE_ID, b);
+
+        // Only the ranges of the body change
+        db.set_source(FILE_ID, Arc::from("let a = 10\n\nlet b = a  +  2\n"));
+        assert_eq!(db.body(FILE_ID, b), body);
+        assert_ne!(db.body_source_map(FILE_ID, b), source_map);
+        assert_eq!(
+            db.body_source_map(FILE_ID, b).expr_range(body.value),
+            20..27
+        );
+    }
+}
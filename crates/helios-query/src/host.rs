@@ -0,0 +1,259 @@
+//! Analyzing files while they're being edited.
+//!
+//! The [`AnalysisHost`] owns the database and changes its inputs, while an
+//! [`Analysis`] is a snapshot of it that can be sent to another thread.
+
+use crate::cancel::{unwind_if_cancelled, Cancelable, Cancelled};
+use crate::{
+    Binding, Bindings, ChangedFile, FileId, HeliosDatabase, Input,
+    InputLocation, NameResolution, SourceRootId, SourceRoots, TypeInference,
+    Vfs,
+};
+use helios_diagnostics::Diagnostic;
+use helios_locale::Locale;
+use helios_parser::Parse;
+use salsa::{Database, Durability, ParallelDatabase};
+use std::sync::Arc;
+
+/// Owns the database and applies changes to its inputs.
+#[derive(Debug, Default)]
+pub struct AnalysisHost {
+    db: HeliosDatabase,
+}
+
+impl AnalysisHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes a snapshot of the database in its current state.
+    pub fn analysis(&self) -> Analysis {
+        Analysis {
+            db: self.db.snapshot(),
+        }
+    }
+
+    /// Sets the name of a file, as shown in its diagnostics.
+    pub fn set_file_name(
+        &mut self,
+        file_id: FileId,
+        name: impl Into<Arc<str>>,
+    ) {
+        self.db.set_file_name(file_id, name.into());
+    }
+
+    /// Sets the source text of a file, cancelling the work that is still being
+    /// done on snapshots (and waiting for them to be dropped).
+    pub fn set_source(&mut self, file_id: FileId, source: impl Into<Arc<str>>) {
+        self.db.set_source(file_id, source.into());
+    }
+
+    /// Sets the locale the messages of diagnostics are written in.
+    pub fn set_locale(&mut self, locale: Locale) {
+        self.db.set_locale_with_durability(locale, Durability::HIGH);
+    }
+
+    /// Applies the changes recorded by the given VFS (see
+    /// [`Vfs::apply_changes`]).
+    pub fn apply_changes(&mut self, vfs: &mut Vfs) -> Vec<ChangedFile> {
+        vfs.apply_changes(&mut self.db)
+    }
+
+    /// Cancels the work that is still being done on snapshots, without
+    /// changing any input.
+    pub fn request_cancellation(&mut self) {
+        self.db.salsa_runtime_mut().synthetic_write(Durability::LOW);
+    }
+
+    pub fn raw_database(&self) -> &HeliosDatabase {
+        &self.db
+    }
+
+    pub fn raw_database_mut(&mut self) -> &mut HeliosDatabase {
+        &mut self.db
+    }
+}
+
+/// A snapshot of the database, whose queries return `Err(Cancelled)` once an
+/// input is about to change.
+#[derive(Debug)]
+pub struct Analysis {
+    db: salsa::Snapshot<HeliosDatabase>,
+}
+
+impl Analysis {
+    /// The source text of a file.
+    pub fn source(&self, file_id: FileId) -> Cancelable<Arc<str>> {
+        self.with_db(|db| Input::source(db, file_id))
+    }
+
+    /// The parsed syntax tree of a file.
+    pub fn parse(&self, file_id: FileId) -> Cancelable<Parse<FileId>> {
+        self.with_db(|db| db.parse(file_id))
+    }
+
+    /// The diagnostics of a file (syntax errors, unresolved names and type
+    /// errors).
+    pub fn diagnostics(
+        &self,
+        file_id: FileId,
+    ) -> Cancelable<Arc<Vec<Diagnostic<FileId>>>> {
+        self.with_db(|db| db.all_diagnostics(file_id))
+    }
+
+    /// Every binding declared in a file.
+    pub fn all_bindings(
+        &self,
+        file_id: FileId,
+    ) -> Cancelable<Arc<Vec<Binding>>> {
+        self.with_db(|db| db.all_bindings(file_id))
+    }
+
+    /// The binding whose name is at the given offset, if any.
+    pub fn binding_at_offset(
+        &self,
+        file_id: FileId,
+        byte_offset: usize,
+    ) -> Cancelable<Option<Binding>> {
+        self.with_db(|db| db.binding_at_offset(file_id, byte_offset))
+    }
+
+    /// The binding referred to by the name at the given offset (or declared
+    /// there), if any.
+    pub fn definition_at_offset(
+        &self,
+        file_id: FileId,
+        byte_offset: usize,
+    ) -> Cancelable<Option<Binding>> {
+        self.with_db(|db| db.definition_at_offset(file_id, byte_offset))
+    }
+
+    /// The files of a source root.
+    pub fn files_in_root(
+        &self,
+        root_id: SourceRootId,
+    ) -> Cancelable<Arc<Vec<FileId>>> {
+        self.with_db(|db| db.files_in_root(root_id))
+    }
+
+    /// The line and column (both starting at zero) of a byte offset.
+    pub fn position_at_offset(
+        &self,
+        file_id: FileId,
+        byte_offset: usize,
+    ) -> Cancelable<(usize, usize)> {
+        self.with_db(|db| db.source_position_at_offset(file_id, byte_offset))
+    }
+
+    /// Runs the given function on the snapshot, unless it's cancelled before
+    /// or while the function runs.
+    ///
+    /// The cancellation is checked before running the function too, as the
+    /// queries that were already computed return their value without checking
+    /// it.
+    fn with_db<F, T>(&self, f: F) -> Cancelable<T>
+    where
+        F: FnOnce(&HeliosDatabase) -> T,
+    {
+        Cancelled::catch(|| {
+            unwind_if_cancelled(&*self.db);
+            f(&self.db)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::thread;
+
+    const FILE_ID: FileId = FileId(0);
+
+    fn host(source: &str) -> AnalysisHost {
+        let mut host = AnalysisHost::new();
+        host.set_file_name(FILE_ID, "test.hl");
+        host.set_source(FILE_ID, source);
+        host
+    }
+
+    #[test]
+    fn test_analysis() {
+        let host = host("let a = $\n");
+        let analysis = host.analysis();
+
+        assert_eq!(analysis.source(FILE_ID), Ok(Arc::from("let a = $\n")));
+        assert_eq!(analysis.position_at_offset(FILE_ID, 8), Ok((0, 8)));
+        assert!(!analysis.diagnostics(FILE_ID).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_edit_cancels_snapshots() {
+        let mut host = host("let a = 1\n");
+        let analysis = host.analysis();
+        let (ready_sender, ready) = mpsc::channel();
+
+        // The snapshot is queried until the edit cancels it, which can only
+        // happen while it's alive (as the edit waits for it to be dropped)
+        let worker = thread::spawn(move || {
+            ready_sender.send(()).unwrap();
+            while analysis.diagnostics(FILE_ID).is_ok() {
+                thread::yield_now();
+            }
+            assert_eq!(analysis.source(FILE_ID), Err(Cancelled));
+        });
+
+        ready.recv().unwrap();
+        host.set_source(FILE_ID, "let a = 2\n");
+        worker.join().unwrap();
+
+        let analysis = host.analysis();
+        assert_eq!(analysis.source(FILE_ID), Ok(Arc::from("let a = 2\n")));
+    }
+
+    #[test]
+    fn test_concurrent_snapshots_while_editing() {
+        let mut host = host("let a = 0\n");
+
+        for i in 1..=10 {
+            let workers = (0..4)
+                .map(|_| {
+                    let analysis = host.analysis();
+                    thread::spawn(move || {
+                        // Each answer is either cancelled or consistent with
+                        // the source the snapshot was taken with
+                        let source = analysis.source(FILE_ID)?;
+                        let parse = analysis.parse(FILE_ID)?;
+                        let text = parse.syntax().text().to_string();
+                        assert_eq!(text, *source);
+                        Ok::<_, Cancelled>(())
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            host.set_source(FILE_ID, format!("let a = {i}\n"));
+            for worker in workers {
+                let _ = worker.join().unwrap();
+            }
+        }
+
+        let source = host.analysis().source(FILE_ID).unwrap();
+        assert_eq!(&*source, "let a = 10\n");
+    }
+
+    #[test]
+    fn test_request_cancellation() {
+        let mut host = host("let a = 1\n");
+        let analysis = host.analysis();
+
+        let worker = thread::spawn(move || {
+            while analysis.parse(FILE_ID).is_ok() {
+                thread::yield_now();
+            }
+        });
+
+        host.request_cancellation();
+        worker.join().unwrap();
+        assert!(host.analysis().parse(FILE_ID).is_ok());
+    }
+}
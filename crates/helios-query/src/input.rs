@@ -2,6 +2,7 @@
 
 use crate::interner::{BindingData, BindingId, Interner};
 use helios_diagnostics::Diagnostic;
+use helios_locale::Locale;
 use helios_parser::Parse;
 use std::sync::Arc;
 
@@ -10,9 +11,17 @@ pub struct FileId(pub u32);
 
 #[salsa::query_group(InputDatabase)]
 pub trait Input: Interner {
+    /// The name of a file (usually its path), as shown in diagnostics.
+    #[salsa::input]
+    fn file_name(&self, file_id: FileId) -> Arc<str>;
+
     /// The source text of a file.
     #[salsa::input]
-    fn source(&self, file_id: FileId) -> Arc<String>;
+    fn source(&self, file_id: FileId) -> Arc<str>;
+
+    /// The locale the messages of diagnostics are written in.
+    #[salsa::input]
+    fn locale(&self) -> Locale;
 
     /// The length of a file's source text.
     fn source_len(&self, file_id: FileId) -> usize;
@@ -30,6 +39,7 @@ fn source_len(db: &dyn Input, file_id: FileId) -> usize {
 }
 
 fn parse(db: &dyn Input, file_id: FileId) -> Parse<FileId> {
+    crate::cancel::unwind_if_cancelled(db);
     let source = db.source(file_id);
     helios_parser::parse(file_id, &source)
 }
@@ -38,7 +48,14 @@ fn diagnostics(
     db: &dyn Input,
     file_id: FileId,
 ) -> Arc<Vec<Diagnostic<FileId>>> {
+    crate::cancel::unwind_if_cancelled(db);
     let parse = db.parse(file_id);
+    let locale = db.locale();
     let messages = parse.messages();
-    Arc::new(messages.iter().map(|message| message.into()).collect())
+    Arc::new(
+        messages
+            .iter()
+            .map(|message| message.generate_diagnostic_in(locale))
+            .collect(),
+    )
 }
@@ -1,3 +1,5 @@
+use crate::FileId;
+
 #[salsa::query_group(InternerDatabase)]
 pub trait Interner: salsa::Database {
     #[salsa::interned]
@@ -6,7 +8,12 @@ pub trait Interner: salsa::Database {
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct BindingData {
+    /// The file the binding is declared in.
+    pub file_id: FileId,
     pub identifier: String,
+    /// How many bindings with the same name the file declares before this
+    /// one, which tells apart the declarations of a name bound more than once.
+    pub occurrence: usize,
 }
 
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
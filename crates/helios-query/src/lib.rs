@@ -1,21 +1,66 @@
+pub mod bindings;
 pub mod cancel;
+pub mod hir;
+pub mod host;
 pub mod input;
 pub mod interner;
 pub mod location;
+pub mod resolve;
+pub mod types;
+pub mod vfs;
 
+use helios_diagnostics::FileInspector;
+use helios_locale::Locale;
+use salsa::Durability;
 use std::fmt::{self, Debug};
+use std::ops::Range;
+use std::sync::Arc;
 
+pub use crate::bindings::*;
+pub use crate::cancel::{Cancelable, Cancelled};
+pub use crate::hir::*;
+pub use crate::host::*;
 pub use crate::input::*;
 pub use crate::interner::*;
 pub use crate::location::*;
+pub use crate::resolve::*;
+pub use crate::types::*;
+pub use crate::vfs::*;
 
-#[salsa::database(InputLocationDatabase, InputDatabase, InternerDatabase)]
-#[derive(Default)]
+#[salsa::database(
+    BindingDatabase,
+    HirDatabase,
+    InputLocationDatabase,
+    InputDatabase,
+    InternerDatabase,
+    NameResolutionDatabase,
+    SourceRootDatabase,
+    TypeInferenceDatabase
+)]
 pub struct HeliosDatabase {
     storage: salsa::Storage<HeliosDatabase>,
 }
 
-impl salsa::Database for HeliosDatabase {}
+/// The locale of a new database is the current one (see [`Locale::current`]),
+/// which can then be changed with `set_locale`.
+impl Default for HeliosDatabase {
+    fn default() -> Self {
+        let mut db = Self {
+            storage: salsa::Storage::default(),
+        };
+        db.set_locale_with_durability(Locale::current(), Durability::HIGH);
+        db
+    }
+}
+
+impl salsa::Database for HeliosDatabase {
+    /// A query that panics on another thread is most likely one that was
+    /// cancelled (see [`cancel`]), so the query waiting for it is cancelled
+    /// too.
+    fn on_propagated_panic(&self) -> ! {
+        Cancelled::throw()
+    }
+}
 
 impl salsa::ParallelDatabase for HeliosDatabase {
     fn snapshot(&self) -> salsa::Snapshot<Self> {
@@ -31,10 +76,58 @@ impl Debug for HeliosDatabase {
     }
 }
 
+/// Diagnostics can be emitted straight from the database, using the same line
+/// index as the location queries.
+///
+/// Both the name and the source of a file must be set before it's inspected,
+/// as salsa panics when an input is missing.
+impl<'a> FileInspector<'a> for HeliosDatabase {
+    type FileId = FileId;
+    type Name = Arc<str>;
+    type Source = Arc<str>;
+
+    fn name(
+        &'a self,
+        id: Self::FileId,
+    ) -> helios_diagnostics::Result<Arc<str>> {
+        Ok(self.file_name(id))
+    }
+
+    fn source(
+        &'a self,
+        id: Self::FileId,
+    ) -> helios_diagnostics::Result<Arc<str>> {
+        Ok(Input::source(self, id))
+    }
+
+    fn line_count(
+        &'a self,
+        id: Self::FileId,
+    ) -> helios_diagnostics::Result<usize> {
+        Ok(self.source_lines(id).line_count())
+    }
+
+    fn line_index(
+        &'a self,
+        id: Self::FileId,
+        byte_index: usize,
+    ) -> helios_diagnostics::Result<usize> {
+        Ok(self.source_line_index(id, byte_index))
+    }
+
+    fn line_range(
+        &'a self,
+        id: Self::FileId,
+        line_index: usize,
+    ) -> helios_diagnostics::Result<Range<usize>> {
+        self.source_lines(id).line_range(line_index)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::sync::Arc;
+    use helios_diagnostics::{ColorChoice, EmitConfig};
 
     const FILE_ID: FileId = FileId(0);
     const SOURCE: &str = "let a = 0\nlet b = 1\n\nlet c = 2\n";
@@ -42,13 +135,13 @@ mod tests {
     #[test]
     fn test_source_location_queries() {
         let mut db = HeliosDatabase::default();
-        db.set_source(FILE_ID, Arc::new(SOURCE.to_string()));
+        db.set_source(FILE_ID, Arc::from(SOURCE));
 
-        assert_eq!(db.source(FILE_ID), Arc::new(SOURCE.to_string()));
+        assert_eq!(Input::source(&db, FILE_ID), Arc::from(SOURCE));
         assert_eq!(db.source_len(FILE_ID), 31);
 
-        let indexes = vec![0, 10, 20, 21, 31];
-        assert_eq!(db.source_line_indexes(FILE_ID), Arc::new(indexes));
+        let starts = [0, 10, 20, 21, 31];
+        assert_eq!(db.source_lines(FILE_ID).starts(), starts);
 
         assert_eq!(db.source_line_start(FILE_ID, 0), 0);
         assert_eq!(db.source_line_start(FILE_ID, 1), 10);
@@ -106,27 +199,91 @@ mod tests {
         assert_eq!(db.source_position_at_offset(FILE_ID, 26), (3, 5));
         assert_eq!(db.source_position_at_offset(FILE_ID, 30), (3, 9));
         assert_eq!(db.source_position_at_offset(FILE_ID, 31), (4, 0));
+
+        assert_eq!(db.source_offset_at_position(FILE_ID, (0, 5)), 5);
+        assert_eq!(db.source_offset_at_position(FILE_ID, (1, 99)), 19);
+        assert_eq!(db.source_offset_at_position(FILE_ID, (3, 4)), 25);
+        assert_eq!(db.source_offset_at_position(FILE_ID, (9, 0)), 31);
     }
 
-    /*
     #[test]
-    fn test_all_bindings() {
-        fn print_bindings(
-            db: &mut HeliosDatabase,
-            bindings: Arc<Vec<BindingId>>,
-        ) {
-            for binding in bindings.iter() {
-                let binding_data: BindingData =
-                    db.lookup_intern_binding(*binding);
-                println!("{:?} => {:?}", binding, binding_data.identifier);
-            }
+    fn test_source_location_queries_with_unicode() {
+        let mut db = HeliosDatabase::default();
+        let source = "let 名 = \"😀\" + x\n";
+        db.set_source(FILE_ID, Arc::from(source));
+
+        // `x` is the 15th character, but the 16th UTF-16 code unit
+        let x = source.find('x').unwrap();
+        assert_eq!(db.source_position_at_offset(FILE_ID, x), (0, 14));
+        assert_eq!(db.source_utf16_position_at_offset(FILE_ID, x), (0, 15));
+        assert_eq!(db.source_offset_at_position(FILE_ID, (0, 14)), x);
+        assert_eq!(db.source_offset_at_utf16_position(FILE_ID, (0, 15)), x);
+
+        // A position between the code units of `😀` is at its start
+        let emoji = source.find('😀').unwrap();
+        assert_eq!(db.source_offset_at_utf16_position(FILE_ID, (0, 10)), emoji);
+    }
+
+    #[test]
+    fn test_emit_diagnostics_from_database() {
+        let mut db = HeliosDatabase::default();
+        db.set_file_name(FILE_ID, Arc::from("test.hl"));
+        db.set_source(FILE_ID, Arc::from("let 名 = \"😀\"\nlet x = $\n"));
+
+        assert_eq!(db.name(FILE_ID), Ok(Arc::from("test.hl")));
+        assert_eq!(db.line_count(FILE_ID), Ok(3));
+        assert_eq!(db.line_range(FILE_ID, 1), Ok(17..27));
+        assert_eq!(db.column_number(FILE_ID, 25), Ok(9));
+
+        let config = EmitConfig::new().color(ColorChoice::Never).width(60);
+        let mut output = Vec::new();
+        for diagnostic in db.diagnostics(FILE_ID).iter() {
+            helios_diagnostics::emit(&mut output, &db, diagnostic, &config)
+                .unwrap();
         }
 
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("test.hl:2:9"), "{output}");
+    }
+
+    #[test]
+    fn test_all_bindings() {
         let mut db = HeliosDatabase::default();
-        db.set_source(FILE_ID, Arc::new(SOURCE.to_string()));
+        db.set_source(FILE_ID, Arc::from("let a = 1\nlet b = a\nlet a = 2\n"));
 
         let bindings = db.all_bindings(FILE_ID);
-        print_bindings(&mut db, bindings);
+        let names = bindings
+            .iter()
+            .map(|binding| {
+                let data = db.lookup_intern_binding(binding.id);
+                (data.identifier, binding.name_range.clone())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("a".to_string(), 4..5),
+                ("b".to_string(), 14..15),
+                ("a".to_string(), 24..25),
+            ]
+        );
+
+        // Each declaration of a name is a different binding
+        assert_ne!(bindings[0].id, bindings[2].id);
+        assert_ne!(bindings[0].id, bindings[1].id);
+        assert_eq!(bindings[1].range, 10..20);
+        assert_eq!(bindings[1].kind, BindingKind::Global);
+
+        assert_eq!(
+            db.binding_at_offset(FILE_ID, 14),
+            Some(bindings[1].clone())
+        );
+        assert_eq!(db.binding_at_offset(FILE_ID, 18), None);
+
+        // Ids are kept when the file is edited around the bindings
+        db.set_source(FILE_ID, Arc::from("let b = 1\nlet a = b\n"));
+        let edited = db.all_bindings(FILE_ID);
+        assert_eq!(edited[0].id, bindings[1].id);
+        assert_eq!(edited[1].id, bindings[0].id);
     }
-    */
 }
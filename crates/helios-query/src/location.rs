@@ -1,11 +1,12 @@
 use crate::{FileId, Input};
+use helios_diagnostics::column::{self, ColumnKind};
+use helios_diagnostics::LineIndex;
 use std::{ops::Range, sync::Arc};
 
 #[salsa::query_group(InputLocationDatabase)]
 pub trait InputLocation: Input {
-    /// The indices of each line in a file. The first element in the returned
-    /// vector will always be `0`.
-    fn source_line_indexes(&self, file_id: FileId) -> Arc<Vec<usize>>;
+    /// The line index of a file, which knows where each of its lines starts.
+    fn source_lines(&self, file_id: FileId) -> Arc<LineIndex>;
 
     fn source_line_start(&self, file_id: FileId, line_index: usize) -> usize;
 
@@ -35,18 +36,27 @@ pub trait InputLocation: Input {
         file_id: FileId,
         position: (usize, usize),
     ) -> usize;
+
+    /// Just like `source_position_at_offset`, except the column is counted in
+    /// UTF-16 code units (as expected by LSP clients).
+    fn source_utf16_position_at_offset(
+        &self,
+        file_id: FileId,
+        byte_offset: usize,
+    ) -> (usize, usize);
+
+    /// Just like `source_offset_at_position`, except the column is counted in
+    /// UTF-16 code units (as sent by LSP clients).
+    fn source_offset_at_utf16_position(
+        &self,
+        file_id: FileId,
+        position: (usize, usize),
+    ) -> usize;
 }
 
-fn source_line_indexes(
-    db: &dyn InputLocation,
-    file_id: FileId,
-) -> Arc<Vec<usize>> {
+fn source_lines(db: &dyn InputLocation, file_id: FileId) -> Arc<LineIndex> {
     let source = db.source(file_id);
-    let indexes = std::iter::once(0)
-        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
-        .collect();
-
-    Arc::new(indexes)
+    Arc::new(LineIndex::new(&source))
 }
 
 fn source_line_start(
@@ -54,15 +64,12 @@ fn source_line_start(
     file_id: FileId,
     line_index: usize,
 ) -> usize {
-    let line_indexes = db.source_line_indexes(file_id);
+    let lines = db.source_lines(file_id);
 
-    if line_index == line_indexes.len() {
+    if line_index == lines.line_count() {
         db.source_len(file_id)
     } else {
-        line_indexes
-            .get(line_index)
-            .cloned()
-            .expect("Out of bounds")
+        lines.line_start(line_index).expect("Out of bounds")
     }
 }
 
@@ -71,10 +78,8 @@ fn source_line_range(
     file_id: FileId,
     line_index: usize,
 ) -> Range<usize> {
-    let line_start = db.source_line_start(file_id, line_index);
-    let next_line_start = db.source_line_start(file_id, line_index + 1);
-
-    line_start..next_line_start
+    let lines = db.source_lines(file_id);
+    lines.line_range(line_index).expect("Out of bounds")
 }
 
 fn source_line_index(
@@ -82,9 +87,7 @@ fn source_line_index(
     file_id: FileId,
     byte_offset: usize,
 ) -> usize {
-    db.source_line_indexes(file_id)
-        .binary_search(&byte_offset)
-        .unwrap_or_else(|expected| expected.saturating_sub(1))
+    db.source_lines(file_id).line_index(byte_offset)
 }
 
 fn source_column_index(
@@ -93,24 +96,7 @@ fn source_column_index(
     line_index: usize,
     byte_offset: usize,
 ) -> usize {
-    fn column_index(
-        source: &str,
-        line_range: Range<usize>,
-        byte_offset: usize,
-    ) -> usize {
-        use std::cmp::min;
-        let end_index = min(byte_offset, min(line_range.end, source.len()));
-
-        (line_range.start..end_index)
-            .filter(|index| source.is_char_boundary(index + 1))
-            .count()
-    }
-
-    let source = db.source(file_id);
-    let line_range = db.source_line_range(file_id, line_index);
-    let column_index = column_index(source.as_ref(), line_range, byte_offset);
-
-    column_index
+    column_index(db, file_id, line_index, byte_offset, ColumnKind::Chars)
 }
 
 fn source_position_at_offset(
@@ -129,6 +115,67 @@ fn source_offset_at_position(
     file_id: FileId,
     position: (usize, usize),
 ) -> usize {
-    let line_indexes = db.source_line_indexes(file_id);
-    line_indexes[position.0] + position.1
+    offset_at_position(db, file_id, position, ColumnKind::Chars)
+}
+
+fn source_utf16_position_at_offset(
+    db: &dyn InputLocation,
+    file_id: FileId,
+    byte_offset: usize,
+) -> (usize, usize) {
+    let line_index = db.source_line_index(file_id, byte_offset);
+    let column_index =
+        column_index(db, file_id, line_index, byte_offset, ColumnKind::Utf16);
+
+    (line_index, column_index)
+}
+
+fn source_offset_at_utf16_position(
+    db: &dyn InputLocation,
+    file_id: FileId,
+    position: (usize, usize),
+) -> usize {
+    offset_at_position(db, file_id, position, ColumnKind::Utf16)
+}
+
+/// The byte offset the given line starts at and its text, without its line
+/// feed.
+fn line_text<'a>(
+    source: &'a str,
+    lines: &LineIndex,
+    line_index: usize,
+) -> (usize, &'a str) {
+    let line_range = lines.line_range(line_index).expect("Out of bounds");
+    let line = source[line_range.clone()].trim_end_matches(['\n', '\r']);
+
+    (line_range.start, line)
+}
+
+fn column_index(
+    db: &dyn InputLocation,
+    file_id: FileId,
+    line_index: usize,
+    byte_offset: usize,
+    kind: ColumnKind,
+) -> usize {
+    let (source, lines) = (db.source(file_id), db.source_lines(file_id));
+    let (line_start, line) = line_text(&source, &lines, line_index);
+    column::column_at(line, byte_offset.saturating_sub(line_start), kind)
+}
+
+fn offset_at_position(
+    db: &dyn InputLocation,
+    file_id: FileId,
+    (line_index, column_index): (usize, usize),
+    kind: ColumnKind,
+) -> usize {
+    let (source, lines) = (db.source(file_id), db.source_lines(file_id));
+
+    // Positions past the last line are at the end of the file
+    if line_index >= lines.line_count() {
+        return source.len();
+    }
+
+    let (line_start, line) = line_text(&source, &lines, line_index);
+    line_start + column::offset_at_column(line, column_index, kind)
 }
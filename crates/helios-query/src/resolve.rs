@@ -0,0 +1,418 @@
+//! Resolving the names used in a file to the bindings they refer to.
+//!
+//! For now, a file only has a single scope: its global bindings are visible
+//! throughout the file, including before they're declared.
+
+use crate::bindings::{to_range, Binding, Bindings};
+use crate::interner::BindingId;
+use crate::FileId;
+use helios_diagnostics::{
+    codes, Applicability, Diagnostic, Edit, Location, Suggestion,
+};
+use helios_locale::Locale;
+use helios_syntax::SyntaxKind;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// The index of a scope in a [`ScopeTree`].
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ScopeId(u32);
+
+/// A name bound in a scope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScopeEntry {
+    pub name: String,
+    pub binding: BindingId,
+}
+
+/// A region of a file in which a set of names is bound.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScopeData {
+    pub parent: Option<ScopeId>,
+    pub range: Range<usize>,
+    pub entries: Vec<ScopeEntry>,
+}
+
+/// The scopes of a file, from the outermost to the innermost.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScopeTree {
+    scopes: Vec<ScopeData>,
+}
+
+impl ScopeTree {
+    /// The scope of the whole file.
+    pub fn root(&self) -> ScopeId {
+        ScopeId(0)
+    }
+
+    pub fn get(&self, scope: ScopeId) -> &ScopeData {
+        &self.scopes[scope.0 as usize]
+    }
+
+    /// The innermost scope that contains the given offset.
+    pub fn scope_at(&self, byte_offset: usize) -> ScopeId {
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, scope)| {
+                scope.range.start <= byte_offset
+                    && byte_offset <= scope.range.end
+            })
+            .map_or(self.root(), |(index, _)| ScopeId(index as u32))
+    }
+
+    /// The binding the given name refers to in a scope, looking through its
+    /// parents if it isn't bound in the scope itself. A name bound more than
+    /// once in a scope refers to its first declaration.
+    pub fn resolve(&self, scope: ScopeId, name: &str) -> Option<BindingId> {
+        self.ancestors(scope).find_map(|scope| {
+            self.get(scope)
+                .entries
+                .iter()
+                .find(|entry| entry.name == name)
+                .map(|entry| entry.binding)
+        })
+    }
+
+    /// Every name visible in a scope, the innermost ones first (a name that
+    /// is shadowed is only included once).
+    pub fn names_in_scope(&self, scope: ScopeId) -> Vec<&str> {
+        let mut names = Vec::new();
+        for scope in self.ancestors(scope) {
+            for entry in &self.get(scope).entries {
+                if !names.contains(&entry.name.as_str()) {
+                    names.push(entry.name.as_str());
+                }
+            }
+        }
+        names
+    }
+
+    fn ancestors(&self, scope: ScopeId) -> impl Iterator<Item = ScopeId> + '_ {
+        std::iter::successors(Some(scope), |&scope| self.get(scope).parent)
+    }
+}
+
+/// A name used in a file (e.g. in a variable reference).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reference {
+    pub name: String,
+    pub range: Range<usize>,
+    /// The binding the name refers to, or `None` if it's unresolved.
+    pub binding: Option<BindingId>,
+}
+
+#[salsa::query_group(NameResolutionDatabase)]
+pub trait NameResolution: Bindings {
+    /// The scopes of a file and the names they bind.
+    fn scope_tree(&self, file_id: FileId) -> Arc<ScopeTree>;
+
+    /// Every name used in a file, in the order they appear, along with the
+    /// binding it refers to.
+    fn resolve_references(&self, file_id: FileId) -> Arc<Vec<Reference>>;
+
+    /// The binding referred to by the name at the given offset (or declared
+    /// there), if any.
+    fn definition_at_offset(
+        &self,
+        file_id: FileId,
+        byte_offset: usize,
+    ) -> Option<Binding>;
+
+    /// Diagnostics for the names of a file that don't refer to any binding.
+    fn name_diagnostics(&self, file_id: FileId)
+        -> Arc<Vec<Diagnostic<FileId>>>;
+}
+
+fn scope_tree(db: &dyn NameResolution, file_id: FileId) -> Arc<ScopeTree> {
+    let entries = db
+        .all_bindings(file_id)
+        .iter()
+        .map(|binding| ScopeEntry {
+            name: db.lookup_intern_binding(binding.id).identifier,
+            binding: binding.id,
+        })
+        .collect();
+
+    let root = ScopeData {
+        parent: None,
+        range: 0..db.source_len(file_id),
+        entries,
+    };
+
+    Arc::new(ScopeTree { scopes: vec![root] })
+}
+
+fn resolve_references(
+    db: &dyn NameResolution,
+    file_id: FileId,
+) -> Arc<Vec<Reference>> {
+    crate::cancel::unwind_if_cancelled(db);
+    let scopes = db.scope_tree(file_id);
+    let root = db.parse(file_id).syntax();
+
+    let references = root
+        .descendants()
+        .filter(|node| node.kind() == SyntaxKind::Exp_VariableRef)
+        .filter_map(|node| {
+            node.children_with_tokens()
+                .filter_map(|element| element.into_token())
+                .find(|token| token.kind() == SyntaxKind::Identifier)
+        })
+        .map(|token| {
+            let range = to_range(token.text_range());
+            let name = token.text().to_string();
+            let scope = scopes.scope_at(range.start);
+            Reference {
+                binding: scopes.resolve(scope, &name),
+                name,
+                range,
+            }
+        })
+        .collect();
+
+    Arc::new(references)
+}
+
+fn definition_at_offset(
+    db: &dyn NameResolution,
+    file_id: FileId,
+    byte_offset: usize,
+) -> Option<Binding> {
+    let contains = |range: &Range<usize>| {
+        range.start <= byte_offset && byte_offset <= range.end
+    };
+
+    let references = db.resolve_references(file_id);
+    let id = match references.iter().find(|it| contains(&it.range)) {
+        Some(reference) => reference.binding?,
+        None => return db.binding_at_offset(file_id, byte_offset),
+    };
+
+    db.all_bindings(file_id)
+        .iter()
+        .find(|binding| binding.id == id)
+        .cloned()
+}
+
+fn name_diagnostics(
+    db: &dyn NameResolution,
+    file_id: FileId,
+) -> Arc<Vec<Diagnostic<FileId>>> {
+    let scopes = db.scope_tree(file_id);
+    let locale = db.locale();
+
+    let diagnostics = db
+        .resolve_references(file_id)
+        .iter()
+        .filter(|reference| reference.binding.is_none())
+        .map(|reference| {
+            let location = Location::new(file_id, reference.range.clone());
+            let scope = scopes.scope_at(reference.range.start);
+            let candidate =
+                closest_name(&reference.name, scopes.names_in_scope(scope));
+            unresolved_name(&reference.name, candidate, location, locale)
+        })
+        .collect();
+
+    Arc::new(diagnostics)
+}
+
+fn unresolved_name(
+    name: &str,
+    candidate: Option<&str>,
+    location: Location<FileId>,
+    locale: Locale,
+) -> Diagnostic<FileId> {
+    let description = locale.formatted("e0006-description", &[]);
+    let message =
+        locale.formatted("e0006-message", &[("name", name.to_string().into())]);
+
+    let diagnostic = Diagnostic::error(locale.text("e0006-title", &[]))
+        .code(codes::UNRESOLVED_NAME)
+        .location(location.clone())
+        .description(description)
+        .message(message);
+
+    match candidate {
+        Some(candidate) => {
+            let suggestion = Suggestion::new(
+                locale.text(
+                    "e0006-suggestion",
+                    &[("name", candidate.to_string().into())],
+                ),
+                Applicability::MaybeIncorrect,
+            )
+            .edit(Edit::new(location, candidate));
+            diagnostic.suggestion(suggestion)
+        }
+        None => diagnostic.hint(locale.formatted("e0006-hint", &[])),
+    }
+}
+
+/// The name that is the closest to the given one (i.e. the most likely to be
+/// what was meant when the given name is a typo), if any is close enough.
+///
+/// Names shorter than three characters get no suggestion, since any other
+/// short name would be as close to them as it is to a typo.
+fn closest_name<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let length = name.chars().count();
+    if length < 3 {
+        return None;
+    }
+
+    // Always less than the length of the name
+    let threshold = length / 3;
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|&(distance, _)| distance <= threshold)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The number of characters to insert, delete, replace or swap with the next
+/// one to turn a string into another (i.e. their optimal string alignment
+/// distance, which counts a transposition as a single typo).
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    // `distances[i][j]` is the distance between the first `i` characters of
+    // `a` and the first `j` characters of `b`
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    distances[0] = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j - 1] + cost)
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HeliosDatabase, Input};
+
+    const FILE_ID: FileId = FileId(0);
+
+    fn database(source: &str) -> HeliosDatabase {
+        let mut db = HeliosDatabase::default();
+        db.set_file_name(FILE_ID, Arc::from("test.hl"));
+        db.set_source(FILE_ID, Arc::from(source));
+        db
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", ""), 0);
+        assert_eq!(edit_distance("count", "count"), 0);
+        assert_eq!(edit_distance("count", ""), 5);
+        assert_eq!(edit_distance("cuont", "count"), 1);
+        assert_eq!(edit_distance("ab", "ca"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("名前", "名"), 1);
+    }
+
+    #[test]
+    fn test_closest_name() {
+        let names = ["count", "total", "a"];
+        assert_eq!(closest_name("cont", names), Some("count"));
+        assert_eq!(closest_name("totl", names), Some("total"));
+        assert_eq!(closest_name("b", names), None);
+        assert_eq!(closest_name("x", ["y"]), None);
+        assert_eq!(closest_name("ab", ["abc", "b"]), None);
+        assert_eq!(closest_name("xyz", names), None);
+        assert_eq!(closest_name("abd", ["abc"]), Some("abc"));
+    }
+
+    #[test]
+    fn test_resolve_references() {
+        let db = database("let a = b\nlet b = a + c\n");
+
+        let bindings = db.all_bindings(FILE_ID);
+        let references = db.resolve_references(FILE_ID);
+        let resolved = references
+            .iter()
+            .map(|it| (it.name.as_str(), it.range.clone(), it.binding))
+            .collect::<Vec<_>>();
+
+        // Global bindings can be used before they're declared
+        assert_eq!(
+            resolved,
+            vec![
+                ("b", 8..9, Some(bindings[1].id)),
+                ("a", 18..19, Some(bindings[0].id)),
+                ("c", 22..23, None),
+            ]
+        );
+
+        let scopes = db.scope_tree(FILE_ID);
+        assert_eq!(scopes.scope_at(20), scopes.root());
+        assert_eq!(scopes.names_in_scope(scopes.root()), vec!["a", "b"]);
+        assert_eq!(scopes.resolve(scopes.root(), "b"), Some(bindings[1].id));
+        assert_eq!(scopes.resolve(scopes.root(), "c"), None);
+    }
+
+    #[test]
+    fn test_definition_at_offset() {
+        let db = database("let a = 1\nlet b = a\nlet a = 2\n");
+        let bindings = db.all_bindings(FILE_ID);
+
+        assert_eq!(
+            db.definition_at_offset(FILE_ID, 18),
+            Some(bindings[0].clone())
+        );
+        assert_eq!(
+            db.definition_at_offset(FILE_ID, 14),
+            Some(bindings[1].clone())
+        );
+        assert_eq!(db.definition_at_offset(FILE_ID, 16), None);
+    }
+
+    #[test]
+    fn test_unresolved_name_diagnostics() {
+        let db = database("let count = 1\nlet total = cuont + xyz\n");
+
+        let diagnostics = db.name_diagnostics(FILE_ID);
+        assert_eq!(diagnostics.len(), 2);
+
+        let typo = &diagnostics[0];
+        assert_eq!(typo.code, Some(codes::UNRESOLVED_NAME));
+        assert_eq!(typo.location, Location::new(FILE_ID, 26..31));
+        assert_eq!(typo.suggestions.len(), 1);
+        assert_eq!(typo.suggestions[0].edits[0].replacement, "count");
+        assert!(typo.hint.is_none());
+
+        let unknown = &diagnostics[1];
+        assert!(unknown.suggestions.is_empty());
+        assert!(unknown.hint.is_some());
+    }
+
+    #[test]
+    fn test_diagnostics_follow_locale() {
+        let mut db = database("let total = xyz\n");
+        db.set_locale(Locale::EnUs);
+        assert_eq!(db.name_diagnostics(FILE_ID)[0].title, "Unresolved name");
+
+        db.set_locale(Locale::Fr);
+        assert_eq!(db.name_diagnostics(FILE_ID)[0].title, "Nom non résolu");
+    }
+}
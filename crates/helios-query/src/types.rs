@@ -0,0 +1,198 @@
+//! The types of a file, as queries.
+
+use crate::{FileId, Hir, NameResolution};
+use helios_diagnostics::Diagnostic;
+use helios_hir::{DeclId, Expr, ExprId};
+use helios_types::{infer_module, InferenceResult, ResolvedVariables, Ty};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[salsa::query_group(TypeInferenceDatabase)]
+pub trait TypeInference: Hir + NameResolution {
+    /// The declarations the variables of a declaration refer to, according
+    /// to name resolution.
+    fn resolved_variables(
+        &self,
+        file_id: FileId,
+        decl: DeclId,
+    ) -> Arc<ResolvedVariables>;
+
+    /// The types of the declarations of a file and of everything in them.
+    fn infer(&self, file_id: FileId) -> Arc<InferenceResult>;
+
+    /// The type of an expression of a declaration.
+    fn type_of_expr(
+        &self,
+        file_id: FileId,
+        decl: DeclId,
+        expr: ExprId,
+    ) -> Option<Ty>;
+
+    /// Diagnostics for the expressions of a file whose types are wrong.
+    fn type_diagnostics(&self, file_id: FileId)
+        -> Arc<Vec<Diagnostic<FileId>>>;
+
+    /// Every diagnostic of a file: the ones of the parser, then the ones of
+    /// name resolution, then the ones of type inference.
+    fn all_diagnostics(&self, file_id: FileId) -> Arc<Vec<Diagnostic<FileId>>>;
+}
+
+fn resolved_variables(
+    db: &dyn TypeInference,
+    file_id: FileId,
+    decl: DeclId,
+) -> Arc<ResolvedVariables> {
+    // Bindings and declarations are matched by the range of their name
+    let module_map = db.module_with_source_map(file_id).1;
+    let decls = db
+        .module(file_id)
+        .decls()
+        .map(|(decl, _)| (module_map.decl(decl).name_range.clone(), decl))
+        .collect::<HashMap<_, _>>();
+    let bindings = db.all_bindings(file_id);
+    let targets = db
+        .resolve_references(file_id)
+        .iter()
+        .filter_map(|reference| {
+            let id = reference.binding?;
+            let binding = bindings.iter().find(|binding| binding.id == id)?;
+            let decl = *decls.get(&binding.name_range)?;
+            Some((reference.range.clone(), decl))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let (body, source_map) = db.body_with_source_map(file_id, decl);
+    let mut variables = ResolvedVariables::default();
+    for (expr, data) in body.exprs.iter() {
+        if let Expr::Variable(_) = data {
+            if let Some(&target) = targets.get(&source_map.expr_range(expr)) {
+                variables.insert(expr, target);
+            }
+        }
+    }
+
+    Arc::new(variables)
+}
+
+fn infer(db: &dyn TypeInference, file_id: FileId) -> Arc<InferenceResult> {
+    crate::cancel::unwind_if_cancelled(db);
+    let module = db.module(file_id);
+    Arc::new(infer_module(
+        &module,
+        &|decl| db.body(file_id, decl),
+        &|decl| db.resolved_variables(file_id, decl),
+    ))
+}
+
+fn type_of_expr(
+    db: &dyn TypeInference,
+    file_id: FileId,
+    decl: DeclId,
+    expr: ExprId,
+) -> Option<Ty> {
+    db.infer(file_id).type_of_expr(decl, expr).cloned()
+}
+
+fn type_diagnostics(
+    db: &dyn TypeInference,
+    file_id: FileId,
+) -> Arc<Vec<Diagnostic<FileId>>> {
+    let locale = db.locale();
+    let diagnostics = db
+        .infer(file_id)
+        .errors
+        .iter()
+        .map(|error| {
+            let source_map = db.body_source_map(file_id, error.decl);
+            let location = source_map.expr_location(file_id, error.expr);
+            error.diagnostic(location, locale)
+        })
+        .collect();
+
+    Arc::new(diagnostics)
+}
+
+fn all_diagnostics(
+    db: &dyn TypeInference,
+    file_id: FileId,
+) -> Arc<Vec<Diagnostic<FileId>>> {
+    let mut diagnostics = db.diagnostics(file_id).to_vec();
+    diagnostics.extend(db.name_diagnostics(file_id).iter().cloned());
+    diagnostics.extend(db.type_diagnostics(file_id).iter().cloned());
+    Arc::new(diagnostics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HeliosDatabase, Input};
+    use helios_diagnostics::{codes, Location, Severity};
+
+    const FILE_ID: FileId = FileId(0);
+
+    fn database(source: &str) -> HeliosDatabase {
+        let mut db = HeliosDatabase::default();
+        db.set_file_name(FILE_ID, Arc::from("test.hl"));
+        db.set_source(FILE_ID, Arc::from(source));
+        db
+    }
+
+    #[test]
+    fn test_type_of_expr() {
+        let db = database("let a = 1\nlet b = a < 2.5\n");
+        let b = db.module(FILE_ID).decls_named("b").next().unwrap();
+        let body = db.body(FILE_ID, b);
+        let source_map = db.body_source_map(FILE_ID, b);
+
+        let a = source_map.expr_at_offset(&body, 18).unwrap();
+        assert_eq!(db.type_of_expr(FILE_ID, b, a), Some(Ty::Int));
+        assert_eq!(db.type_of_expr(FILE_ID, b, body.value), Some(Ty::Bool));
+    }
+
+    #[test]
+    fn test_infer_follows_name_resolution() {
+        let db = database("let a = 1\nlet a = 2.5\nlet b = a + 1\n");
+        let module = db.module(FILE_ID);
+        let b = module.decls_named("b").next().unwrap();
+
+        let body = db.body(FILE_ID, b);
+        let source_map = db.body_source_map(FILE_ID, b);
+        let a = source_map.expr_at_offset(&body, 30).unwrap();
+        let first = module.decls_named("a").next().unwrap();
+        assert_eq!(db.resolved_variables(FILE_ID, b).get(a), Some(&first));
+        assert_eq!(db.type_of_expr(FILE_ID, b, a), Some(Ty::Int));
+        assert_eq!(db.type_diagnostics(FILE_ID).len(), 0);
+    }
+
+    #[test]
+    fn test_type_diagnostics() {
+        let db = database("let a = 1\nlet b = a + 2.5\n");
+
+        let diagnostics = db.type_diagnostics(FILE_ID);
+        assert_eq!(diagnostics.len(), 1);
+        let mismatch = &diagnostics[0];
+        assert_eq!(mismatch.severity, Severity::Error);
+        assert_eq!(mismatch.code, Some(codes::MISMATCHED_TYPES));
+        assert_eq!(mismatch.location, Location::new(FILE_ID, 22..25));
+
+        let message = mismatch.message.render_plain();
+        assert!(message.contains("Int"), "{message}");
+        assert!(message.contains("Float"), "{message}");
+    }
+
+    #[test]
+    fn test_all_diagnostics() {
+        let db = database("let a = b + (1 < 2)\n");
+
+        // The parser's diagnostics come first, and the unresolved name isn't
+        // reported again as a type error
+        let all = db.all_diagnostics(FILE_ID);
+        let parser = db.diagnostics(FILE_ID);
+        let codes = all[parser.len()..]
+            .iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect::<Vec<_>>();
+        assert_eq!(all[..parser.len()], parser[..]);
+        assert_eq!(codes, [Some(codes::UNRESOLVED_NAME)]);
+    }
+}
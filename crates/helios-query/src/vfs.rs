@@ -0,0 +1,508 @@
+//! A virtual file system for the query database.
+//!
+//! The [`Vfs`] gives every path a [`FileId`] and groups files into source
+//! roots, and [`Vfs::apply_changes`] turns the changes it recorded into inputs
+//! of the database.
+
+use crate::{FileId, Input};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The extension of Helios source files.
+pub const SOURCE_FILE_EXTENSION: &str = "hl";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceRootId(pub u32);
+
+/// A directory of source files, such as the directory of a project.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceRoot {
+    path: PathBuf,
+    files: BTreeMap<PathBuf, FileId>,
+}
+
+impl SourceRoot {
+    /// The path of the directory.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The files of the root, ordered by their paths.
+    pub fn files(&self) -> impl Iterator<Item = FileId> + '_ {
+        self.files.values().copied()
+    }
+
+    /// The file at the given path, relative to the root.
+    pub fn file_at(&self, relative_path: impl AsRef<Path>) -> Option<FileId> {
+        self.files.get(relative_path.as_ref()).copied()
+    }
+
+    /// The path of the given file, relative to the root.
+    pub fn relative_path(&self, file_id: FileId) -> Option<&Path> {
+        self.files
+            .iter()
+            .find(|(_, id)| **id == file_id)
+            .map(|(path, _)| path.as_path())
+    }
+}
+
+#[salsa::query_group(SourceRootDatabase)]
+pub trait SourceRoots: Input {
+    /// The source root a file belongs to, if any.
+    #[salsa::input]
+    fn file_source_root(&self, file_id: FileId) -> Option<SourceRootId>;
+
+    /// The contents of a source root.
+    #[salsa::input]
+    fn source_root(&self, root_id: SourceRootId) -> Arc<SourceRoot>;
+
+    /// The files of a source root, ordered by their paths.
+    fn files_in_root(&self, root_id: SourceRootId) -> Arc<Vec<FileId>>;
+}
+
+fn files_in_root(
+    db: &dyn SourceRoots,
+    root_id: SourceRootId,
+) -> Arc<Vec<FileId>> {
+    Arc::new(db.source_root(root_id).files().collect())
+}
+
+/// How a file changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChangeKind {
+    Create,
+    Modify,
+    Delete,
+}
+
+/// A file that changed since the changes were last applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChangedFile {
+    pub file_id: FileId,
+    pub kind: ChangeKind,
+}
+
+#[derive(Clone, Debug)]
+struct VfsFile {
+    path: PathBuf,
+    contents: Option<Arc<str>>,
+}
+
+/// Maps paths to [`FileId`]s and keeps the contents of every file.
+///
+/// A path keeps its [`FileId`] even after its file is deleted, so that the
+/// file gets the same id if it's created again.
+#[derive(Clone, Debug, Default)]
+pub struct Vfs {
+    files: Vec<VfsFile>,
+    ids: HashMap<PathBuf, FileId>,
+    roots: Vec<PathBuf>,
+    changes: Vec<ChangedFile>,
+    roots_changed: bool,
+}
+
+impl Vfs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of the file at the given path, if it exists.
+    pub fn file_id(&self, path: impl AsRef<Path>) -> Option<FileId> {
+        let file_id = *self.ids.get(path.as_ref())?;
+        self.file_contents(file_id).map(|_| file_id)
+    }
+
+    /// The path of the given file.
+    pub fn file_path(&self, file_id: FileId) -> &Path {
+        &self.files[file_id.0 as usize].path
+    }
+
+    /// The contents of the given file, or `None` if it was deleted.
+    pub fn file_contents(&self, file_id: FileId) -> Option<&Arc<str>> {
+        self.files[file_id.0 as usize].contents.as_ref()
+    }
+
+    /// The files that currently exist, ordered by their ids.
+    pub fn iter(&self) -> impl Iterator<Item = (FileId, &Path)> + '_ {
+        self.files
+            .iter()
+            .enumerate()
+            .filter(|(_, file)| file.contents.is_some())
+            .map(|(index, file)| (FileId(index as u32), file.path.as_path()))
+    }
+
+    /// Sets the contents of the file at the given path, where `None` means
+    /// that the file was deleted, and records the change.
+    ///
+    /// Nothing is recorded if the contents didn't change.
+    pub fn set_file_contents(
+        &mut self,
+        path: impl Into<PathBuf>,
+        contents: Option<String>,
+    ) -> FileId {
+        let path = path.into();
+        let file_id = match self.ids.get(&path) {
+            Some(file_id) => *file_id,
+            None => {
+                let file_id = FileId(self.files.len() as u32);
+                self.files.push(VfsFile {
+                    path: path.clone(),
+                    contents: None,
+                });
+                self.ids.insert(path, file_id);
+                file_id
+            }
+        };
+
+        let file = &mut self.files[file_id.0 as usize];
+        let kind = match (&file.contents, &contents) {
+            (None, None) => return file_id,
+            (None, Some(_)) => ChangeKind::Create,
+            (Some(_), None) => ChangeKind::Delete,
+            (Some(old), Some(new)) if **old == **new => return file_id,
+            (Some(_), Some(_)) => ChangeKind::Modify,
+        };
+
+        file.contents = contents.map(Arc::from);
+        self.changes.push(ChangedFile { file_id, kind });
+        file_id
+    }
+
+    /// Reads the file at the given path from the file system (or records that
+    /// it was deleted if it doesn't exist anymore).
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> io::Result<FileId> {
+        let path = path.as_ref();
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => Some(contents),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => return Err(error),
+        };
+
+        Ok(self.set_file_contents(path, contents))
+    }
+
+    /// Reads whatever is at the given path again: a source file, a directory
+    /// whose source files are all read again, or nothing anymore, in which
+    /// case every file that was under the path is deleted.
+    ///
+    /// A file that can't be read keeps its previous contents, and its error
+    /// is returned without stopping the other files from being read.
+    pub fn load_path(
+        &mut self,
+        path: impl AsRef<Path>,
+    ) -> Vec<(PathBuf, io::Error)> {
+        let path = path.as_ref();
+        let mut errors = Vec::new();
+        let mut paths = Vec::new();
+        if path.is_dir() {
+            if let Err(error) = collect_source_files(path, &mut paths) {
+                errors.push((path.to_path_buf(), error));
+                return errors;
+            }
+        } else if path.is_file()
+            && path
+                .extension()
+                .is_some_and(|it| it == SOURCE_FILE_EXTENSION)
+        {
+            paths.push(path.to_path_buf());
+        }
+
+        // Files under the path that aren't there anymore have been moved or
+        // deleted along with (or instead of) their directory
+        let deleted = self
+            .iter()
+            .filter(|(_, it)| {
+                it.starts_with(path) && !paths.iter().any(|p| p == it)
+            })
+            .map(|(_, it)| it.to_path_buf())
+            .collect::<Vec<_>>();
+        for deleted_path in deleted {
+            self.set_file_contents(deleted_path, None);
+        }
+
+        paths.sort();
+        for path in paths {
+            if let Err(error) = self.load_file(&path) {
+                errors.push((path, error));
+            }
+        }
+
+        errors
+    }
+
+    /// Adds a source root for the given directory (without reading it).
+    pub fn add_root(&mut self, path: impl Into<PathBuf>) -> SourceRootId {
+        let path = path.into();
+        match self.roots.iter().position(|root| *root == path) {
+            Some(index) => SourceRootId(index as u32),
+            None => {
+                self.roots.push(path);
+                self.roots_changed = true;
+                SourceRootId(self.roots.len() as u32 - 1)
+            }
+        }
+    }
+
+    /// Adds a source root for the given directory and reads every source file
+    /// inside of it (including the ones in its subdirectories).
+    ///
+    /// Hidden files and directories (i.e. whose name starts with a dot) are
+    /// skipped.
+    pub fn load_root(
+        &mut self,
+        path: impl Into<PathBuf>,
+    ) -> io::Result<SourceRootId> {
+        let path = path.into();
+        let mut paths = Vec::new();
+        collect_source_files(&path, &mut paths)?;
+        paths.sort();
+
+        let root_id = self.add_root(path);
+        for path in paths {
+            self.load_file(path)?;
+        }
+
+        Ok(root_id)
+    }
+
+    /// The path of the given source root.
+    pub fn root_path(&self, root_id: SourceRootId) -> &Path {
+        &self.roots[root_id.0 as usize]
+    }
+
+    /// The innermost source root that contains the given path, if any.
+    pub fn root_of(&self, path: impl AsRef<Path>) -> Option<SourceRootId> {
+        let path = path.as_ref();
+        self.roots
+            .iter()
+            .enumerate()
+            .filter(|(_, root)| path.starts_with(root))
+            .max_by_key(|(_, root)| root.components().count())
+            .map(|(index, _)| SourceRootId(index as u32))
+    }
+
+    /// Determines if any file changed since the changes were last applied.
+    pub fn has_changes(&self) -> bool {
+        !self.changes.is_empty() || self.roots_changed
+    }
+
+    /// Returns the changes since they were last taken (or applied).
+    pub fn take_changes(&mut self) -> Vec<ChangedFile> {
+        std::mem::take(&mut self.changes)
+    }
+
+    /// Sets the inputs of the database for every file that changed since the
+    /// changes were last applied, and returns these changes.
+    ///
+    /// Source roots are only updated if a file was created or deleted (or if
+    /// a root was added), as modifying a file doesn't change the roots.
+    pub fn apply_changes(
+        &mut self,
+        db: &mut dyn SourceRoots,
+    ) -> Vec<ChangedFile> {
+        let changes = self.take_changes();
+
+        for change in &changes {
+            let file = &self.files[change.file_id.0 as usize];
+            if change.kind == ChangeKind::Create {
+                let name = file.path.display().to_string();
+                db.set_file_name(change.file_id, Arc::from(name));
+            }
+
+            // The text of deleted files isn't needed anymore
+            let source = file.contents.clone().unwrap_or_else(|| Arc::from(""));
+            db.set_source(change.file_id, source);
+        }
+
+        let roots_changed = std::mem::take(&mut self.roots_changed)
+            || changes
+                .iter()
+                .any(|change| change.kind != ChangeKind::Modify);
+
+        if roots_changed {
+            let mut roots = self
+                .roots
+                .iter()
+                .map(|path| SourceRoot {
+                    path: path.clone(),
+                    files: BTreeMap::new(),
+                })
+                .collect::<Vec<_>>();
+
+            for (index, file) in self.files.iter().enumerate() {
+                let file_id = FileId(index as u32);
+                let root_id = file
+                    .contents
+                    .as_ref()
+                    .and_then(|_| self.root_of(&file.path));
+
+                if let Some(root_id) = root_id {
+                    let root = &mut roots[root_id.0 as usize];
+                    let relative_path = file
+                        .path
+                        .strip_prefix(&root.path)
+                        .expect("files should be inside of their root");
+                    root.files.insert(relative_path.to_path_buf(), file_id);
+                }
+
+                db.set_file_source_root(file_id, root_id);
+            }
+
+            for (index, root) in roots.into_iter().enumerate() {
+                db.set_source_root(SourceRootId(index as u32), Arc::new(root));
+            }
+        }
+
+        changes
+    }
+}
+
+/// Collects the paths of the source files inside of the given directory (and
+/// its subdirectories).
+fn collect_source_files(
+    dir: &Path,
+    paths: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if is_hidden {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            collect_source_files(&path, paths)?;
+        } else if path
+            .extension()
+            .is_some_and(|it| it == SOURCE_FILE_EXTENSION)
+        {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HeliosDatabase;
+
+    #[test]
+    fn test_set_file_contents() {
+        let mut vfs = Vfs::new();
+        let a = vfs.set_file_contents("/a.hl", Some("let a = 1".to_string()));
+        let b = vfs.set_file_contents("/b.hl", Some("let b = 2".to_string()));
+        assert_ne!(a, b);
+        assert_eq!(vfs.file_id("/a.hl"), Some(a));
+        assert_eq!(vfs.file_path(b), Path::new("/b.hl"));
+
+        // Setting the same contents again isn't a change
+        vfs.set_file_contents("/a.hl", Some("let a = 1".to_string()));
+        vfs.set_file_contents("/a.hl", Some("let a = 3".to_string()));
+        vfs.set_file_contents("/b.hl", None);
+        assert_eq!(vfs.file_id("/b.hl"), None);
+
+        let kinds = vfs
+            .take_changes()
+            .into_iter()
+            .map(|change| (change.file_id, change.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (a, ChangeKind::Create),
+                (b, ChangeKind::Create),
+                (a, ChangeKind::Modify),
+                (b, ChangeKind::Delete),
+            ]
+        );
+
+        // Deleted files get their id back when they're created again
+        assert_eq!(vfs.set_file_contents("/b.hl", Some(String::new())), b);
+    }
+
+    #[test]
+    fn test_root_of() {
+        let mut vfs = Vfs::new();
+        let project = vfs.add_root("/project");
+        let tests = vfs.add_root("/project/tests");
+
+        assert_eq!(vfs.root_of("/project/main.hl"), Some(project));
+        assert_eq!(vfs.root_of("/project/tests/a.hl"), Some(tests));
+        assert_eq!(vfs.root_of("/elsewhere/main.hl"), None);
+        assert_eq!(vfs.add_root("/project"), project);
+    }
+
+    #[test]
+    fn test_load_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |path: &str, contents: &str| {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        };
+        write("main.hl", "let a = 1\n");
+        write("lib/math.hl", "let b = 2\n");
+        write("README.md", "# Project\n");
+        write(".hidden/ignored.hl", "let c = 3\n");
+
+        let mut vfs = Vfs::new();
+        let root_id = vfs.load_root(dir.path()).unwrap();
+        let mut db = HeliosDatabase::default();
+        vfs.apply_changes(&mut db);
+
+        let main = vfs.file_id(dir.path().join("main.hl")).unwrap();
+        let math = vfs.file_id(dir.path().join("lib/math.hl")).unwrap();
+        assert_eq!(*db.files_in_root(root_id), vec![math, main]);
+        assert_eq!(db.file_source_root(main), Some(root_id));
+        assert_eq!(&*Input::source(&db, math), "let b = 2\n");
+
+        let root = db.source_root(root_id);
+        assert_eq!(root.file_at("lib/math.hl"), Some(math));
+        assert_eq!(root.relative_path(main), Some(Path::new("main.hl")));
+
+        // Deleting a file removes it from its root
+        std::fs::remove_file(dir.path().join("lib/math.hl")).unwrap();
+        vfs.load_file(dir.path().join("lib/math.hl")).unwrap();
+        let changes = vfs.apply_changes(&mut db);
+        assert_eq!(changes[0].kind, ChangeKind::Delete);
+        assert_eq!(*db.files_in_root(root_id), vec![main]);
+        assert_eq!(db.file_source_root(math), None);
+    }
+
+    #[test]
+    fn test_load_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("lib");
+        std::fs::create_dir(&lib).unwrap();
+        std::fs::write(lib.join("math.hl"), "let a = 1\n").unwrap();
+
+        let mut vfs = Vfs::new();
+        vfs.load_root(dir.path()).unwrap();
+        let math = vfs.file_id(lib.join("math.hl")).unwrap();
+
+        // Renaming a directory deletes its files and creates them elsewhere
+        let core = dir.path().join("core");
+        std::fs::rename(&lib, &core).unwrap();
+        assert!(vfs.load_path(&lib).is_empty());
+        assert!(vfs.load_path(&core).is_empty());
+        assert_eq!(vfs.file_id(lib.join("math.hl")), None);
+        let moved = vfs.file_id(core.join("math.hl")).unwrap();
+        assert_ne!(moved, math);
+
+        // A file that can't be read is reported and keeps its contents
+        std::fs::write(core.join("math.hl"), [0xff, 0xfe]).unwrap();
+        std::fs::write(core.join("io.hl"), "let b = 2\n").unwrap();
+        let errors = vfs.load_path(&core);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, core.join("math.hl"));
+        assert_eq!(errors[0].1.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(vfs.file_id(core.join("math.hl")), Some(moved));
+        assert!(vfs.file_id(core.join("io.hl")).is_some());
+    }
+}
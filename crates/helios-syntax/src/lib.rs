@@ -2,13 +2,15 @@ mod lang;
 mod repr;
 
 use helios_formatting::FormattedString;
+use helios_locale::Locale;
 use std::fmt::{self, Display};
 
 pub use crate::lang::HeliosLanguage;
-use crate::repr::{Article, HumanReadableRepr};
+pub use crate::repr::{Article, HumanReadableRepr};
 
 pub type SyntaxNode = rowan::SyntaxNode<HeliosLanguage>;
 pub type SyntaxToken = rowan::SyntaxToken<HeliosLanguage>;
+pub type SyntaxNodePtr = rowan::ast::SyntaxNodePtr<HeliosLanguage>;
 
 /// A convenient way to construct new `SyntaxNode` symbols.
 ///
@@ -181,6 +183,13 @@ impl SyntaxKind {
         matches!(self, Comment | DocComment | Whitespace | Newline)
     }
 
+    /// Determines if the [`SyntaxKind`] is a layout token (i.e. a token that
+    /// marks the start or end of an indented block).
+    #[inline]
+    pub fn is_layout(self) -> bool {
+        matches!(self, SyntaxKind::Indent | SyntaxKind::Dedent)
+    }
+
     /// Determines if the [`SyntaxKind`] is a keyword.
     #[inline]
     pub fn is_keyword(self) -> bool {
@@ -218,17 +227,67 @@ impl SyntaxKind {
         self == SyntaxKind::Identifier || self == SyntaxKind::ReservedIdentifier
     }
 
+    /// Describes the [`SyntaxKind`] for humans in the current locale (e.g.
+    /// "an opening parenthesis symbol (`(`)").
     pub fn human_readable_repr(self) -> HumanReadableRepr {
+        self.human_readable_repr_in(Locale::current())
+    }
+
+    /// Describes the [`SyntaxKind`] for humans in the given locale.
+    pub fn human_readable_repr_in(self, locale: Locale) -> HumanReadableRepr {
         HumanReadableRepr {
-            article: self.article(),
-            qualifier: self.qualifier(),
-            description: self.description(),
-            kind: self.kind(),
+            locale,
+            article: self.article_in(locale),
+            name: self.name_in(locale),
             code_repr: self.code_repr(),
             example: self.example(),
         }
     }
 
+    /// The id of the message that names the [`SyntaxKind`] in the catalogs of
+    /// `helios-locale` (e.g. `syntax-sym-lparen`).
+    fn message_id(self) -> String {
+        if self.is_keyword() {
+            return "syntax-keyword".to_string();
+        }
+
+        let name = format!("{self:?}").to_lowercase().replace('_', "-");
+        format!("syntax-{name}")
+    }
+
+    /// The article that goes before the name of the [`SyntaxKind`] in the
+    /// given locale (e.g. "an").
+    pub fn article_in(self, locale: Locale) -> String {
+        locale.article(&self.message_id())
+    }
+
+    /// The name of the [`SyntaxKind`] in the given locale, without an article
+    /// (e.g. "opening parenthesis symbol").
+    pub fn name_in(self, locale: Locale) -> String {
+        let keyword = self.keyword().unwrap_or_default();
+        locale.text(&self.message_id(), &[("keyword", keyword.into())])
+    }
+
+    /// The name of the general kind of the [`SyntaxKind`] in the given locale
+    /// (e.g. "symbol" for every symbol), or its own name if it doesn't belong
+    /// to one.
+    pub fn category_in(self, locale: Locale) -> String {
+        let id = match self {
+            kind if kind.is_keyword() => "category-keyword",
+            kind if kind.is_symbol() => "category-symbol",
+            kind if kind.is_literal() => "category-literal",
+            kind if kind.is_expression() => "category-expression",
+            kind if kind.is_declaration() => "category-declaration",
+            kind if kind.is_comment() => "category-comment",
+            kind if kind.is_identifier() => "category-identifier",
+            kind => return kind.name_in(locale),
+        };
+
+        locale.text(id, &[])
+    }
+
+    /// The English article of the [`SyntaxKind`].
+    #[deprecated(note = "use `SyntaxKind::article_in` with a locale")]
     pub fn article(self) -> Article {
         match self {
             kind if kind.is_keyword() => Article::The,
@@ -253,6 +312,8 @@ impl SyntaxKind {
         }
     }
 
+    /// The English qualifier of the [`SyntaxKind`] (e.g. "opening"), if any.
+    #[deprecated(note = "use `SyntaxKind::name_in` with a locale")]
     pub fn qualifier(self) -> Option<String> {
         let s = match self {
             SyntaxKind::Sym_LBrace => "opening curly",
@@ -267,34 +328,15 @@ impl SyntaxKind {
         Some(s.to_string())
     }
 
+    /// The English description of the [`SyntaxKind`], between its qualifier
+    /// and its kind (e.g. "parenthesis").
+    #[deprecated(note = "use `SyntaxKind::name_in` with a locale")]
     pub fn description(self) -> Option<String> {
+        if let Some(keyword) = self.keyword() {
+            return Some(keyword.to_string());
+        }
+
         let s = match self {
-            // keywords
-            SyntaxKind::Kwd_And => "and",
-            SyntaxKind::Kwd_As => "as",
-            SyntaxKind::Kwd_Case => "case",
-            SyntaxKind::Kwd_Else => "else",
-            SyntaxKind::Kwd_Enum => "enum",
-            SyntaxKind::Kwd_For => "for",
-            SyntaxKind::Kwd_Forall => "forall",
-            SyntaxKind::Kwd_Func => "func",
-            SyntaxKind::Kwd_If => "if",
-            SyntaxKind::Kwd_Impl => "impl",
-            SyntaxKind::Kwd_Import => "import",
-            SyntaxKind::Kwd_In => "in",
-            SyntaxKind::Kwd_Iter => "iter",
-            SyntaxKind::Kwd_Let => "let",
-            SyntaxKind::Kwd_Module => "module",
-            SyntaxKind::Kwd_Not => "not",
-            SyntaxKind::Kwd_Of => "of",
-            SyntaxKind::Kwd_Or => "or",
-            SyntaxKind::Kwd_Range => "range",
-            SyntaxKind::Kwd_Record => "record",
-            SyntaxKind::Kwd_Type => "type",
-            SyntaxKind::Kwd_Var => "var",
-            SyntaxKind::Kwd_While => "while",
-            SyntaxKind::Kwd_With => "with",
-            SyntaxKind::Kwd_Yield => "yield",
             // symbols
             SyntaxKind::Sym_Ampersand => "ampersand",
             SyntaxKind::Sym_Asterisk => "asterisk",
@@ -354,6 +396,8 @@ impl SyntaxKind {
         Some(s.to_string())
     }
 
+    /// The English name of the general kind of the [`SyntaxKind`].
+    #[deprecated(note = "use `SyntaxKind::category_in` with a locale")]
     pub fn kind(self) -> String {
         let s = match self {
             kind if kind.is_keyword() => "keyword",
@@ -376,6 +420,40 @@ impl SyntaxKind {
         s.to_string()
     }
 
+    /// The text of the keyword, if the [`SyntaxKind`] is one.
+    pub fn keyword(self) -> Option<&'static str> {
+        let s = match self {
+            SyntaxKind::Kwd_And => "and",
+            SyntaxKind::Kwd_As => "as",
+            SyntaxKind::Kwd_Case => "case",
+            SyntaxKind::Kwd_Else => "else",
+            SyntaxKind::Kwd_Enum => "enum",
+            SyntaxKind::Kwd_For => "for",
+            SyntaxKind::Kwd_Forall => "forall",
+            SyntaxKind::Kwd_Func => "func",
+            SyntaxKind::Kwd_If => "if",
+            SyntaxKind::Kwd_Impl => "impl",
+            SyntaxKind::Kwd_Import => "import",
+            SyntaxKind::Kwd_In => "in",
+            SyntaxKind::Kwd_Iter => "iter",
+            SyntaxKind::Kwd_Let => "let",
+            SyntaxKind::Kwd_Module => "module",
+            SyntaxKind::Kwd_Not => "not",
+            SyntaxKind::Kwd_Of => "of",
+            SyntaxKind::Kwd_Or => "or",
+            SyntaxKind::Kwd_Range => "range",
+            SyntaxKind::Kwd_Record => "record",
+            SyntaxKind::Kwd_Type => "type",
+            SyntaxKind::Kwd_Var => "var",
+            SyntaxKind::Kwd_While => "while",
+            SyntaxKind::Kwd_With => "with",
+            SyntaxKind::Kwd_Yield => "yield",
+            _ => return None,
+        };
+
+        Some(s)
+    }
+
     pub fn code_repr(self) -> Option<String> {
         let s = match self {
             SyntaxKind::Sym_Ampersand => "&",
@@ -616,7 +694,8 @@ mod tests {
     #[test]
     fn test_syntax_kind_human_readable_repr() {
         fn check(kind: SyntaxKind, input: impl Into<String>) {
-            assert_eq!(format!("{}", kind.human_readable_repr()), input.into());
+            let repr = kind.human_readable_repr_in(Locale::EnUs);
+            assert_eq!(format!("{repr}"), input.into());
         }
 
         use SyntaxKind::*;
@@ -670,4 +749,43 @@ mod tests {
         check(Placeholder, "a placeholder");
         check(Error, "an error");
     }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_syntax_kind_deprecated_parts() {
+        use SyntaxKind::*;
+        assert_eq!(Sym_LParen.article(), Article::An);
+        assert_eq!(Kwd_Let.article(), Article::The);
+        assert_eq!(Sym_LParen.qualifier().as_deref(), Some("opening"));
+        assert_eq!(Sym_LParen.description().as_deref(), Some("parenthesis"));
+        assert_eq!(Sym_LParen.kind(), "symbol");
+        assert_eq!(Kwd_Let.description().as_deref(), Some("let"));
+        assert_eq!(Indent.description(), None);
+        assert_eq!(Indent.kind(), "indent");
+    }
+
+    #[test]
+    fn test_syntax_kind_human_readable_repr_in_french() {
+        fn check(kind: SyntaxKind, input: &str) {
+            let repr = kind.human_readable_repr_in(Locale::Fr);
+            assert_eq!(repr.to_string(), input);
+        }
+
+        use SyntaxKind::*;
+
+        check(Kwd_And, "le mot-clé and");
+        check(Sym_LParen, "un symbole parenthèse ouvrante (`(`)");
+        check(Lit_Integer, "un littéral entier (comme `123`)");
+        check(Exp_Binary, "une expression binaire");
+        check(Newline, "une nouvelle ligne");
+        check(Identifier, "un identifiant (comme `foo`)");
+    }
+
+    #[test]
+    fn test_syntax_kind_category() {
+        assert_eq!(SyntaxKind::Kwd_Let.category_in(Locale::EnUs), "keyword");
+        assert_eq!(SyntaxKind::DocComment.category_in(Locale::EnUs), "comment");
+        assert_eq!(SyntaxKind::Newline.category_in(Locale::EnUs), "new line");
+        assert_eq!(SyntaxKind::Sym_At.category_in(Locale::Fr), "symbole");
+    }
 }
@@ -1,6 +1,9 @@
-use helios_formatting::{FormattedString, FormattedStringSegment};
+use helios_formatting::FormattedString;
+use helios_locale::{Arg, Locale};
 use std::fmt::{self, Display};
 
+/// An English article, as returned by the deprecated
+/// [`SyntaxKind::article`](crate::SyntaxKind::article).
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Article {
     A,
@@ -18,70 +21,47 @@ impl Display for Article {
     }
 }
 
+/// The description of a [`SyntaxKind`](crate::SyntaxKind) for humans, in a
+/// particular locale.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct HumanReadableRepr {
-    pub article: Article,
-    pub qualifier: Option<String>,
-    pub description: Option<String>,
-    pub kind: String,
+    pub locale: Locale,
+    pub article: String,
+    pub name: String,
     pub code_repr: Option<String>,
     pub example: Option<String>,
 }
 
 impl From<HumanReadableRepr> for FormattedString {
     fn from(repr: HumanReadableRepr) -> Self {
-        let mut formatted = FormattedString::new();
-
-        formatted.push(repr.article.to_string() + " ");
-
-        if let Some(qualifier) = repr.qualifier {
-            formatted.push(qualifier + " ");
-        }
-
-        if let Some(description) = repr.description {
-            formatted.push(description + " ");
-        }
-
-        formatted.push(repr.kind);
-
-        if let Some(code_repr) = repr.code_repr {
-            formatted.push(" (");
-            formatted.push(FormattedStringSegment::code(code_repr));
-            formatted.push(")");
-        }
-
-        if let Some(example) = repr.example {
-            formatted.push(" (such as ");
-            formatted.push(FormattedStringSegment::code(example));
-            formatted.push(")");
+        let locale = repr.locale;
+        let phrase = locale.formatted(
+            "syntax-phrase",
+            &[("article", repr.article.into()), ("kind", repr.name.into())],
+        );
+
+        match (repr.code_repr, repr.example) {
+            (Some(code), _) => locale.formatted(
+                "syntax-with-code",
+                &[("phrase", phrase.into()), ("code", code.into())],
+            ),
+            (None, Some(example)) => locale.formatted(
+                "syntax-with-example",
+                &[("phrase", phrase.into()), ("example", example.into())],
+            ),
+            (None, None) => phrase,
         }
+    }
+}
 
-        formatted
+impl From<HumanReadableRepr> for Arg {
+    fn from(repr: HumanReadableRepr) -> Self {
+        Arg::Formatted(repr.into())
     }
 }
 
 impl Display for HumanReadableRepr {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{} ", self.article)?;
-
-        if let Some(qualifier) = &self.qualifier {
-            write!(f, "{qualifier} ")?;
-        }
-
-        if let Some(description) = &self.description {
-            write!(f, "{description} ")?;
-        }
-
-        write!(f, "{}", self.kind)?;
-
-        if let Some(code_repr) = &self.code_repr {
-            write!(f, " (`{code_repr}`)")?;
-        }
-
-        if let Some(example) = &self.example {
-            write!(f, " (such as `{example}`)")?;
-        }
-
-        Ok(())
+        write!(f, "{}", FormattedString::from(self.clone()))
     }
 }
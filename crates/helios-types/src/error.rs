@@ -0,0 +1,65 @@
+use crate::ty::rename_vars;
+use crate::Ty;
+use helios_diagnostics::{codes, Diagnostic, Location};
+use helios_hir::{DeclId, ExprId};
+use helios_locale::Locale;
+
+/// An expression whose type isn't the one its context requires.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeError {
+    /// The declaration the expression is in.
+    pub decl: DeclId,
+    pub expr: ExprId,
+    pub kind: TypeErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeErrorKind {
+    Mismatch {
+        expected: Ty,
+        found: Ty,
+    },
+    /// The expression is the operand of an arithmetic or ordering operator,
+    /// but isn't a number.
+    NotNumeric {
+        found: Ty,
+    },
+}
+
+impl TypeError {
+    /// Generates the diagnostic of the error, pointing at the given location
+    /// (i.e. the location of its expression).
+    pub fn diagnostic<FileId>(
+        &self,
+        location: Location<FileId>,
+        locale: Locale,
+    ) -> Diagnostic<FileId>
+    where
+        FileId: Default,
+    {
+        let description = locale.formatted("e0007-description", &[]);
+
+        let message = match &self.kind {
+            TypeErrorKind::Mismatch { expected, found } => {
+                let tys = rename_vars([expected.clone(), found.clone()]);
+                locale
+                    .formatted("e0007-expected", &[])
+                    .code_block(tys[0].to_string())
+                    .text(locale.text("e0007-found", &[]))
+                    .code_block(tys[1].to_string())
+            }
+            TypeErrorKind::NotNumeric { found } => {
+                let found = &rename_vars([found.clone()])[0];
+                locale
+                    .formatted("e0007-not-numeric", &[])
+                    .code_block(found.to_string())
+            }
+        };
+
+        Diagnostic::error(locale.text("e0007-title", &[]))
+            .code(codes::MISMATCHED_TYPES)
+            .location(location)
+            .description(description)
+            .message(message)
+    }
+}
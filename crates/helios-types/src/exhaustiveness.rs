@@ -0,0 +1,622 @@
+//! Checking that the arms of a `match` cover every value, and only once.
+//!
+//! [`check_match`] implements the usefulness algorithm from Luc Maranget's
+//! "Warnings for pattern matching". Nothing calls it yet, as the grammar
+//! doesn't have enums or `match` expressions.
+
+use helios_hir::arena::{Arena, Idx};
+use helios_hir::Name;
+use std::fmt::{self, Display};
+
+pub type EnumId = Idx<EnumDef>;
+
+/// An enum type (e.g. `type Shape = enum { case Circle(Float) case Point }`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnumDef {
+    pub name: Name,
+    pub variants: Vec<VariantDef>,
+}
+
+/// A case of an enum, along with the types of its fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantDef {
+    pub name: Name,
+    pub fields: Vec<MatchTy>,
+}
+
+/// The type of the values a pattern is matched against.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatchTy {
+    /// A type with too many values to list them all (e.g. `Int`), which can
+    /// only be covered by a wildcard.
+    Opaque,
+    Bool,
+    /// An enum, defined in the arena given to [`check_match`] (so that enums
+    /// can refer to themselves).
+    Enum(EnumId),
+    Tuple(Vec<MatchTy>),
+    /// A record, whose fields are in the order they're declared.
+    Record(Vec<(Name, MatchTy)>),
+}
+
+/// A pattern, as written in an arm of a `match`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatchPat {
+    /// A pattern that matches every value (i.e. `_`, or a name bound to the
+    /// whole value).
+    Wildcard,
+    Bool(bool),
+    /// A literal of an opaque type (e.g. `42`).
+    Literal(String),
+    Variant {
+        name: Name,
+        fields: Vec<MatchPat>,
+    },
+    Tuple(Vec<MatchPat>),
+    /// A record pattern, where the fields that are left out match every
+    /// value.
+    Record(Vec<(Name, MatchPat)>),
+    /// A pattern that matches the values of any of its alternatives (e.g.
+    /// `Circle(_) | Point`).
+    Or(Vec<MatchPat>),
+}
+
+impl Display for MatchPat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list(
+            f: &mut fmt::Formatter<'_>,
+            items: impl IntoIterator<Item = impl Display>,
+            separator: &str,
+        ) -> fmt::Result {
+            for (index, item) in items.into_iter().enumerate() {
+                if index > 0 {
+                    f.write_str(separator)?;
+                }
+                write!(f, "{item}")?;
+            }
+            Ok(())
+        }
+
+        match self {
+            MatchPat::Wildcard => f.write_str("_"),
+            MatchPat::Bool(value) => write!(f, "{value}"),
+            MatchPat::Literal(text) => f.write_str(text),
+            MatchPat::Variant { name, fields } if fields.is_empty() => {
+                write!(f, "{name}")
+            }
+            MatchPat::Variant { name, fields } => {
+                write!(f, "{name}(")?;
+                list(f, fields, ", ")?;
+                f.write_str(")")
+            }
+            MatchPat::Tuple(fields) => {
+                f.write_str("(")?;
+                list(f, fields, ", ")?;
+                f.write_str(")")
+            }
+            MatchPat::Record(fields) if fields.is_empty() => f.write_str("{}"),
+            MatchPat::Record(fields) => {
+                let fields =
+                    fields.iter().map(|(name, pat)| format!("{name}: {pat}"));
+                f.write_str("{ ")?;
+                list(f, fields, ", ")?;
+                f.write_str(" }")
+            }
+            MatchPat::Or(alternatives) => list(f, alternatives, " | "),
+        }
+    }
+}
+
+/// What's wrong with the arms of a `match`, if anything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchCheck {
+    /// Examples of the values that aren't matched by any arm (which is empty
+    /// if the `match` is exhaustive).
+    pub missing: Vec<MatchPat>,
+    /// The indices of the arms that can't be reached.
+    pub unreachable_arms: Vec<usize>,
+}
+
+impl MatchCheck {
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.unreachable_arms.is_empty()
+    }
+}
+
+/// Checks the patterns of the arms of a `match` against the type of the value
+/// it's applied to.
+///
+/// The patterns must already be known to have the given type (e.g. a variant
+/// pattern must name a variant of the enum).
+pub fn check_match(
+    enums: &Arena<EnumDef>,
+    ty: &MatchTy,
+    arms: &[MatchPat],
+) -> MatchCheck {
+    let checker = Checker { enums };
+    let tys = [ty.clone()];
+    let mut rows: Vec<Row> = Vec::new();
+    let mut check = MatchCheck::default();
+
+    for (index, arm) in arms.iter().enumerate() {
+        let row = vec![arm.clone()];
+        if checker.usefulness(&rows, &row, &tys).is_empty() {
+            check.unreachable_arms.push(index);
+        }
+        rows.push(row);
+    }
+
+    check.missing = checker
+        .usefulness(&rows, &[MatchPat::Wildcard], &tys)
+        .into_iter()
+        .map(|mut witness| witness.remove(0))
+        .collect();
+    check
+}
+
+/// A row of the pattern matrix: the patterns of an arm, once its outer
+/// patterns are taken apart into the patterns of their fields.
+type Row = Vec<MatchPat>;
+
+/// What a value is built with, which a pattern can check for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Ctor {
+    /// The only constructor of a tuple or record.
+    Single,
+    Bool(bool),
+    Variant(usize),
+    Literal(String),
+}
+
+struct Checker<'a> {
+    enums: &'a Arena<EnumDef>,
+}
+
+impl Checker<'_> {
+    /// The values matched by the given row but not by any row of the matrix
+    /// (as rows of patterns), where `tys` are the types of the columns.
+    fn usefulness(
+        &self,
+        matrix: &[Row],
+        row: &[MatchPat],
+        tys: &[MatchTy],
+    ) -> Vec<Row> {
+        let (head, ty) = match (row.first(), tys.first()) {
+            (Some(head), Some(ty)) => (head, ty),
+            // Every column is matched, so the row is useful only if nothing
+            // else matches
+            _ => {
+                return match matrix.is_empty() {
+                    true => vec![Vec::new()],
+                    false => Vec::new(),
+                };
+            }
+        };
+
+        if let MatchPat::Or(alternatives) = head {
+            return alternatives
+                .iter()
+                .flat_map(|alternative| {
+                    let mut row = row.to_vec();
+                    row[0] = alternative.clone();
+                    self.usefulness(matrix, &row, tys)
+                })
+                .collect();
+        }
+
+        if let Some(ctor) = self.ctor(head, ty) {
+            return self.usefulness_of_ctor(matrix, row, tys, &ctor);
+        }
+
+        // The row starts with a wildcard, so it's useful if it's useful for
+        // any constructor of the type
+        let mut used = Vec::new();
+        for matrix_row in matrix {
+            for row in expand_or(matrix_row) {
+                if let Some(ctor) = self.ctor(&row[0], ty) {
+                    if !used.contains(&ctor) {
+                        used.push(ctor);
+                    }
+                }
+            }
+        }
+
+        let all = self.all_ctors(ty);
+        if let Some(all) = &all {
+            if all.iter().all(|ctor| used.contains(ctor)) {
+                return all
+                    .iter()
+                    .flat_map(|ctor| {
+                        self.usefulness_of_ctor(matrix, row, tys, ctor)
+                    })
+                    .collect();
+            }
+        }
+
+        // Some constructors aren't matched by the matrix, so the row is
+        // useful for them as long as it's useful for the rest of its columns
+        let default = matrix
+            .iter()
+            .flat_map(|row| expand_or(row))
+            .filter(|row| self.ctor(&row[0], ty).is_none())
+            .map(|row| row[1..].to_vec())
+            .collect::<Vec<_>>();
+        let witnesses = self.usefulness(&default, &row[1..], &tys[1..]);
+
+        // The missing constructors are only listed if some are matched, as
+        // listing all of them wouldn't be any clearer than a wildcard
+        let heads = match all {
+            Some(all) if !used.is_empty() => all
+                .iter()
+                .filter(|ctor| !used.contains(ctor))
+                .map(|ctor| {
+                    let arity = self.field_tys(ty, ctor).len();
+                    self.ctor_pat(ctor, ty, vec![MatchPat::Wildcard; arity])
+                })
+                .collect(),
+            _ => vec![MatchPat::Wildcard],
+        };
+
+        heads
+            .iter()
+            .flat_map(|head| {
+                witnesses.iter().map(move |witness| {
+                    let mut row = vec![head.clone()];
+                    row.extend(witness.iter().cloned());
+                    row
+                })
+            })
+            .collect()
+    }
+
+    /// The values built with the given constructor that are matched by the
+    /// row but not by the matrix.
+    fn usefulness_of_ctor(
+        &self,
+        matrix: &[Row],
+        row: &[MatchPat],
+        tys: &[MatchTy],
+        ctor: &Ctor,
+    ) -> Vec<Row> {
+        let field_tys = self.field_tys(&tys[0], ctor);
+        let arity = field_tys.len();
+
+        let matrix = matrix
+            .iter()
+            .flat_map(|row| self.specialize(row, ctor, &tys[0]))
+            .collect::<Vec<_>>();
+        let mut tys_of_row = field_tys;
+        tys_of_row.extend(tys[1..].iter().cloned());
+
+        self.specialize(row, ctor, &tys[0])
+            .iter()
+            .flat_map(|row| self.usefulness(&matrix, row, &tys_of_row))
+            .map(|mut witness| {
+                let rest = witness.split_off(arity);
+                let mut row = vec![self.ctor_pat(ctor, &tys[0], witness)];
+                row.extend(rest);
+                row
+            })
+            .collect()
+    }
+
+    /// The rows to check once the first column of the given row is known to
+    /// be built with the given constructor: none if it's built with another
+    /// one, or the row with the patterns of the fields in place of the first
+    /// pattern.
+    fn specialize(
+        &self,
+        row: &[MatchPat],
+        ctor: &Ctor,
+        ty: &MatchTy,
+    ) -> Vec<Row> {
+        expand_or(row)
+            .into_iter()
+            .filter_map(|row| {
+                let fields = match self.ctor(&row[0], ty) {
+                    Some(it) if it != *ctor => return None,
+                    _ => self.fields(&row[0], ctor, ty),
+                };
+                let mut specialized = fields;
+                specialized.extend(row[1..].iter().cloned());
+                Some(specialized)
+            })
+            .collect()
+    }
+
+    /// The constructor checked by a pattern, or `None` if it's a wildcard.
+    fn ctor(&self, pat: &MatchPat, ty: &MatchTy) -> Option<Ctor> {
+        match pat {
+            MatchPat::Wildcard | MatchPat::Or(_) => None,
+            MatchPat::Bool(value) => Some(Ctor::Bool(*value)),
+            MatchPat::Literal(text) => Some(Ctor::Literal(text.clone())),
+            MatchPat::Variant { name, .. } => {
+                let MatchTy::Enum(id) = ty else {
+                    return None;
+                };
+                self.enums[*id]
+                    .variants
+                    .iter()
+                    .position(|variant| variant.name == *name)
+                    .map(Ctor::Variant)
+            }
+            MatchPat::Tuple(_) | MatchPat::Record(_) => Some(Ctor::Single),
+        }
+    }
+
+    /// Every constructor of a type, or `None` if there are too many to list
+    /// them.
+    fn all_ctors(&self, ty: &MatchTy) -> Option<Vec<Ctor>> {
+        match ty {
+            MatchTy::Opaque => None,
+            MatchTy::Bool => Some(vec![Ctor::Bool(false), Ctor::Bool(true)]),
+            MatchTy::Enum(id) => Some(
+                (0..self.enums[*id].variants.len())
+                    .map(Ctor::Variant)
+                    .collect(),
+            ),
+            MatchTy::Tuple(_) | MatchTy::Record(_) => Some(vec![Ctor::Single]),
+        }
+    }
+
+    /// The types of the fields of the values built with a constructor.
+    fn field_tys(&self, ty: &MatchTy, ctor: &Ctor) -> Vec<MatchTy> {
+        match (ty, ctor) {
+            (MatchTy::Tuple(tys), Ctor::Single) => tys.clone(),
+            (MatchTy::Record(fields), Ctor::Single) => {
+                fields.iter().map(|(_, ty)| ty.clone()).collect()
+            }
+            (MatchTy::Enum(id), Ctor::Variant(index)) => {
+                self.enums[*id].variants[*index].fields.clone()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The patterns of the fields of a pattern built with the constructor (in
+    /// the order of the fields of its type).
+    fn fields(
+        &self,
+        pat: &MatchPat,
+        ctor: &Ctor,
+        ty: &MatchTy,
+    ) -> Vec<MatchPat> {
+        match (pat, ty) {
+            (MatchPat::Variant { fields, .. }, _)
+            | (MatchPat::Tuple(fields), _) => fields.clone(),
+            (MatchPat::Record(fields), MatchTy::Record(field_tys)) => field_tys
+                .iter()
+                .map(|(name, _)| {
+                    fields
+                        .iter()
+                        .find(|(field, _)| field == name)
+                        .map_or(MatchPat::Wildcard, |(_, pat)| pat.clone())
+                })
+                .collect(),
+            _ => vec![MatchPat::Wildcard; self.field_tys(ty, ctor).len()],
+        }
+    }
+
+    /// The pattern built with a constructor and the patterns of its fields.
+    fn ctor_pat(
+        &self,
+        ctor: &Ctor,
+        ty: &MatchTy,
+        fields: Vec<MatchPat>,
+    ) -> MatchPat {
+        match (ctor, ty) {
+            (Ctor::Bool(value), _) => MatchPat::Bool(*value),
+            (Ctor::Literal(text), _) => MatchPat::Literal(text.clone()),
+            (Ctor::Variant(index), MatchTy::Enum(id)) => MatchPat::Variant {
+                name: self.enums[*id].variants[*index].name.clone(),
+                fields,
+            },
+            (Ctor::Single, MatchTy::Record(field_tys)) => MatchPat::Record(
+                field_tys
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .zip(fields)
+                    .collect(),
+            ),
+            _ => MatchPat::Tuple(fields),
+        }
+    }
+}
+
+/// The rows matching the same values as the given row, without or-patterns
+/// in their first column.
+fn expand_or(row: &[MatchPat]) -> Vec<Row> {
+    match row.first() {
+        Some(MatchPat::Or(alternatives)) => alternatives
+            .iter()
+            .flat_map(|alternative| {
+                let mut row = row.to_vec();
+                row[0] = alternative.clone();
+                expand_or(&row)
+            })
+            .collect(),
+        _ => vec![row.to_vec()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A context with the `Shape` and `List` enums:
+    ///
+    /// ```text
+    /// type Shape = enum { case Circle(Float) case Square(Float) case Point }
+    /// type List = enum { case Nil case Cons(Bool, List) }
+    /// ```
+    fn enums() -> (Arena<EnumDef>, EnumId, EnumId) {
+        let mut enums = Arena::new();
+        let variant = |name: &str, fields| VariantDef {
+            name: Name::new(name),
+            fields,
+        };
+
+        let shape = enums.alloc(EnumDef {
+            name: Name::new("Shape"),
+            variants: vec![
+                variant("Circle", vec![MatchTy::Opaque]),
+                variant("Square", vec![MatchTy::Opaque]),
+                variant("Point", vec![]),
+            ],
+        });
+        let list = enums.alloc(EnumDef {
+            name: Name::new("List"),
+            variants: Vec::new(),
+        });
+        enums[list].variants = vec![
+            variant("Nil", vec![]),
+            variant("Cons", vec![MatchTy::Bool, MatchTy::Enum(list)]),
+        ];
+
+        (enums, shape, list)
+    }
+
+    fn variant(name: &str, fields: Vec<MatchPat>) -> MatchPat {
+        MatchPat::Variant {
+            name: Name::new(name),
+            fields,
+        }
+    }
+
+    /// Checks the arms and returns the missing patterns as text, along with
+    /// the unreachable arms.
+    fn check(
+        enums: &Arena<EnumDef>,
+        ty: MatchTy,
+        arms: &[MatchPat],
+    ) -> (Vec<String>, Vec<usize>) {
+        let check = check_match(enums, &ty, arms);
+        let missing = check.missing.iter().map(|pat| pat.to_string());
+        (missing.collect(), check.unreachable_arms)
+    }
+
+    #[test]
+    fn test_enum_variants() {
+        let (enums, shape, _) = enums();
+        let shape = MatchTy::Enum(shape);
+        let circle = variant("Circle", vec![MatchPat::Wildcard]);
+        let square = variant("Square", vec![MatchPat::Wildcard]);
+        let point = variant("Point", vec![]);
+
+        assert_eq!(
+            check(&enums, shape.clone(), std::slice::from_ref(&circle)),
+            (vec!["Square(_)".to_string(), "Point".to_string()], vec![])
+        );
+        assert_eq!(
+            check(&enums, shape.clone(), &[circle.clone(), square, point]),
+            (vec![], vec![])
+        );
+        assert_eq!(
+            check(&enums, shape.clone(), &[MatchPat::Wildcard, circle]),
+            (vec![], vec![1])
+        );
+        assert_eq!(check(&enums, shape, &[]), (vec!["_".to_string()], vec![]));
+    }
+
+    #[test]
+    fn test_literals_need_a_wildcard() {
+        let (enums, shape, _) = enums();
+        let circle = |radius: &str| {
+            variant("Circle", vec![MatchPat::Literal(radius.to_string())])
+        };
+        let arms = [
+            circle("1.0"),
+            circle("2.0"),
+            circle("1.0"),
+            variant("Square", vec![MatchPat::Wildcard]),
+            variant("Point", vec![]),
+        ];
+
+        assert_eq!(
+            check(&enums, MatchTy::Enum(shape), &arms),
+            (vec!["Circle(_)".to_string()], vec![2])
+        );
+    }
+
+    #[test]
+    fn test_tuples_of_bools() {
+        let (enums, ..) = enums();
+        let ty = MatchTy::Tuple(vec![MatchTy::Bool, MatchTy::Bool]);
+        let pair = |a, b| MatchPat::Tuple(vec![a, b]);
+        let arms = [
+            pair(MatchPat::Bool(true), MatchPat::Wildcard),
+            pair(MatchPat::Wildcard, MatchPat::Bool(true)),
+        ];
+
+        assert_eq!(
+            check(&enums, ty.clone(), &arms),
+            (vec!["(false, false)".to_string()], vec![])
+        );
+
+        let arms = [
+            pair(MatchPat::Bool(true), MatchPat::Bool(true)),
+            pair(MatchPat::Bool(false), MatchPat::Wildcard),
+            pair(MatchPat::Wildcard, MatchPat::Bool(true)),
+        ];
+        assert_eq!(
+            check(&enums, ty, &arms),
+            (vec!["(true, false)".to_string()], vec![2])
+        );
+    }
+
+    #[test]
+    fn test_records() {
+        let (enums, ..) = enums();
+        let ty = MatchTy::Record(vec![
+            (Name::new("visible"), MatchTy::Bool),
+            (Name::new("size"), MatchTy::Opaque),
+        ]);
+        let visible = |value| {
+            MatchPat::Record(vec![(
+                Name::new("visible"),
+                MatchPat::Bool(value),
+            )])
+        };
+
+        assert_eq!(
+            check(&enums, ty.clone(), &[visible(true)]),
+            (vec!["{ visible: false, size: _ }".to_string()], vec![])
+        );
+        assert_eq!(
+            check(&enums, ty, &[visible(true), visible(false)]),
+            (vec![], vec![])
+        );
+    }
+
+    #[test]
+    fn test_or_patterns() {
+        let (enums, shape, _) = enums();
+        let shapes = MatchPat::Or(vec![
+            variant("Circle", vec![MatchPat::Wildcard]),
+            variant("Point", vec![]),
+        ]);
+        let arms = [shapes, variant("Point", vec![])];
+
+        assert_eq!(
+            check(&enums, MatchTy::Enum(shape), &arms),
+            (vec!["Square(_)".to_string()], vec![1])
+        );
+    }
+
+    #[test]
+    fn test_recursive_enums() {
+        let (enums, _, list) = enums();
+        let nil = variant("Nil", vec![]);
+        let cons = |head, tail| variant("Cons", vec![head, tail]);
+        let arms = [
+            nil.clone(),
+            cons(MatchPat::Bool(true), MatchPat::Wildcard),
+            cons(MatchPat::Bool(false), nil),
+        ];
+
+        assert_eq!(
+            check(&enums, MatchTy::Enum(list), &arms),
+            (vec!["Cons(false, Cons(_, _))".to_string()], vec![])
+        );
+    }
+}
@@ -0,0 +1,501 @@
+//! Inferring the types of the declarations of a file.
+//!
+//! Mutually dependent bindings are inferred together, after the bindings they
+//! depend on, and are only generic outside of their own group.
+
+use crate::unify::Unifier;
+use crate::{Scheme, Ty, TyVar, TypeError, TypeErrorKind};
+use helios_hir::arena::ArenaMap;
+use helios_hir::{
+    BinaryOp, Body, DeclId, Expr, ExprId, Literal, Module, Pat, PatId, UnaryOp,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// The types of the declarations of a file and of everything in them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InferenceResult {
+    decls: HashMap<DeclId, Scheme>,
+    bodies: HashMap<DeclId, BodyTypes>,
+    /// The errors found while inferring the types, in the order of the
+    /// declarations they're in.
+    pub errors: Vec<TypeError>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct BodyTypes {
+    exprs: ArenaMap<Expr, Ty>,
+    pats: ArenaMap<Pat, Ty>,
+}
+
+impl InferenceResult {
+    /// The type of a declaration.
+    pub fn type_of_decl(&self, decl: DeclId) -> Option<&Scheme> {
+        self.decls.get(&decl)
+    }
+
+    /// The type of an expression of a declaration.
+    pub fn type_of_expr(&self, decl: DeclId, expr: ExprId) -> Option<&Ty> {
+        self.bodies.get(&decl)?.exprs.get(expr)
+    }
+
+    /// The type of a pattern of a declaration.
+    pub fn type_of_pat(&self, decl: DeclId, pat: PatId) -> Option<&Ty> {
+        self.bodies.get(&decl)?.pats.get(pat)
+    }
+}
+
+/// The declarations the variables of a body refer to (the variables that
+/// don't refer to any are left out).
+pub type ResolvedVariables = ArenaMap<Expr, DeclId>;
+
+/// Infers the types of the declarations of a module, given functions that
+/// return the body of each one and the declarations its variables refer to.
+pub fn infer_module(
+    module: &Module,
+    body_of: &dyn Fn(DeclId) -> Arc<Body>,
+    variables_of: &dyn Fn(DeclId) -> Arc<ResolvedVariables>,
+) -> InferenceResult {
+    let bodies = module
+        .decls()
+        .map(|(decl, _)| (decl, body_of(decl)))
+        .collect::<HashMap<_, _>>();
+    let variables = module
+        .decls()
+        .map(|(decl, _)| (decl, variables_of(decl)))
+        .collect::<HashMap<_, _>>();
+
+    let dependencies = module
+        .decls()
+        .map(|(decl, _)| {
+            let body = &bodies[&decl];
+            let dependencies = body
+                .exprs
+                .iter()
+                .filter_map(|(expr, _)| variables[&decl].get(expr).copied())
+                .collect::<Vec<_>>();
+            (decl, dependencies)
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut ctx = InferenceContext {
+        unifier: Unifier::new(),
+        variables: &variables,
+        schemes: HashMap::new(),
+        monomorphic: HashMap::new(),
+        errors: Vec::new(),
+    };
+
+    let decls = module.decls().map(|(decl, _)| decl).collect::<Vec<_>>();
+    let mut body_types = HashMap::new();
+    for group in dependency_groups(&decls, &dependencies) {
+        for &decl in &group {
+            let ty = ctx.unifier.new_var();
+            ctx.monomorphic.insert(decl, ty);
+        }
+        for &decl in &group {
+            let types = ctx.infer_body(decl, &bodies[&decl]);
+            body_types.insert(decl, types);
+        }
+        for decl in group {
+            let ty = ctx.monomorphic.remove(&decl).unwrap();
+            let scheme = ctx.unifier.generalize(&ty, &ctx.monomorphic_vars());
+            ctx.schemes.insert(decl, scheme);
+        }
+    }
+
+    let mut result = InferenceResult::default();
+    for (decl, _) in module.decls() {
+        result
+            .decls
+            .insert(decl, ctx.schemes.remove(&decl).unwrap());
+
+        let types = &body_types[&decl];
+        let mut resolved = BodyTypes::default();
+        for (expr, ty) in &types.exprs {
+            resolved.exprs.insert(*expr, ctx.unifier.resolve(ty));
+        }
+        for (pat, ty) in &types.pats {
+            resolved.pats.insert(*pat, ctx.unifier.resolve(ty));
+        }
+        result.bodies.insert(decl, resolved);
+    }
+
+    result.errors = ctx.errors;
+    result
+        .errors
+        .sort_by_key(|error| module.position(error.decl));
+    result
+}
+
+struct InferenceContext<'a> {
+    unifier: Unifier,
+    variables: &'a HashMap<DeclId, Arc<ResolvedVariables>>,
+    /// The types of the declarations that are generalized.
+    schemes: HashMap<DeclId, Scheme>,
+    /// The types of the declarations being inferred, which can't be generic
+    /// in their own group.
+    monomorphic: HashMap<DeclId, Ty>,
+    errors: Vec<TypeError>,
+}
+
+/// The types of the nodes of a body, before the variables are resolved.
+#[derive(Default)]
+struct UnresolvedTypes {
+    exprs: Vec<(ExprId, Ty)>,
+    pats: Vec<(PatId, Ty)>,
+}
+
+impl InferenceContext<'_> {
+    fn infer_body(&mut self, decl: DeclId, body: &Body) -> UnresolvedTypes {
+        let mut types = UnresolvedTypes::default();
+        let value = self.infer_expr(decl, body, body.value, &mut types);
+
+        let ty = self.monomorphic[&decl].clone();
+        if let Pat::Bind(_) = body.pats[body.binding] {
+            // The variable of a declaration is new, so this can't fail
+            let _ = self.unifier.unify(&ty, &value);
+        }
+        types.pats.push((body.binding, ty));
+        types
+    }
+
+    fn infer_expr(
+        &mut self,
+        decl: DeclId,
+        body: &Body,
+        expr: ExprId,
+        types: &mut UnresolvedTypes,
+    ) -> Ty {
+        let ty = match &body.exprs[expr] {
+            Expr::Missing => Ty::Error,
+            Expr::Literal(Literal::Integer(_)) => Ty::Int,
+            Expr::Literal(Literal::Float(_)) => Ty::Float,
+            Expr::Variable(_) => match self.variables[&decl].get(expr) {
+                Some(target) => match self.monomorphic.get(target) {
+                    Some(ty) => ty.clone(),
+                    None => self.unifier.instantiate(&self.schemes[target]),
+                },
+                // Name resolution already reported it
+                None => Ty::Error,
+            },
+            Expr::Unary { op, operand } => {
+                let ty = self.infer_expr(decl, body, *operand, types);
+                match op {
+                    UnaryOp::Neg => {
+                        match self.require_numeric(decl, *operand, &ty) {
+                            true => ty,
+                            false => Ty::Error,
+                        }
+                    }
+                    UnaryOp::Not => {
+                        self.expect(decl, *operand, &ty, &Ty::Bool);
+                        Ty::Bool
+                    }
+                }
+            }
+            Expr::Binary { op, lhs, rhs } => {
+                let lhs_ty = self.infer_expr(decl, body, *lhs, types);
+                let rhs_ty = self.infer_expr(decl, body, *rhs, types);
+                self.infer_binary(decl, *op, (*lhs, lhs_ty), (*rhs, rhs_ty))
+            }
+        };
+
+        types.exprs.push((expr, ty.clone()));
+        ty
+    }
+
+    fn infer_binary(
+        &mut self,
+        decl: DeclId,
+        op: BinaryOp,
+        (lhs, lhs_ty): (ExprId, Ty),
+        (rhs, rhs_ty): (ExprId, Ty),
+    ) -> Ty {
+        use BinaryOp::*;
+
+        // The operands of arithmetic and ordering operators must be numbers of
+        // the same type. The left one is checked first, so that the right one
+        // is expected to have the type of the left one.
+        if let Add | Sub | Mul | Div | Lt | LtEq | Gt | GtEq = op {
+            if !self.require_numeric(decl, lhs, &lhs_ty) {
+                return match op {
+                    Add | Sub | Mul | Div => Ty::Error,
+                    _ => Ty::Bool,
+                };
+            }
+        }
+        if op != Seq {
+            self.expect(decl, rhs, &rhs_ty, &lhs_ty);
+        }
+
+        match op {
+            Add | Sub | Mul | Div => lhs_ty,
+            Eq | NotEq | Lt | LtEq | Gt | GtEq => Ty::Bool,
+            Assign => Ty::Unit,
+            Seq => rhs_ty,
+        }
+    }
+
+    /// Requires an expression to have the expected type, reporting an error
+    /// if it doesn't.
+    fn expect(&mut self, decl: DeclId, expr: ExprId, ty: &Ty, expected: &Ty) {
+        if self.unifier.unify(expected, ty).is_err() {
+            let kind = TypeErrorKind::Mismatch {
+                expected: self.unifier.resolve(expected),
+                found: self.unifier.resolve(ty),
+            };
+            self.errors.push(TypeError { decl, expr, kind });
+        }
+    }
+
+    /// Requires an expression to be a number, reporting an error (and
+    /// returning `false`) if it isn't.
+    fn require_numeric(&mut self, decl: DeclId, expr: ExprId, ty: &Ty) -> bool {
+        if self.unifier.require_numeric(ty).is_ok() {
+            return true;
+        }
+        let kind = TypeErrorKind::NotNumeric {
+            found: self.unifier.resolve(ty),
+        };
+        self.errors.push(TypeError { decl, expr, kind });
+        false
+    }
+
+    /// The type variables of the declarations that aren't generalized yet.
+    fn monomorphic_vars(&self) -> Vec<TyVar> {
+        let mut vars = Vec::new();
+        for ty in self.monomorphic.values() {
+            self.unifier
+                .resolve(ty)
+                .walk_vars(&mut |var| vars.push(var));
+        }
+        vars
+    }
+}
+
+/// Groups the declarations that depend on each other (i.e. the strongly
+/// connected components of the dependency graph), ordered so that every group
+/// comes after the groups it depends on.
+fn dependency_groups(
+    decls: &[DeclId],
+    dependencies: &HashMap<DeclId, Vec<DeclId>>,
+) -> Vec<Vec<DeclId>> {
+    // Tarjan's algorithm, which finds a group only once every group it
+    // depends on is found. It keeps its own stack of the declarations being
+    // visited (with the index of their next dependency), so that long chains
+    // of dependencies don't overflow the call stack.
+    let mut index = HashMap::new();
+    let mut low_link = HashMap::new();
+    let mut stack = Vec::new();
+    let mut groups = Vec::new();
+
+    for &root in decls {
+        if index.contains_key(&root) {
+            continue;
+        }
+
+        let mut visiting = vec![(root, 0)];
+        let root_index = index.len();
+        index.insert(root, root_index);
+        low_link.insert(root, root_index);
+        stack.push(root);
+
+        while let Some((decl, next)) = visiting.last_mut() {
+            let decl = *decl;
+            if let Some(&dependency) = dependencies[&decl].get(*next) {
+                *next += 1;
+                if !index.contains_key(&dependency) {
+                    let dependency_index = index.len();
+                    index.insert(dependency, dependency_index);
+                    low_link.insert(dependency, dependency_index);
+                    stack.push(dependency);
+                    visiting.push((dependency, 0));
+                } else if stack.contains(&dependency) {
+                    let dependency_index = index[&dependency];
+                    let entry = low_link.get_mut(&decl).unwrap();
+                    *entry = (*entry).min(dependency_index);
+                }
+                continue;
+            }
+
+            // Every dependency was visited
+            visiting.pop();
+            if low_link[&decl] == index[&decl] {
+                let start = stack.iter().position(|&it| it == decl);
+                let mut group = stack.split_off(start.unwrap());
+                group.sort();
+                groups.push(group);
+            }
+            if let Some(&(parent, _)) = visiting.last() {
+                let decl_low_link = low_link[&decl];
+                let entry = low_link.get_mut(&parent).unwrap();
+                *entry = (*entry).min(decl_low_link);
+            }
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helios_hir::{lower_body, lower_module, DeclKind, Name};
+
+    /// Infers the types of the declarations of the source, returning the type
+    /// of each one along with the errors.
+    fn infer(source: &str) -> (Vec<String>, Vec<TypeError>) {
+        let root = helios_parser::parse((), source).syntax();
+        let (module, source_map) = lower_module(&root);
+        let bodies = module
+            .decls()
+            .map(|(decl, _)| {
+                let node = source_map.decl(decl).node.to_node(&root);
+                (decl, Arc::new(lower_body(&node).0))
+            })
+            .collect::<HashMap<_, _>>();
+
+        // A stand-in for name resolution, which is done by `helios-query`
+        let variables_of = |decl: DeclId| {
+            let mut variables = ResolvedVariables::default();
+            for (expr, data) in bodies[&decl].exprs.iter() {
+                if let Expr::Variable(name) = data {
+                    if let Some(target) =
+                        module.decls_named(name.as_str()).next()
+                    {
+                        variables.insert(expr, target);
+                    }
+                }
+            }
+            Arc::new(variables)
+        };
+
+        let result =
+            infer_module(&module, &|decl| bodies[&decl].clone(), &variables_of);
+        let types = module
+            .decls()
+            .map(|(decl, data)| {
+                let scheme = result.type_of_decl(decl).unwrap();
+                format!("{}: {scheme}", data.name)
+            })
+            .collect();
+        (types, result.errors)
+    }
+
+    #[test]
+    fn test_infer_literals_and_operators() {
+        let (types, errors) = infer(
+            "let a = 1\nlet b = 2.5 * -0.5\nlet c = a < 2\nlet d = !c\n\
+             let e = a <- 3\nlet f = e; b\n",
+        );
+        assert_eq!(
+            types,
+            [
+                "a: Int", "b: Float", "c: Bool", "d: Bool", "e: Unit",
+                "f: Float"
+            ]
+        );
+        assert_eq!(errors, []);
+    }
+
+    #[test]
+    fn test_infer_bindings_in_any_order() {
+        let (types, errors) = infer("let a = b + 1\nlet b = c\nlet c = 2\n");
+        assert_eq!(types, ["a: Int", "b: Int", "c: Int"]);
+        assert_eq!(errors, []);
+    }
+
+    #[test]
+    fn test_let_polymorphism() {
+        // `a` refers to itself, so nothing constrains its type: it's generic,
+        // so it can be used both as an `Int` and as a `Bool`
+        let (types, errors) = infer(
+            "let a = a\nlet b = a + 1\nlet c = !a\nlet d = -d\nlet e = d + 1.5\n",
+        );
+        assert_eq!(
+            types,
+            [
+                "a: forall a. a",
+                "b: Int",
+                "c: Bool",
+                "d: forall a: Num. a",
+                "e: Float"
+            ]
+        );
+        assert_eq!(errors, []);
+
+        // Generic numbers stay numbers
+        let (_, errors) = infer("let d = -d\nlet e = !d\n");
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_mutually_dependent_bindings_are_monomorphic() {
+        let (types, errors) = infer("let a = b\nlet b = a + 1\n");
+        assert_eq!(types, ["a: Int", "b: Int"]);
+        assert_eq!(errors, []);
+    }
+
+    #[test]
+    fn test_dependency_groups_of_long_chains() {
+        // Each declaration depends on the next one, and the last two depend
+        // on each other, which is too deep to visit recursively
+        let count = 100_000;
+        let decls = (0..count)
+            .map(|index| DeclId::new(DeclKind::Global, &Name::new("a"), index))
+            .collect::<Vec<_>>();
+        let mut dependencies = decls
+            .windows(2)
+            .map(|pair| (pair[0], vec![pair[1]]))
+            .collect::<HashMap<_, _>>();
+        let [.., second_last, last] = decls[..] else {
+            unreachable!()
+        };
+        dependencies.insert(last, vec![second_last]);
+
+        let groups = dependency_groups(&decls, &dependencies);
+        assert_eq!(groups.len(), decls.len() - 1);
+        assert_eq!(groups[0], [second_last, last]);
+        assert_eq!(groups[groups.len() - 1], [decls[0]]);
+    }
+
+    #[test]
+    fn test_type_errors() {
+        let (types, errors) =
+            infer("let a = 1 + 2.5\nlet b = (1 < 2) * 3\nlet c = !4\n");
+        assert_eq!(types, ["a: Int", "b: {unknown}", "c: Bool"]);
+
+        let decl = |name| DeclId::new(DeclKind::Global, &Name::new(name), 0);
+        let kinds = errors
+            .iter()
+            .map(|error| (error.decl, error.kind.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                (
+                    decl("a"),
+                    TypeErrorKind::Mismatch {
+                        expected: Ty::Int,
+                        found: Ty::Float
+                    }
+                ),
+                (decl("b"), TypeErrorKind::NotNumeric { found: Ty::Bool }),
+                (
+                    decl("c"),
+                    TypeErrorKind::Mismatch {
+                        expected: Ty::Bool,
+                        found: Ty::Int
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unresolved_names_have_unknown_types() {
+        let (types, errors) = infer("let a = b + 1\nlet c = !b\n");
+        assert_eq!(types, ["a: {unknown}", "c: Bool"]);
+        assert_eq!(errors, []);
+    }
+}
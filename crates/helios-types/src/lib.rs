@@ -0,0 +1,15 @@
+//! Inferring the types of Helios programs.
+//!
+//! Types are inferred from the HIR with the Hindley-Milner algorithm (see
+//! [`infer_module`]), so programs don't need any type annotation.
+
+pub mod error;
+pub mod exhaustiveness;
+pub mod infer;
+pub mod ty;
+pub mod unify;
+
+pub use crate::error::*;
+pub use crate::exhaustiveness::*;
+pub use crate::infer::*;
+pub use crate::ty::*;
@@ -0,0 +1,168 @@
+use helios_hir::Name;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display};
+
+/// A type variable, which stands for a type that isn't known yet (or, in a
+/// [`Scheme`], for any type).
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TyVar(pub u32);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Ty {
+    /// The type of an expression that couldn't be typed because of an error
+    /// reported elsewhere (e.g. an unresolved name). It's compatible with
+    /// every type, so that a single error isn't reported over and over.
+    Error,
+    Var(TyVar),
+    Unit,
+    Bool,
+    Int,
+    Float,
+    Function {
+        param: Box<Ty>,
+        ret: Box<Ty>,
+    },
+    /// A record, whose fields are sorted by name.
+    Record(BTreeMap<Name, Ty>),
+}
+
+impl Ty {
+    pub fn function(param: Ty, ret: Ty) -> Self {
+        Ty::Function {
+            param: Box::new(param),
+            ret: Box::new(ret),
+        }
+    }
+
+    pub fn record(fields: impl IntoIterator<Item = (Name, Ty)>) -> Self {
+        Ty::Record(fields.into_iter().collect())
+    }
+
+    /// Calls the given function on every type variable of the type, from
+    /// left to right.
+    pub fn walk_vars(&self, f: &mut impl FnMut(TyVar)) {
+        match self {
+            Ty::Var(var) => f(*var),
+            Ty::Function { param, ret } => {
+                param.walk_vars(f);
+                ret.walk_vars(f);
+            }
+            Ty::Record(fields) => {
+                for ty in fields.values() {
+                    ty.walk_vars(f);
+                }
+            }
+            Ty::Error | Ty::Unit | Ty::Bool | Ty::Int | Ty::Float => {}
+        }
+    }
+
+    /// Replaces every type variable of the type by the result of the given
+    /// function.
+    pub fn map_vars(&self, f: &mut impl FnMut(TyVar) -> Ty) -> Ty {
+        match self {
+            Ty::Var(var) => f(*var),
+            Ty::Function { param, ret } => {
+                Ty::function(param.map_vars(f), ret.map_vars(f))
+            }
+            Ty::Record(fields) => Ty::Record(
+                fields
+                    .iter()
+                    .map(|(name, ty)| (name.clone(), ty.map_vars(f)))
+                    .collect(),
+            ),
+            ty => ty.clone(),
+        }
+    }
+}
+
+/// Type variables are shown as letters (`a`, `b`, ..., `z`, `a1`, ...).
+impl Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ty::Error => f.write_str("{unknown}"),
+            Ty::Var(var) => {
+                let letter = (b'a' + (var.0 % 26) as u8) as char;
+                match var.0 / 26 {
+                    0 => write!(f, "{letter}"),
+                    n => write!(f, "{letter}{n}"),
+                }
+            }
+            Ty::Unit => f.write_str("Unit"),
+            Ty::Bool => f.write_str("Bool"),
+            Ty::Int => f.write_str("Int"),
+            Ty::Float => f.write_str("Float"),
+            Ty::Function { param, ret } => match **param {
+                Ty::Function { .. } => write!(f, "({param}) -> {ret}"),
+                _ => write!(f, "{param} -> {ret}"),
+            },
+            Ty::Record(fields) if fields.is_empty() => f.write_str("{}"),
+            Ty::Record(fields) => {
+                f.write_str("{ ")?;
+                for (index, (name, ty)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{name}: {ty}")?;
+                }
+                f.write_str(" }")
+            }
+        }
+    }
+}
+
+/// A type that may be generic over some of its type variables (e.g. the type
+/// of a binding that is used with different types).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scheme {
+    /// The type variables that can be replaced by any type.
+    pub vars: Vec<TyVar>,
+    /// The variables of `vars` that can only be replaced by numeric types.
+    pub numeric: Vec<TyVar>,
+    pub ty: Ty,
+}
+
+impl Scheme {
+    /// A scheme that isn't generic.
+    pub fn mono(ty: Ty) -> Self {
+        Self {
+            vars: Vec::new(),
+            numeric: Vec::new(),
+            ty,
+        }
+    }
+}
+
+/// Numeric variables are shown with a `Num` bound (e.g. `forall a: Num. a`).
+impl Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let vars = self.vars.iter().map(|&var| Ty::Var(var));
+        let renamed = rename_vars(vars.chain([self.ty.clone()]));
+        let (ty, vars) = renamed.split_last().unwrap();
+
+        if !vars.is_empty() {
+            f.write_str("forall")?;
+            for (original, var) in self.vars.iter().zip(vars) {
+                write!(f, " {var}")?;
+                if self.numeric.contains(original) {
+                    f.write_str(": Num")?;
+                }
+            }
+            f.write_str(". ")?;
+        }
+        write!(f, "{ty}")
+    }
+}
+
+/// Renames the type variables of the given types to `a`, `b`, `c`, ... in the
+/// order they first appear, so they're easier to read when shown together.
+pub fn rename_vars(tys: impl IntoIterator<Item = Ty>) -> Vec<Ty> {
+    let mut names = HashMap::new();
+    tys.into_iter()
+        .map(|ty| {
+            ty.map_vars(&mut |var| {
+                let next = TyVar(names.len() as u32);
+                Ty::Var(*names.entry(var).or_insert(next))
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,280 @@
+//! Solving the equations between types.
+//!
+//! The [`Unifier`] records the type each type variable is bound to. A variable
+//! can be restricted to numeric types, which is how `+` works on both `Int`
+//! and `Float`.
+
+use crate::{Scheme, Ty, TyVar};
+use std::collections::HashMap;
+
+/// Why two types couldn't be unified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnifyError {
+    /// The types are different (or one of them contains the other, which
+    /// would make an infinite type).
+    Mismatch,
+    /// A type was required to be numeric, but isn't.
+    NotNumeric,
+}
+
+#[derive(Clone, Debug)]
+enum VarState {
+    Unbound { numeric: bool },
+    Bound(Ty),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Unifier {
+    vars: Vec<VarState>,
+}
+
+impl Unifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a type variable that isn't bound to anything yet.
+    pub fn new_var(&mut self) -> Ty {
+        self.new_var_with(false)
+    }
+
+    /// Creates a type variable that can only be bound to a numeric type.
+    pub fn new_numeric_var(&mut self) -> Ty {
+        self.new_var_with(true)
+    }
+
+    fn new_var_with(&mut self, numeric: bool) -> Ty {
+        let var = TyVar(self.vars.len() as u32);
+        self.vars.push(VarState::Unbound { numeric });
+        Ty::Var(var)
+    }
+
+    /// Replaces every bound type variable of the given type by the type it's
+    /// bound to, recursively.
+    pub fn resolve(&self, ty: &Ty) -> Ty {
+        ty.map_vars(&mut |var| match &self.vars[var.0 as usize] {
+            VarState::Bound(ty) => self.resolve(ty),
+            VarState::Unbound { .. } => Ty::Var(var),
+        })
+    }
+
+    /// Makes both types the same, binding their variables if needed.
+    ///
+    /// The variables bound before a failure stay bound, which only affects
+    /// the types shown in later errors.
+    pub fn unify(&mut self, a: &Ty, b: &Ty) -> Result<(), UnifyError> {
+        let a = self.shallow_resolve(a);
+        let b = self.shallow_resolve(b);
+
+        match (&a, &b) {
+            (Ty::Var(a), Ty::Var(b)) if a == b => Ok(()),
+            // Variables are bound to errors too, so that what depends on them
+            // is unknown as well (instead of generic)
+            (Ty::Var(var), ty) | (ty, Ty::Var(var)) => self.bind(*var, ty),
+            (Ty::Error, _) | (_, Ty::Error) => Ok(()),
+            (Ty::Unit, Ty::Unit)
+            | (Ty::Bool, Ty::Bool)
+            | (Ty::Int, Ty::Int)
+            | (Ty::Float, Ty::Float) => Ok(()),
+            (
+                Ty::Function { param, ret },
+                Ty::Function {
+                    param: other_param,
+                    ret: other_ret,
+                },
+            ) => {
+                self.unify(param, other_param)?;
+                self.unify(ret, other_ret)
+            }
+            (Ty::Record(fields), Ty::Record(other_fields))
+                if fields.keys().eq(other_fields.keys()) =>
+            {
+                for (ty, other_ty) in fields.values().zip(other_fields.values())
+                {
+                    self.unify(ty, other_ty)?;
+                }
+                Ok(())
+            }
+            _ => Err(UnifyError::Mismatch),
+        }
+    }
+
+    /// Requires the given type to be numeric (i.e. `Int` or `Float`, or a
+    /// variable that can only be bound to them).
+    pub fn require_numeric(&mut self, ty: &Ty) -> Result<(), UnifyError> {
+        match self.shallow_resolve(ty) {
+            Ty::Error | Ty::Int | Ty::Float => Ok(()),
+            Ty::Var(var) => {
+                self.vars[var.0 as usize] = VarState::Unbound { numeric: true };
+                Ok(())
+            }
+            _ => Err(UnifyError::NotNumeric),
+        }
+    }
+
+    /// Replaces the generic variables of a scheme by new variables.
+    pub fn instantiate(&mut self, scheme: &Scheme) -> Ty {
+        let fresh = scheme
+            .vars
+            .iter()
+            .map(|&var| {
+                let numeric = scheme.numeric.contains(&var);
+                (var, self.new_var_with(numeric))
+            })
+            .collect::<HashMap<_, _>>();
+
+        scheme.ty.map_vars(&mut |var| match fresh.get(&var) {
+            Some(ty) => ty.clone(),
+            None => Ty::Var(var),
+        })
+    }
+
+    /// Makes a type generic over the variables that are still unbound, except
+    /// the given ones (e.g. those of bindings that aren't generalized yet).
+    pub fn generalize(&self, ty: &Ty, monomorphic: &[TyVar]) -> Scheme {
+        let ty = self.resolve(ty);
+        let mut vars = Vec::new();
+        ty.walk_vars(&mut |var| {
+            if !vars.contains(&var) && !monomorphic.contains(&var) {
+                vars.push(var);
+            }
+        });
+        let numeric = vars
+            .iter()
+            .copied()
+            .filter(|&var| self.is_numeric(var))
+            .collect();
+        Scheme { vars, numeric, ty }
+    }
+
+    fn is_numeric(&self, var: TyVar) -> bool {
+        matches!(
+            self.vars[var.0 as usize],
+            VarState::Unbound { numeric: true }
+        )
+    }
+
+    /// Follows the bindings of a variable until reaching a type that isn't a
+    /// bound variable.
+    fn shallow_resolve(&self, ty: &Ty) -> Ty {
+        match ty {
+            Ty::Var(var) => match &self.vars[var.0 as usize] {
+                VarState::Bound(ty) => self.shallow_resolve(ty),
+                VarState::Unbound { .. } => ty.clone(),
+            },
+            ty => ty.clone(),
+        }
+    }
+
+    fn bind(&mut self, var: TyVar, ty: &Ty) -> Result<(), UnifyError> {
+        if self.is_numeric(var) {
+            self.require_numeric(ty)?;
+        }
+        if !matches!(ty, Ty::Var(_)) && self.occurs(var, ty) {
+            return Err(UnifyError::Mismatch);
+        }
+        self.vars[var.0 as usize] = VarState::Bound(ty.clone());
+        Ok(())
+    }
+
+    fn occurs(&self, var: TyVar, ty: &Ty) -> bool {
+        let mut occurs = false;
+        self.resolve(ty).walk_vars(&mut |it| occurs |= it == var);
+        occurs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helios_hir::Name;
+
+    #[test]
+    fn test_unify_simple_types() {
+        let mut unifier = Unifier::new();
+        let a = unifier.new_var();
+
+        assert_eq!(unifier.unify(&Ty::Int, &Ty::Int), Ok(()));
+        assert_eq!(
+            unifier.unify(&Ty::Int, &Ty::Bool),
+            Err(UnifyError::Mismatch)
+        );
+        assert_eq!(unifier.unify(&a, &Ty::Float), Ok(()));
+        assert_eq!(unifier.resolve(&a), Ty::Float);
+        assert_eq!(unifier.unify(&Ty::Error, &Ty::Bool), Ok(()));
+    }
+
+    #[test]
+    fn test_unify_functions_and_records() {
+        let mut unifier = Unifier::new();
+        let a = unifier.new_var();
+        let b = unifier.new_var();
+
+        let f = Ty::function(a.clone(), Ty::Bool);
+        let g = Ty::function(Ty::Int, b.clone());
+        assert_eq!(unifier.unify(&f, &g), Ok(()));
+        assert_eq!(unifier.resolve(&f), Ty::function(Ty::Int, Ty::Bool));
+        assert_eq!(unifier.resolve(&f).to_string(), "Int -> Bool");
+
+        let point = |x: Ty| {
+            Ty::record([(Name::new("x"), x), (Name::new("y"), Ty::Float)])
+        };
+        let c = unifier.new_var();
+        assert_eq!(unifier.unify(&point(c.clone()), &point(Ty::Float)), Ok(()));
+        assert_eq!(unifier.resolve(&c), Ty::Float);
+        assert_eq!(point(Ty::Int).to_string(), "{ x: Int, y: Float }");
+
+        let other = Ty::record([(Name::new("x"), Ty::Int)]);
+        assert_eq!(
+            unifier.unify(&point(Ty::Int), &other),
+            Err(UnifyError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_occurs_check() {
+        let mut unifier = Unifier::new();
+        let a = unifier.new_var();
+        let f = Ty::function(a.clone(), Ty::Int);
+        assert_eq!(unifier.unify(&a, &f), Err(UnifyError::Mismatch));
+    }
+
+    #[test]
+    fn test_numeric_vars() {
+        let mut unifier = Unifier::new();
+        let a = unifier.new_numeric_var();
+        let b = unifier.new_var();
+
+        // Binding a numeric variable to another restricts the other one
+        assert_eq!(unifier.unify(&a, &b), Ok(()));
+        assert_eq!(unifier.unify(&b, &Ty::Bool), Err(UnifyError::NotNumeric));
+        assert_eq!(unifier.unify(&b, &Ty::Int), Ok(()));
+        assert_eq!(unifier.resolve(&a), Ty::Int);
+    }
+
+    #[test]
+    fn test_generalize_and_instantiate() {
+        let mut unifier = Unifier::new();
+        let a = unifier.new_var();
+        let scheme = unifier.generalize(&Ty::function(a.clone(), a), &[]);
+        assert_eq!(scheme.to_string(), "forall a. a -> a");
+
+        let first = unifier.instantiate(&scheme);
+        let second = unifier.instantiate(&scheme);
+        assert_ne!(first, second);
+
+        let identity_of = |ty: Ty| Ty::function(ty.clone(), ty);
+        assert_eq!(unifier.unify(&first, &identity_of(Ty::Int)), Ok(()));
+        assert_eq!(unifier.unify(&second, &identity_of(Ty::Bool)), Ok(()));
+
+        // Numeric variables stay numeric in every instance
+        let b = unifier.new_numeric_var();
+        let scheme = unifier.generalize(&Ty::function(b.clone(), b), &[]);
+        assert_eq!(scheme.to_string(), "forall a: Num. a -> a");
+        let instance = unifier.instantiate(&scheme);
+        assert_eq!(
+            unifier.unify(&instance, &identity_of(Ty::Bool)),
+            Err(UnifyError::NotNumeric)
+        );
+    }
+}
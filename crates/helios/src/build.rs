@@ -1,12 +1,83 @@
 use colored::*;
-use helios_diagnostics::{Diagnostic, ManyFiles, Severity};
+use helios_diagnostics::{
+    ColorChoice, Diagnostic, DiagnosticSink, EmitConfig, Level, Outcome,
+    SarifReport,
+};
+use helios_locale::Locale;
+use helios_query::{
+    FileId, HeliosDatabase, Input, SourceRoots, TypeInference, Vfs,
+};
+use notify::{RecursiveMode, Watcher};
+use std::collections::HashMap;
 use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
 
 /// Compiling support for Helios files
 #[derive(clap::Parser)]
 pub struct HeliosBuildOpts {
     /// The entry point file for the program to be built
     pub file: String,
+    /// The format of the diagnostics emitted during the build
+    #[clap(long, arg_enum, default_value = "human")]
+    pub message_format: MessageFormat,
+    /// The maximum number of errors to emit (or 0 to emit every error)
+    #[clap(long, default_value_t = 0)]
+    pub error_limit: usize,
+    /// Ignores the diagnostics with the given code
+    #[clap(short = 'A', long = "allow", value_name = "CODE")]
+    pub allow: Vec<String>,
+    /// Reports the diagnostics with the given code as warnings
+    #[clap(short = 'W', long = "warn", value_name = "CODE")]
+    pub warn: Vec<String>,
+    /// Reports the diagnostics with the given code (or every warning if the
+    /// code is `warnings`) as errors
+    #[clap(short = 'D', long = "deny", value_name = "CODE")]
+    pub deny: Vec<String>,
+    /// When to use colors (`always`, `never` or `auto`)
+    #[clap(long, default_value = "auto", value_name = "WHEN")]
+    pub color: ColorChoice,
+    /// The width diagnostics are wrapped at (defaults to the width of the
+    /// terminal)
+    #[clap(long)]
+    pub width: Option<usize>,
+    /// Checks every file in the directory of the entry point again whenever
+    /// one of them changes
+    #[clap(long)]
+    pub watch: bool,
+}
+
+impl HeliosBuildOpts {
+    /// Creates a sink with the error limit and severity overrides of these
+    /// options, where `--deny` wins over `--warn`, which wins over `--allow`.
+    fn sink<FileId: PartialEq>(&self) -> DiagnosticSink<FileId> {
+        let overrides = [
+            (&self.allow, Level::Allow),
+            (&self.warn, Level::Warn),
+            (&self.deny, Level::Deny),
+        ];
+
+        overrides.into_iter().fold(
+            DiagnosticSink::new().error_limit(self.error_limit),
+            |sink, (codes, level)| {
+                codes
+                    .iter()
+                    .fold(sink, |sink, code| sink.level(code, level))
+            },
+        )
+    }
+}
+
+/// The format diagnostics are emitted in.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MessageFormat {
+    /// Colored text meant to be read by humans
+    Human,
+    /// One JSON object per line for each diagnostic
+    Json,
+    /// A SARIF 2.1.0 report of every diagnostic
+    Sarif,
 }
 
 type Result<T> = std::result::Result<T, Error>;
@@ -25,75 +96,330 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<notify::Error> for Error {
+    fn from(error: notify::Error) -> Self {
+        Self::IoError(error.to_string())
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::BuildError(count) => {
-                let suffix = if *count == 1 { "" } else { "s" };
-                write!(
-                    f,
-                    "Failed to build due to {count} previous error{suffix}"
-                )
+                let args = [("count", (*count).into())];
+                f.write_str(&Locale::current().text("build-failed", &args))
             }
             Self::IoError(error) => {
-                write!(f, "Failed to build due to an IO error: {error}")
+                let args = [("error", error.as_str().into())];
+                f.write_str(&Locale::current().text("build-io-error", &args))
             }
         }
     }
 }
 
-fn __build(path: &str) -> Result<()> {
-    let source = std::fs::read_to_string(path)?;
+fn __build(opts: &HeliosBuildOpts) -> Result<()> {
+    if opts.watch {
+        return watch(opts);
+    }
+
+    let path = opts.file.as_str();
+
+    // Every phase of the build is a query of the database, whose inputs are
+    // the files loaded into the VFS
+    let mut vfs = Vfs::new();
+    let mut db = HeliosDatabase::default();
+    let file_id = vfs.load_file(path)?;
+    if vfs.file_contents(file_id).is_none() {
+        return Err(Error::IoError(format!("No such file: `{path}`")));
+    }
+    vfs.apply_changes(&mut db);
+
+    if opts.message_format == MessageFormat::Human {
+        println!("{}", db.parse(file_id).debug_tree().cyan());
+    }
+
+    let outcome = emit_diagnostics(
+        opts,
+        &db,
+        db.all_diagnostics(file_id).iter().cloned(),
+    );
+
+    // The build fails if any error (or bug) was reported, even if it wasn't
+    // emitted because of the error limit
+    if outcome.is_success() {
+        Ok(())
+    } else {
+        Err(Error::BuildError(outcome.errors + outcome.bugs))
+    }
+}
+
+/// Emits the given diagnostics in the format of the options, once they went
+/// through the sink of the options.
+fn emit_diagnostics(
+    opts: &HeliosBuildOpts,
+    db: &HeliosDatabase,
+    diagnostics: impl IntoIterator<Item = Diagnostic<FileId>>,
+) -> Outcome {
+    let message_format = opts.message_format;
     let mut stdout = std::io::stdout();
-    let mut files = ManyFiles::new();
 
-    let file_id = files.add(path, source);
-    let file = files.get(file_id).unwrap();
+    let mut sink = opts.sink();
+    sink.extend(diagnostics);
+    let (diagnostics, outcome) = sink.finish();
+
+    let mut report = SarifReport::new("helios", env!("CARGO_PKG_VERSION"));
+    let config = EmitConfig::new().color(opts.color).width(opts.width);
+
+    for diagnostic in &diagnostics {
+        match message_format {
+            MessageFormat::Human => {
+                helios_diagnostics::emit(&mut stdout, db, diagnostic, &config)
+            }
+            MessageFormat::Json => {
+                helios_diagnostics::emit_json(&mut stdout, db, diagnostic)
+            }
+            MessageFormat::Sarif => report.add(db, diagnostic),
+        }
+        .expect("Failed to print diagnostic");
+    }
+
+    if message_format == MessageFormat::Sarif {
+        report
+            .write(&mut stdout)
+            .expect("Failed to print SARIF report");
+    }
 
-    let parse = helios_parser::parse(file_id, file.source());
-    println!("{}", parse.debug_tree().cyan());
+    if message_format == MessageFormat::Human && outcome.omitted > 0 {
+        let note = Locale::current().text(
+            "build-errors-omitted",
+            &[
+                ("count", outcome.omitted.into()),
+                ("limit", opts.error_limit.into()),
+            ],
+        );
+        println!("{}\n", note.dimmed());
+    }
 
-    let mut emitted_ranges = Vec::new();
-    let mut severities = Vec::new();
+    outcome
+}
 
-    for message in parse.messages() {
-        let diagnostic = Diagnostic::from(message);
-        severities.push(diagnostic.severity);
+/// What tells a diagnostic apart from the others of its file in [`watch`]:
+/// everything but its byte offsets, which change whenever text is added or
+/// removed above it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DiagnosticKey {
+    code: Option<&'static str>,
+    title: String,
+    message: String,
+    /// The offset of the diagnostic from the start of its line.
+    column: usize,
+    /// The text the diagnostic points at.
+    text: String,
+}
+
+impl DiagnosticKey {
+    fn new(source: &str, diagnostic: &Diagnostic<FileId>) -> Self {
+        let range = &diagnostic.location.range;
+        let line_start = source
+            .get(..range.start)
+            .and_then(|before| before.rfind('\n'))
+            .map_or(0, |index| index + 1);
 
-        if !(emitted_ranges.contains(&diagnostic.location)) {
-            emitted_ranges.push(diagnostic.location.clone());
-            helios_diagnostics::emit(&mut stdout, &files, &diagnostic)
-                .expect("Failed to print diagnostic");
+        Self {
+            code: diagnostic.code,
+            title: diagnostic.title.clone(),
+            message: diagnostic.message.render_plain(),
+            column: range.start.saturating_sub(line_start),
+            text: source.get(range.clone()).unwrap_or_default().to_string(),
         }
     }
+}
 
-    // An empty vector (i.e. no messages to report) or a vector of severities
-    // lower in importance than error is okay
-    let is_ok = {
-        severities.is_empty()
-            || severities
-                .iter()
-                .any(|severity| *severity < Severity::Error)
+/// Checks every file of the directory of the entry point, then checks them
+/// again whenever one of them changes (until the process is interrupted).
+///
+/// Only the diagnostics that weren't emitted by the previous pass are emitted
+/// (compared by their [`DiagnosticKey`], so that the diagnostics that only
+/// moved aren't emitted again), and the queries of the files that didn't
+/// change are never recomputed.
+fn watch(opts: &HeliosBuildOpts) -> Result<()> {
+    let entry = Path::new(&opts.file);
+    if !entry.is_file() {
+        return Err(Error::IoError(format!("No such file: `{}`", opts.file)));
+    }
+
+    let root_path = match entry.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
     };
+    // Events are reported with absolute paths, which are turned back into
+    // paths relative to the root as it was given
+    let canonical_root = root_path.canonicalize()?;
 
-    let message_count = emitted_ranges.len();
+    let mut vfs = Vfs::new();
+    let mut db = HeliosDatabase::default();
+    let root_id = vfs.load_root(root_path)?;
+    vfs.apply_changes(&mut db);
 
-    if is_ok {
-        Ok(())
-    } else {
-        Err(Error::BuildError(message_count))
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(&canonical_root, RecursiveMode::Recursive)?;
+
+    let mut previous = HashMap::<FileId, Vec<DiagnosticKey>>::new();
+    let mut changed_files = db.files_in_root(root_id).len();
+
+    loop {
+        let start = Instant::now();
+        let mut current = HashMap::new();
+        let (mut new, mut fixed) = (Vec::new(), 0);
+
+        for file_id in db.files_in_root(root_id).iter().copied() {
+            let source = db.source(file_id);
+            let diagnostics = db.all_diagnostics(file_id);
+            let keys = diagnostics
+                .iter()
+                .map(|diagnostic| DiagnosticKey::new(&source, diagnostic))
+                .collect::<Vec<_>>();
+            let old = previous.remove(&file_id).unwrap_or_default();
+            if old != keys {
+                new.extend(
+                    diagnostics
+                        .iter()
+                        .zip(&keys)
+                        .filter(|(_, key)| !old.contains(key))
+                        .map(|(diagnostic, _)| diagnostic.clone()),
+                );
+                fixed += old.iter().filter(|key| !keys.contains(key)).count();
+            }
+            current.insert(file_id, keys);
+        }
+
+        // The diagnostics of deleted files are gone too
+        fixed += previous.values().map(|it| it.len()).sum::<usize>();
+        previous = current;
+
+        let elapsed = start.elapsed();
+        let outcome = emit_diagnostics(opts, &db, new);
+        let new_count =
+            outcome.bugs + outcome.errors + outcome.warnings + outcome.notes;
+
+        let summary = Locale::current().text(
+            "build-watch-summary",
+            &[
+                ("count", changed_files.into()),
+                ("elapsed", format!("{elapsed:.2?}").into()),
+                ("new", new_count.into()),
+                ("fixed", fixed.into()),
+            ],
+        );
+        // Nothing but the diagnostics is printed to stdout in the other
+        // formats
+        if opts.message_format == MessageFormat::Human {
+            println!("{}\n", summary.dimmed());
+        } else {
+            eprintln!("{summary}");
+        }
+
+        changed_files = 0;
+        while changed_files == 0 {
+            for path in wait_for_changes(&receiver)? {
+                let path = match path.strip_prefix(&canonical_root) {
+                    Ok(relative_path) => root_path.join(relative_path),
+                    Err(_) => continue,
+                };
+
+                // A directory that was moved or deleted has its files read
+                // again too, and a file that can't be read doesn't stop the
+                // build from being watched
+                for (path, error) in vfs.load_path(&path) {
+                    let message = Locale::current().text(
+                        "build-watch-read-error",
+                        &[
+                            ("path", path.display().to_string().into()),
+                            ("error", error.to_string().into()),
+                        ],
+                    );
+                    eprintln!("{message}");
+                }
+            }
+
+            changed_files = vfs.apply_changes(&mut db).len();
+        }
     }
 }
 
-/// Starts the build process with the given path to a file.
-pub fn build(path: &str) {
-    println!("\n{} {}\n", "Building".green().bold(), path.underline());
+/// Blocks until the watcher reports an event, then collects the paths of the
+/// events that follow it closely (as saving a file usually triggers several
+/// events at once).
+fn wait_for_changes(
+    receiver: &Receiver<notify::Result<notify::Event>>,
+) -> Result<Vec<PathBuf>> {
+    const DEBOUNCE: Duration = Duration::from_millis(50);
+
+    let first = receiver.recv().map_err(|error| {
+        Error::IoError(format!("The file watcher stopped: {error}"))
+    })?;
+
+    let mut paths = Vec::new();
+    let events = std::iter::once(first)
+        .chain(std::iter::from_fn(|| receiver.recv_timeout(DEBOUNCE).ok()));
+    for event in events {
+        paths.extend(event?.paths);
+    }
+
+    paths.sort();
+    paths.dedup();
+    Ok(paths)
+}
+
+/// Starts the build process with the given options.
+///
+/// Unless diagnostics are emitted in a human-readable format, nothing but the
+/// diagnostics is printed to stdout.
+pub fn build(opts: &HeliosBuildOpts) {
+    let path = opts.file.as_str();
+    let is_human = opts.message_format == MessageFormat::Human;
+
+    // The rest of the output (e.g. the banner) follows the same choice
+    match opts.color {
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+        ColorChoice::Auto => {}
+    }
+
+    if is_human {
+        println!("\n{} {}\n", "Building".green().bold(), path.underline());
+    }
 
-    if let Err(error) = __build(path) {
+    if let Err(error) = __build(opts) {
         let error = format!("{}", error).red().bold();
         eprintln!("{}", error);
         std::process::exit(1);
     }
 
-    println!("{}", "Finished building".green().bold());
+    if is_human {
+        println!("{}", "Finished building".green().bold());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helios_diagnostics::Location;
+
+    #[test]
+    fn test_diagnostic_key_ignores_offsets() {
+        let diagnostic = |range| {
+            Diagnostic::error("Unresolved name")
+                .location(Location::new(FileId(0), range))
+        };
+
+        let key = DiagnosticKey::new("let a = b\n", &diagnostic(8..9));
+        let moved =
+            DiagnosticKey::new("let c = 1\nlet a = b\n", &diagnostic(18..19));
+        assert_eq!(key, moved);
+
+        let changed = DiagnosticKey::new("let a = bc\n", &diagnostic(8..10));
+        assert_ne!(key, changed);
+    }
 }
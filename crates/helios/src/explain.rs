@@ -0,0 +1,33 @@
+use colored::*;
+use helios_diagnostics::codes;
+
+/// Detailed explanations of diagnostic codes
+#[derive(clap::Parser)]
+pub struct HeliosExplainOpts {
+    /// The code to explain (e.g. `E0001`)
+    pub code: String,
+}
+
+/// Prints the explanation of the given diagnostic code.
+pub fn explain(opts: &HeliosExplainOpts) {
+    match codes::explain(&opts.code) {
+        Some(explanation) => {
+            let header = format!("{}: {}", explanation.code, explanation.title);
+            println!("{}\n", header.bold());
+            let explanation = &explanation.explanation;
+            if colored::control::SHOULD_COLORIZE.should_colorize() {
+                println!("{}", explanation.render_ansi());
+            } else {
+                println!("{}", explanation.render_plain());
+            }
+        }
+        None => {
+            let error = format!("Unknown diagnostic code `{}`", opts.code);
+            eprintln!("{}", error.red().bold());
+
+            let known = codes::all().collect::<Vec<_>>().join(", ");
+            eprintln!("The known codes are: {known}");
+            std::process::exit(1);
+        }
+    }
+}
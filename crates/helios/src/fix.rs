@@ -0,0 +1,143 @@
+use colored::*;
+use helios_diagnostics::suggestion::apply_edits;
+use helios_diagnostics::Diagnostic;
+use std::fmt::Display;
+
+/// Applies the machine-applicable fixes suggested for Helios files
+#[derive(clap::Parser)]
+pub struct HeliosFixOpts {
+    /// The files to be fixed
+    #[clap(required = true)]
+    pub files: Vec<String>,
+    /// Checks if there are fixes to apply without writing to the files
+    #[clap(long)]
+    pub check: bool,
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Error {
+    CheckError(usize),
+    IoError(String),
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::IoError(error.to_string())
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CheckError(count) => {
+                let suffix = if *count == 1 { "" } else { "s" };
+                write!(f, "{count} file{suffix} would be fixed")
+            }
+            Self::IoError(error) => {
+                write!(f, "Failed to fix due to an IO error: {error}")
+            }
+        }
+    }
+}
+
+/// Applies the machine-applicable suggestions of the given diagnostics to the
+/// source text, returning the fixed text and the number of edits applied.
+///
+/// Edits that overlap an edit of an earlier suggestion are skipped, so they
+/// don't corrupt the source text. Running the fixes again after reparsing the
+/// fixed text will pick them up if they still apply.
+pub fn fix_source(
+    source: &str,
+    diagnostics: &[Diagnostic<()>],
+) -> (String, usize) {
+    let edits = diagnostics
+        .iter()
+        .flat_map(|diagnostic| &diagnostic.suggestions)
+        .filter(|suggestion| suggestion.is_machine_applicable())
+        .flat_map(|suggestion| &suggestion.edits)
+        .map(|edit| (edit.location.range.clone(), &*edit.replacement))
+        .collect::<Vec<_>>();
+
+    apply_edits(source, &edits)
+}
+
+fn __fix(opts: &HeliosFixOpts) -> Result<()> {
+    let mut unfixed_count = 0;
+
+    for path in &opts.files {
+        let source = std::fs::read_to_string(path)?;
+        let diagnostics = helios_parser::parse((), &source)
+            .messages()
+            .iter()
+            .map(Diagnostic::from)
+            .collect::<Vec<_>>();
+
+        let (fixed, count) = fix_source(&source, &diagnostics);
+        if count == 0 {
+            continue;
+        }
+
+        let suffix = if count == 1 { "" } else { "es" };
+        if opts.check {
+            println!(
+                "{} {path} ({count} fix{suffix})",
+                "Would fix".yellow().bold()
+            );
+            unfixed_count += 1;
+        } else {
+            std::fs::write(path, fixed)?;
+            println!("{} {path} ({count} fix{suffix})", "Fixed".green().bold());
+        }
+    }
+
+    if unfixed_count == 0 {
+        Ok(())
+    } else {
+        Err(Error::CheckError(unfixed_count))
+    }
+}
+
+/// Applies the machine-applicable fixes to the files, or checks if there are
+/// any to apply if `--check` was passed.
+pub fn fix(opts: &HeliosFixOpts) {
+    if let Err(error) = __fix(opts) {
+        let error = format!("{}", error).red().bold();
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(source: &str) -> (String, usize) {
+        let diagnostics = helios_parser::parse((), source)
+            .messages()
+            .iter()
+            .map(Diagnostic::from)
+            .collect::<Vec<_>>();
+        fix_source(source, &diagnostics)
+    }
+
+    #[test]
+    fn test_fix_unclosed_parentheses() {
+        assert_eq!(fix("((1 + 2) * 3"), ("((1 + 2) * 3)".to_string(), 1));
+        assert_eq!(fix("((1 # a\n"), ("((1)) # a\n".to_string(), 2));
+    }
+
+    #[test]
+    fn test_fix_keeps_balanced_parentheses() {
+        let source = "let a = (1 +\n  2)\n";
+        assert_eq!(fix(source), (source.to_string(), 0));
+    }
+
+    #[test]
+    fn test_fix_skips_uncertain_suggestions() {
+        assert_eq!(fix("let let = 1"), ("let let = 1".to_string(), 0));
+    }
+}
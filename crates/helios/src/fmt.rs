@@ -0,0 +1,92 @@
+use colored::*;
+use helios_fmt::FormatOptions;
+use std::fmt::Display;
+
+/// Formatting support for Helios files
+#[derive(clap::Parser)]
+pub struct HeliosFmtOpts {
+    /// The files to be formatted
+    #[clap(required = true)]
+    pub files: Vec<String>,
+    /// Checks if the files are formatted without writing to them
+    #[clap(long)]
+    pub check: bool,
+    /// The maximum width of each line
+    #[clap(long, default_value_t = 80)]
+    pub max_width: usize,
+    /// The number of spaces each block is indented by
+    #[clap(long, default_value_t = 4)]
+    pub indent_width: usize,
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Error {
+    CheckError(usize),
+    IoError(String),
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::IoError(error.to_string())
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CheckError(count) => {
+                let suffix = if *count == 1 { "" } else { "s" };
+                write!(f, "{count} file{suffix} would be reformatted")
+            }
+            Self::IoError(error) => {
+                write!(f, "Failed to format due to an IO error: {error}")
+            }
+        }
+    }
+}
+
+fn __fmt(opts: &HeliosFmtOpts) -> Result<()> {
+    let options = FormatOptions {
+        max_width: opts.max_width,
+        indent_width: opts.indent_width,
+    };
+
+    let mut unformatted_count = 0;
+
+    for path in &opts.files {
+        let source = std::fs::read_to_string(path)?;
+        let formatted = helios_fmt::format_source(&source, &options);
+
+        if formatted == source {
+            continue;
+        }
+
+        if opts.check {
+            println!("{} {}", "Would reformat".yellow().bold(), path);
+            unformatted_count += 1;
+        } else {
+            std::fs::write(path, formatted)?;
+            println!("{} {}", "Formatted".green().bold(), path);
+        }
+    }
+
+    if unformatted_count == 0 {
+        Ok(())
+    } else {
+        Err(Error::CheckError(unformatted_count))
+    }
+}
+
+/// Formats the files with the given options, or checks if they are formatted
+/// if `--check` was passed.
+pub fn fmt(opts: &HeliosFmtOpts) {
+    if let Err(error) = __fmt(opts) {
+        let error = format!("{}", error).red().bold();
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
@@ -1,2 +1,6 @@
 pub mod build;
+pub mod explain;
+pub mod fix;
+pub mod fmt;
+pub mod parse;
 pub mod repl;
@@ -1,7 +1,12 @@
 use clap::Parser;
 
 use helios::build::HeliosBuildOpts;
+use helios::explain::HeliosExplainOpts;
+use helios::fix::HeliosFixOpts;
+use helios::fmt::HeliosFmtOpts;
+use helios::parse::HeliosParseOpts;
 use helios::repl::HeliosReplOpts;
+use helios_locale::Locale;
 
 #[derive(Parser)]
 #[clap(version = "0.2.0")]
@@ -12,6 +17,10 @@ struct HeliosOpts {
     /// The verbosity of the output to stdout
     #[clap(short, long)]
     verbose: bool,
+    /// The language of the messages (e.g. `en-US` or `fr`), which defaults to
+    /// the one set by `LC_ALL`, `LC_MESSAGES` or `LANG`
+    #[clap(long, global = true)]
+    lang: Option<Locale>,
     /// Recognized subcommands
     #[clap(subcommand)]
     subcommand: HeliosSubcommand,
@@ -20,16 +29,37 @@ struct HeliosOpts {
 #[derive(Parser)]
 enum HeliosSubcommand {
     Build(HeliosBuildOpts),
+    Explain(HeliosExplainOpts),
+    Fix(HeliosFixOpts),
+    Fmt(HeliosFmtOpts),
+    Parse(HeliosParseOpts),
     Repl(HeliosReplOpts),
 }
 
 fn main() {
     env_logger::init();
     let opts = HeliosOpts::parse();
+    opts.lang.unwrap_or_else(Locale::from_env).set_current();
+
     match opts.subcommand {
         HeliosSubcommand::Build(build_opts) => {
             log::trace!("Starting build process...");
-            helios::build::build(&build_opts.file);
+            helios::build::build(&build_opts);
+        }
+        HeliosSubcommand::Explain(explain_opts) => {
+            helios::explain::explain(&explain_opts);
+        }
+        HeliosSubcommand::Fix(fix_opts) => {
+            log::trace!("Starting fixing process...");
+            helios::fix::fix(&fix_opts);
+        }
+        HeliosSubcommand::Fmt(fmt_opts) => {
+            log::trace!("Starting formatting process...");
+            helios::fmt::fmt(&fmt_opts);
+        }
+        HeliosSubcommand::Parse(parse_opts) => {
+            log::trace!("Starting parsing process...");
+            helios::parse::parse(&parse_opts);
         }
         HeliosSubcommand::Repl(_repl_opts) => {
             log::trace!("Starting new REPL session...");
@@ -0,0 +1,59 @@
+use colored::*;
+
+/// Parsing support for Helios files (useful for debugging the parser)
+#[derive(clap::Parser)]
+pub struct HeliosParseOpts {
+    /// The file to be parsed
+    pub file: String,
+    /// Traces every step taken by the parser instead of printing the syntax
+    /// tree
+    #[clap(long)]
+    pub trace: bool,
+    /// The format of the trace
+    #[clap(long, arg_enum, default_value = "log", requires = "trace")]
+    pub format: TraceFormat,
+    /// Writes the output to the given file instead of stdout
+    #[clap(short, long)]
+    pub output: Option<String>,
+}
+
+/// The format a trace of the parser is printed in.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TraceFormat {
+    /// One line for each step, indented by the depth of the node
+    Log,
+    /// A Graphviz graph of the nodes that were started
+    Dot,
+}
+
+fn __parse(opts: &HeliosParseOpts) -> std::io::Result<()> {
+    let source = std::fs::read_to_string(&opts.file)?;
+
+    let output = if opts.trace {
+        let (_, trace) = helios_parser::parse_with_trace((), &source);
+        match opts.format {
+            TraceFormat::Log => trace.render_log(),
+            TraceFormat::Dot => trace.render_dot(),
+        }
+    } else {
+        helios_parser::parse((), &source).debug_tree()
+    };
+
+    match &opts.output {
+        Some(path) => std::fs::write(path, output),
+        None => {
+            print!("{}", output);
+            Ok(())
+        }
+    }
+}
+
+/// Parses the file with the given options, printing either its syntax tree or
+/// a trace of the parser.
+pub fn parse(opts: &HeliosParseOpts) {
+    if let Err(error) = __parse(opts) {
+        let error = format!("Failed to parse due to an IO error: {}", error);
+        eprintln!("{}", error.red().bold());
+        std::process::exit(1);
+    }
+}
@@ -1,7 +1,7 @@
 //! REPL support for the Helios programming language.
 
 use colored::*;
-use helios_diagnostics::{Diagnostic, ManyFiles};
+use helios_diagnostics::{Diagnostic, DiagnosticSink, EmitConfig, ManyFiles};
 use std::io::{self, Write};
 
 const LOGO_BANNER: &[&str] = &[
@@ -60,6 +60,7 @@ fn start_main_loop() -> io::Result<()> {
 
     let mut input = String::new();
     let mut files = ManyFiles::new();
+    let config = EmitConfig::new();
 
     loop {
         write!(stdout, "{}", "> ".blue())?;
@@ -93,14 +94,17 @@ fn start_main_loop() -> io::Result<()> {
             let parse = helios_parser::parse(file_id, file.source());
             println!("{}", parse.debug_tree().cyan());
 
-            let mut emitted_ranges = Vec::new();
-            for message in parse.messages() {
-                let diagnostic = Diagnostic::from(message);
-                if !(emitted_ranges.contains(&diagnostic.location)) {
-                    emitted_ranges.push(diagnostic.location.clone());
-                    helios_diagnostics::emit(&mut stdout, &files, &diagnostic)
-                        .expect("Failed to print diagnostic");
-                }
+            let mut sink = DiagnosticSink::new();
+            sink.extend(parse.messages().iter().map(Diagnostic::from));
+
+            for diagnostic in sink.finish().0 {
+                helios_diagnostics::emit(
+                    &mut stdout,
+                    &files,
+                    &diagnostic,
+                    &config,
+                )
+                .expect("Failed to print diagnostic");
             }
         }
 